@@ -10,7 +10,7 @@ use anyhow::{Context, Result};
 use clap::ValueEnum;
 use rusqlite::Connection;
 
-use crate::model::Annotation;
+use crate::model::{rate, Annotation, CoverageData, FileCoverage, FileSummary, LineDetail};
 use crate::report::ReportFormatter;
 use crate::{db, diff, report};
 
@@ -19,6 +19,10 @@ use crate::{db, diff, report};
 pub enum Style {
     Text,
     Markdown,
+    /// Machine-readable `{covered, total, rate, per_file}` JSON, for CI
+    /// pipelines that gate on `covrs diff-coverage` without shell-parsing
+    /// human text (see [`report::JsonFormatter`]).
+    Json,
 }
 
 impl Style {
@@ -27,10 +31,56 @@ impl Style {
         match self {
             Style::Text => Box::new(report::TextFormatter),
             Style::Markdown => Box::new(report::MarkdownFormatter),
+            Style::Json => Box::new(report::JsonFormatter),
         }
     }
 }
 
+/// Output style for the per-file coverage summary table.
+#[derive(Clone, ValueEnum)]
+pub enum SummaryStyle {
+    Text,
+    Markdown,
+    /// Like `Text`, but with each percentage ANSI-colored by coverage band
+    /// for an at-a-glance terminal summary (`covrs summary --style color`).
+    Color,
+}
+
+impl SummaryStyle {
+    /// Get the formatter for this style.
+    pub fn formatter(&self) -> Box<dyn report::SummaryFormatter> {
+        match self {
+            SummaryStyle::Text => Box::new(report::TextSummaryFormatter),
+            SummaryStyle::Markdown => Box::new(report::MarkdownSummaryFormatter),
+            SummaryStyle::Color => Box::new(report::ColorSummaryFormatter),
+        }
+    }
+}
+
+/// Output format for the `export` command.
+#[derive(Clone, ValueEnum)]
+pub enum ExportFormat {
+    Lcov,
+    Cobertura,
+    /// Per-file, per-line hit-count JSON for GitLab's MR diff coverage view
+    /// (see [`crate::gitlab::line_coverage_json`]).
+    GitlabJson,
+}
+
+impl ExportFormat {
+    /// Render `data` in this format (see [`crate::parsers::lcov::export_data`]
+    /// / [`crate::parsers::cobertura::export_data`] /
+    /// [`crate::gitlab::line_coverage_json`]).
+    pub fn render(&self, data: &crate::model::CoverageData) -> String {
+        match self {
+            ExportFormat::Lcov => crate::parsers::lcov::export_data(data),
+            ExportFormat::Cobertura => crate::parsers::cobertura::export_data(data),
+            ExportFormat::GitlabJson => crate::gitlab::line_coverage_json(data),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn cmd_ingest(
     conn: &mut Connection,
     file: &Path,
@@ -38,6 +88,11 @@ pub fn cmd_ingest(
     name: Option<&str>,
     overwrite: bool,
     root: Option<&Path>,
+    source_maps: bool,
+    merge_into: Option<&str>,
+    include_globs: Option<&[String]>,
+    exclude_globs: Option<&[String]>,
+    source_root: Option<&Path>,
 ) -> Result<String> {
     let cwd;
     let root = match root {
@@ -47,8 +102,23 @@ pub fn cmd_ingest(
             &cwd
         }
     };
-    let (report_id, detected_format, actual_name) =
-        crate::ingest::ingest(conn, file, format, name, overwrite, Some(root))?;
+    let (report_id, detected_format, actual_name) = crate::ingest::ingest(
+        conn,
+        file,
+        format,
+        name,
+        overwrite,
+        Some(root),
+        None,
+        source_maps,
+        false,
+        None,
+        None,
+        merge_into,
+        include_globs,
+        exclude_globs,
+        source_root,
+    )?;
     Ok(format!(
         "Ingested {} as format '{}' → report id {} (name: '{}')\n",
         file.display(),
@@ -58,8 +128,81 @@ pub fn cmd_ingest(
     ))
 }
 
+/// Write a browsable static HTML coverage report to `out_dir` (`covrs html`).
+/// `report` scopes the report to a single stored report by name (default:
+/// every report in the database, unioned). `root` is the `--source-root`
+/// used to locate each file's source text on disk. See
+/// [`report::write_html_report`] for the page layout.
+pub fn cmd_html(
+    conn: &Connection,
+    out_dir: &Path,
+    root: Option<&Path>,
+    report: Option<&str>,
+) -> Result<String> {
+    report::write_html_report(conn, out_dir, root, report)?;
+    Ok(format!("Wrote HTML coverage report to {}\n", out_dir.display()))
+}
+
+/// Combine several already-ingested reports into one unified report
+/// (`covrs merge`) via [`db::merge_reports`].
+pub fn cmd_merge(conn: &mut Connection, report_names: &[String], new_name: &str) -> Result<String> {
+    let names: Vec<&str> = report_names.iter().map(String::as_str).collect();
+    let (_report_id, file_count, line_count) = db::merge_reports(conn, &names, new_name)?;
+
+    Ok(format!(
+        "Merged {} report(s) into '{new_name}': {file_count} file(s), {line_count} line(s)\n",
+        report_names.len(),
+    ))
+}
+
+/// Export a single named report back out as LCOV or Cobertura text
+/// (`covrs export`), for handing coverage data to other tools or CI
+/// dashboards. When `out` is `Some`, the rendered text is written to that
+/// file and a confirmation message is returned instead of the text itself.
+pub fn cmd_export(
+    conn: &Connection,
+    report_name: &str,
+    format: &ExportFormat,
+    out: Option<&Path>,
+) -> Result<String> {
+    let data = db::get_report_coverage(conn, report_name)?;
+    let rendered = format.render(&data);
+
+    match out {
+        Some(path) => {
+            std::fs::write(path, &rendered)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Ok(format!(
+                "Exported report '{}' to {}\n",
+                report_name,
+                path.display()
+            ))
+        }
+        None => Ok(rendered),
+    }
+}
+
+/// Compare two stored reports (`covrs compare --base <name> --head <name>`):
+/// aggregate and per-file line/branch/function rate change, plus newly
+/// covered/missed lines, via [`crate::compare::compare`].
+pub fn cmd_compare(conn: &Connection, base: &str, head: &str) -> Result<String> {
+    let base_data = db::get_report_coverage(conn, base)?;
+    let head_data = db::get_report_coverage(conn, head)?;
+    let delta = crate::compare::compare(&base_data, &head_data);
+    Ok(report::format_compare(&delta))
+}
+
+/// Reclaim space left by deleted/overwritten reports (`covrs compact`).
+pub fn cmd_compact(conn: &mut Connection) -> Result<String> {
+    let stats = db::compact(conn)?;
+    Ok(format!(
+        "Removed {} orphaned file(s). Database size: {} -> {} bytes\n",
+        stats.orphaned_files_removed, stats.size_before, stats.size_after,
+    ))
+}
+
 pub fn cmd_summary(conn: &Connection) -> Result<String> {
-    let summary = db::get_summary(conn)?;
+    let summary = db::get_summary(conn, db::MergeMode::Union)?;
 
     let mut out = String::new();
     writeln!(out, "Files:      {}", summary.total_files).unwrap();
@@ -109,7 +252,7 @@ pub fn cmd_reports(conn: &Connection) -> Result<String> {
 }
 
 pub fn cmd_files(conn: &Connection, sort_by_coverage: bool) -> Result<String> {
-    let mut files = db::get_file_summaries(conn)?;
+    let mut files = db::get_file_summaries(conn, db::MergeMode::Union)?;
 
     if sort_by_coverage {
         files.sort_by(|a, b| a.line_rate().total_cmp(&b.line_rate()));
@@ -139,8 +282,148 @@ pub fn cmd_files(conn: &Connection, sort_by_coverage: bool) -> Result<String> {
     Ok(out)
 }
 
-pub fn cmd_lines(conn: &Connection, source_file: &str, uncovered: bool) -> Result<String> {
-    let lines = db::get_lines(conn, source_file)?;
+/// One directory in the rollup tree built by [`cmd_tree`]: aggregated
+/// line/branch totals over every file nested under it, plus the files that
+/// live directly in it (not in a subdirectory).
+#[derive(Default)]
+struct DirNode {
+    total_lines: u64,
+    covered_lines: u64,
+    total_branches: u64,
+    covered_branches: u64,
+    files: Vec<FileSummary>,
+    children: std::collections::BTreeMap<String, DirNode>,
+}
+
+impl DirNode {
+    fn add_totals(&mut self, f: &FileSummary) {
+        self.total_lines += f.total_lines;
+        self.covered_lines += f.covered_lines;
+        self.total_branches += f.total_branches;
+        self.covered_branches += f.covered_branches;
+    }
+
+    fn line_rate(&self) -> f64 {
+        rate(self.covered_lines, self.total_lines)
+    }
+}
+
+/// Insert every file into a tree keyed by each of its ancestor directory
+/// prefixes, accumulating line/branch totals at every ancestor along the way.
+fn build_dir_tree(files: Vec<FileSummary>) -> DirNode {
+    let mut root = DirNode::default();
+
+    for f in files {
+        root.add_totals(&f);
+
+        let ancestor_dirs: Vec<String> = Path::new(&f.path)
+            .parent()
+            .map(|p| p.to_string_lossy().into_owned())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.split('/').map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let mut node = &mut root;
+        for dir in ancestor_dirs {
+            node = node.children.entry(dir).or_default();
+            node.add_totals(&f);
+        }
+        node.files.push(f);
+    }
+
+    root
+}
+
+/// Collapse a chain of single-child, file-less directories at the top of
+/// the tree into one combined root label (e.g. `src/covrs` rather than a
+/// `src` node containing only a `covrs` node).
+fn collapse_root(mut prefix: String, mut node: DirNode) -> (String, DirNode) {
+    while node.files.is_empty() && node.children.len() == 1 {
+        let (name, child) = node.children.into_iter().next().unwrap();
+        prefix = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        node = child;
+    }
+    (prefix, node)
+}
+
+/// Depth-first render: each directory's `name/   rate%` header, then its
+/// own files, then its subdirectories (recursively).
+fn render_dir_tree(out: &mut String, name: &str, node: &DirNode, depth: usize) {
+    let indent = "  ".repeat(depth);
+    writeln!(
+        out,
+        "{indent}{name}/   {:.1}% ({}/{})",
+        node.line_rate() * 100.0,
+        node.covered_lines,
+        node.total_lines,
+    )
+    .unwrap();
+
+    let mut files = node.files.iter().collect::<Vec<_>>();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    for f in &files {
+        let file_name = Path::new(&f.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| f.path.clone());
+        writeln!(
+            out,
+            "{indent}  {file_name}   {:.1}% ({}/{})",
+            f.line_rate() * 100.0,
+            f.covered_lines,
+            f.total_lines,
+        )
+        .unwrap();
+    }
+
+    for (child_name, child) in &node.children {
+        render_dir_tree(out, child_name, child, depth + 1);
+    }
+}
+
+/// Directory-rollup view of project coverage (`covrs tree`): files grouped
+/// into the directory hierarchy implied by their `/`-split paths, each
+/// directory annotated with the summed line rate of everything beneath it.
+pub fn cmd_tree(conn: &Connection) -> Result<String> {
+    let files = db::get_file_summaries(conn, db::MergeMode::Union)?;
+
+    if files.is_empty() {
+        return Ok("No files in database.\n".to_string());
+    }
+
+    let root = build_dir_tree(files);
+    let (prefix, root) = collapse_root(String::new(), root);
+    let label = if prefix.is_empty() { "." } else { &prefix };
+
+    let mut out = String::new();
+    render_dir_tree(&mut out, label, &root, 0);
+    Ok(out)
+}
+
+pub fn cmd_lines(
+    conn: &Connection,
+    source_file: &str,
+    uncovered: bool,
+    source: bool,
+    root: Option<&Path>,
+) -> Result<String> {
+    let lines = db::get_lines(conn, source_file, db::MergeMode::Union)?;
+    let resolved_path = match root {
+        Some(root) => root.join(source_file),
+        None => Path::new(source_file).to_path_buf(),
+    };
+
+    if source {
+        if let Ok(text) = std::fs::read_to_string(&resolved_path) {
+            return Ok(render_source_detail(&text, &lines));
+        }
+        // Source file isn't on disk at this path (e.g. the database was
+        // ingested elsewhere) — fall back to the plain table below.
+    }
 
     if uncovered {
         let uncovered_lines: Vec<_> = lines.iter().filter(|l| l.hit_count == 0).collect();
@@ -151,6 +434,17 @@ pub fn cmd_lines(conn: &Connection, source_file: &str, uncovered: bool) -> Resul
             ));
         }
 
+        // When the source is available on disk, show the missed lines with
+        // a few lines of surrounding context (like a diff hunk) instead of
+        // bare line-number ranges, so the output is actionable without
+        // opening the file separately.
+        if let Ok(text) = std::fs::read_to_string(&resolved_path) {
+            let mut out = String::new();
+            writeln!(out, "Uncovered lines in '{source_file}':").unwrap();
+            out.push_str(&report::HunkFormatter::default().format(&text, &lines));
+            return Ok(out);
+        }
+
         let mut out = String::new();
         writeln!(out, "Uncovered lines in '{source_file}':").unwrap();
         let uncovered_numbers: Vec<u32> = uncovered_lines.iter().map(|l| l.line_number).collect();
@@ -179,19 +473,98 @@ pub fn cmd_lines(conn: &Connection, source_file: &str, uncovered: bool) -> Resul
     }
 }
 
+/// Render `source_text` as a continuous annotated listing (`cmd_lines`'s
+/// `--source` mode): each line is prefixed by its hit count and a ✓/✗
+/// marker, like Deno's detailed coverage reporter. Lines with no matching
+/// row in `lines` (blank lines, comments, braces) still print, but with a
+/// blank count column, so the listing reads as the whole file rather than
+/// just the instrumented subset.
+fn render_source_detail(source_text: &str, lines: &[LineDetail]) -> String {
+    report::PrettyFormatter.format(source_text, lines)
+}
+
 /// Core diff-coverage logic. Accepts the diff text directly so callers can
 /// obtain it from stdin, `git diff`, or the GitHub API.
+///
+/// When `fail_under` is `Some`, the diff's overall line coverage is checked
+/// against it (see [`report::Thresholds`]) and any failure is both rendered
+/// inline by the formatter and reflected in the returned boolean, which
+/// callers map to a process exit code.
 pub fn cmd_diff_coverage(
     conn: &Connection,
     diff_text: &str,
     path_prefix: Option<&str>,
     style: &Style,
     sha: Option<&str>,
-) -> Result<String> {
-    let report = build_diff_report(conn, diff_text, path_prefix, sha)?;
+    fail_under: Option<f64>,
+) -> Result<(String, bool)> {
+    let mut report = build_diff_report(conn, diff_text, path_prefix, sha)?;
+
+    if let Some(min) = fail_under {
+        let thresholds = report::Thresholds {
+            diff_min: Some(min),
+            ..Default::default()
+        };
+        report.threshold_failures = thresholds.evaluate(&report);
+    }
+
+    let passed = report.passed();
     let formatter = style.formatter();
+    Ok((report.format(formatter.as_ref()), passed))
+}
+
+/// Check whole-project coverage (not a diff) against `thresholds`
+/// (`covrs check`), returning the formatted failure report alongside
+/// whether everything passed — callers map the boolean to a process exit
+/// code. Unlike [`cmd_diff_coverage`]'s `fail_under`, this also applies any
+/// per-glob [`report::Thresholds::path_overrides`] to every file in the
+/// database, not just ones touched by a diff.
+pub fn cmd_check(conn: &Connection, thresholds: &report::Thresholds) -> Result<(String, bool)> {
+    let summary = db::get_summary(conn, db::MergeMode::Union)?;
+    let files = db::get_file_summaries(conn, db::MergeMode::Union)?;
+
+    let failures = report::check_project_thresholds(thresholds, &summary, &files);
+    let passed = failures.is_empty();
+
+    let out = if passed {
+        "All thresholds passed.\n".to_string()
+    } else {
+        let mut out = String::new();
+        for failure in &failures {
+            writeln!(out, "{failure}").unwrap();
+        }
+        out
+    };
 
-    Ok(report.format(formatter.as_ref()))
+    Ok((out, passed))
+}
+
+/// Print the per-file coverage summary table (distinct from [`cmd_summary`]'s
+/// single totals block), restricted to files matching `allow`/`deny` path
+/// globs (see [`report::path_allowed`]) so vendored or generated files can be
+/// excluded from the printed totals, and checked against `fail_under` for CI
+/// gating — callers map the returned boolean to a process exit code, same
+/// convention as [`cmd_check`]/[`cmd_diff_coverage`].
+pub fn cmd_summary_table(
+    conn: &Connection,
+    style: &SummaryStyle,
+    allow: &[String],
+    deny: &[String],
+    fail_under: Option<f64>,
+) -> Result<(String, bool)> {
+    let files: Vec<FileSummary> = db::get_file_summaries(conn, db::MergeMode::Union)?
+        .into_iter()
+        .filter(|f| report::path_allowed(allow, deny, &f.path))
+        .collect();
+    let summary = report::summarize(&files);
+
+    let passed = match fail_under {
+        Some(min) => summary.line_rate() * 100.0 >= min,
+        None => true,
+    };
+
+    let formatter = style.formatter();
+    Ok((formatter.format(&summary, &files), passed))
 }
 
 /// Build a [`report::DiffCoverageReport`] without formatting it.
@@ -217,6 +590,9 @@ pub fn build_diff_report(
 ///
 /// Each missed line range becomes a single `warning` annotation. Consecutive
 /// missed lines within the same file are merged into range annotations.
+/// Diff lines whose branch decision was only partially exercised (see
+/// [`crate::model::FileBranchDiffCoverage::partial`]) get an additional,
+/// single-line annotation reporting how many arms were taken.
 pub fn build_annotations(report: &report::DiffCoverageReport) -> Vec<Annotation> {
     let mut annotations = Vec::new();
 
@@ -229,11 +605,31 @@ pub fn build_annotations(report: &report::DiffCoverageReport) -> Vec<Annotation>
         let ranges = report::coalesce_ranges(&file.missed_lines, &all_instrumentable);
 
         for (start, end) in ranges {
+            // A coalesced range may bridge over a few non-instrumentable
+            // lines, so count the actual missed diff lines it covers
+            // rather than assuming every line in [start, end] is one.
+            let missed_count = file
+                .missed_lines
+                .iter()
+                .filter(|&&l| l >= start && l <= end)
+                .count();
+
             annotations.push(Annotation {
                 path: file.path.clone(),
                 start_line: start,
                 end_line: end,
-                message: annotation_message(start, end),
+                message: annotation_message(missed_count),
+            });
+        }
+    }
+
+    for branch_file in &report.branch_files {
+        for &(line, taken, total) in &branch_file.partial {
+            annotations.push(Annotation {
+                path: branch_file.path.clone(),
+                start_line: line,
+                end_line: line,
+                message: format!("Line {line}: branch taken {taken}/{total} times"),
             });
         }
     }
@@ -242,18 +638,20 @@ pub fn build_annotations(report: &report::DiffCoverageReport) -> Vec<Annotation>
 }
 
 /// Build a human-readable annotation message for a missed line range.
-fn annotation_message(start: u32, end: u32) -> String {
-    if start == end {
-        format!("Line {start} not covered by tests")
+fn annotation_message(missed_count: usize) -> String {
+    if missed_count == 1 {
+        "1 diff line not covered".to_string()
     } else {
-        format!("Lines {start}-{end} not covered by tests")
+        format!("{missed_count} diff lines not covered")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{CoverageData, FileCoverage, FunctionCoverage, LineCoverage};
+    use crate::model::{
+        BranchCoverage, BranchKind, CoverageData, FileCoverage, FunctionCoverage, LineCoverage,
+    };
 
     /// Create an in-memory database with schema initialized.
     fn test_db() -> Connection {
@@ -312,6 +710,7 @@ mod tests {
                     functions: vec![],
                 },
             ],
+            ..Default::default()
         };
         db::insert_coverage(conn, "test-report", "lcov", None, &data, false).unwrap();
     }
@@ -377,12 +776,80 @@ mod tests {
         assert!(main_pos < lib_pos);
     }
 
+    #[test]
+    fn test_cmd_tree_collapses_single_root_prefix() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let out = cmd_tree(&conn).unwrap();
+
+        // Both seeded files live under src/, so the root label collapses
+        // to "src" rather than showing an empty "." node above it.
+        assert!(out.starts_with("src/"));
+        assert!(out.contains("main.rs"));
+        assert!(out.contains("lib.rs"));
+    }
+
+    #[test]
+    fn test_cmd_tree_nested_dirs() {
+        let mut conn = test_db();
+        db::insert_coverage(
+            &mut conn,
+            "report",
+            "lcov",
+            None,
+            &CoverageData {
+                files: vec![
+                    FileCoverage {
+                        path: "src/parsers/lcov.rs".to_string(),
+                        lines: vec![
+                            LineCoverage {
+                                line_number: 1,
+                                hit_count: 1,
+                            },
+                            LineCoverage {
+                                line_number: 2,
+                                hit_count: 0,
+                            },
+                        ],
+                        branches: vec![],
+                        functions: vec![],
+                    },
+                    FileCoverage {
+                        path: "src/main.rs".to_string(),
+                        lines: vec![LineCoverage {
+                            line_number: 1,
+                            hit_count: 1,
+                        }],
+                        branches: vec![],
+                        functions: vec![],
+                    },
+                ],
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+
+        let out = cmd_tree(&conn).unwrap();
+
+        // Root collapses to "src", with "parsers" as a nested subdirectory
+        // rolling up lcov.rs's 50% alongside main.rs at the top level.
+        assert!(out.starts_with("src/"));
+        let parsers_pos = out.find("parsers/").unwrap();
+        let lcov_pos = out.find("lcov.rs").unwrap();
+        let main_pos = out.find("main.rs").unwrap();
+        assert!(parsers_pos < lcov_pos);
+        assert!(out.contains("50.0%"));
+        assert!(main_pos > 0);
+    }
+
     #[test]
     fn test_cmd_lines() {
         let mut conn = test_db();
         seed_coverage(&mut conn);
 
-        let out = cmd_lines(&conn, "src/main.rs", false).unwrap();
+        let out = cmd_lines(&conn, "src/main.rs", false, false, None).unwrap();
 
         assert!(out.contains("LINE"));
         assert!(out.contains("HITS"));
@@ -394,7 +861,7 @@ mod tests {
     fn test_cmd_lines_no_data() {
         let conn = test_db();
 
-        let result = cmd_lines(&conn, "nonexistent.rs", false);
+        let result = cmd_lines(&conn, "nonexistent.rs", false, false, None);
         assert!(result.is_err());
     }
 
@@ -403,7 +870,7 @@ mod tests {
         let mut conn = test_db();
         seed_coverage(&mut conn);
 
-        let out = cmd_lines(&conn, "src/main.rs", true).unwrap();
+        let out = cmd_lines(&conn, "src/main.rs", true, false, None).unwrap();
 
         assert!(out.contains("Uncovered lines in 'src/main.rs':"));
         assert!(out.contains("3-4"));
@@ -415,11 +882,118 @@ mod tests {
         let mut conn = test_db();
         seed_coverage(&mut conn);
 
-        let out = cmd_lines(&conn, "src/lib.rs", true).unwrap();
+        let out = cmd_lines(&conn, "src/lib.rs", true, false, None).unwrap();
 
         assert!(out.contains("All instrumentable lines are covered"));
     }
 
+    #[test]
+    fn test_cmd_lines_uncovered_shows_hunk_when_source_is_available() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/main.rs"),
+            "fn main() {\n    let a = 1;\n    let b = 2;\n    let c = 3;\n}\n",
+        )
+        .unwrap();
+
+        let mut conn = test_db();
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: "src/main.rs".to_string(),
+                lines: vec![
+                    LineCoverage {
+                        line_number: 2,
+                        hit_count: 1,
+                    },
+                    LineCoverage {
+                        line_number: 3,
+                        hit_count: 0,
+                    },
+                ],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "test-report", "lcov", None, &data, false).unwrap();
+
+        let out = cmd_lines(&conn, "src/main.rs", true, false, Some(dir.path())).unwrap();
+
+        assert!(out.contains("Uncovered lines in 'src/main.rs':"));
+        // The hunk view renders the real source text, not bare ranges.
+        assert!(out.contains("let b = 2;"));
+        assert!(out.contains("✗"));
+        assert!(!out.contains("3-3"));
+    }
+
+    #[test]
+    fn test_cmd_lines_source_annotates_and_blanks_uninstrumented_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        std::fs::write(&path, "fn main() {\n    let x = 1;\n    let y = 0;\n}\n").unwrap();
+        let path_str = path.to_str().unwrap().to_string();
+
+        let mut conn = test_db();
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: path_str.clone(),
+                lines: vec![
+                    LineCoverage {
+                        line_number: 2,
+                        hit_count: 5,
+                    },
+                    LineCoverage {
+                        line_number: 3,
+                        hit_count: 0,
+                    },
+                ],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "test-report", "lcov", None, &data, false).unwrap();
+
+        let out = cmd_lines(&conn, &path_str, false, true, None).unwrap();
+
+        // Line 1 ("fn main() {") has no DB entry, so its count column is blank.
+        let line1 = out.lines().find(|l| l.ends_with("fn main() {")).unwrap();
+        assert!(!line1.contains('✓') && !line1.contains('✗'));
+
+        // Line 2 is instrumented (hit_count 5) and covered; line 3 is missed.
+        let line2 = out.lines().find(|l| l.contains("let x = 1;")).unwrap();
+        assert!(line2.trim_start().starts_with('2'));
+        assert!(line2.contains('✓'));
+
+        let line3 = out.lines().find(|l| l.contains("let y = 0;")).unwrap();
+        assert!(line3.contains('✗'));
+    }
+
+    #[test]
+    fn test_cmd_lines_source_falls_back_when_file_missing() {
+        let mut conn = test_db();
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: "nonexistent/dir/file.rs".to_string(),
+                lines: vec![LineCoverage {
+                    line_number: 1,
+                    hit_count: 1,
+                }],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "test-report", "lcov", None, &data, false).unwrap();
+
+        let out = cmd_lines(&conn, "nonexistent/dir/file.rs", false, true, None).unwrap();
+
+        assert!(out.contains("LINE"));
+        assert!(out.contains("HITS"));
+        assert!(!out.contains("fn main"));
+    }
+
     #[test]
     fn test_cmd_diff_coverage_text() {
         let mut conn = test_db();
@@ -436,10 +1010,62 @@ diff --git a/src/main.rs b/src/main.rs
 +    let z = 3;
 ";
 
-        let out = cmd_diff_coverage(&conn, diff_text, None, &Style::Text, None).unwrap();
+        let (out, passed) =
+            cmd_diff_coverage(&conn, diff_text, None, &Style::Text, None, None).unwrap();
 
         assert!(out.contains("Diff coverage:"));
         assert!(out.contains("50.0%"));
+        assert!(passed);
+    }
+
+    #[test]
+    fn test_cmd_diff_coverage_json() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let diff_text = "\
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -0,0 +1,4 @@
++fn main() {
++    let x = 1;
++    let y = 2;
++    let z = 3;
+";
+
+        let (out, passed) =
+            cmd_diff_coverage(&conn, diff_text, None, &Style::Json, None, None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+        assert_eq!(parsed["covered"], 2);
+        assert_eq!(parsed["total"], 4);
+        assert!(parsed["per_file"].as_array().unwrap()[0]["path"] == "src/main.rs");
+        assert!(passed);
+    }
+
+    #[test]
+    fn test_cmd_diff_coverage_json_fail_under_still_gates() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let diff_text = "\
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -0,0 +1,4 @@
++fn main() {
++    let x = 1;
++    let y = 2;
++    let z = 3;
+";
+
+        let (out, passed) =
+            cmd_diff_coverage(&conn, diff_text, None, &Style::Json, None, Some(90.0)).unwrap();
+
+        // --format json doesn't change the fail_under gating, only the rendering.
+        assert!(serde_json::from_str::<serde_json::Value>(&out).is_ok());
+        assert!(!passed);
     }
 
     #[test]
@@ -458,8 +1084,15 @@ diff --git a/src/main.rs b/src/main.rs
 +    let z = 3;
 ";
 
-        let out =
-            cmd_diff_coverage(&conn, diff_text, None, &Style::Markdown, Some("abc1234")).unwrap();
+        let (out, _passed) = cmd_diff_coverage(
+            &conn,
+            diff_text,
+            None,
+            &Style::Markdown,
+            Some("abc1234"),
+            None,
+        )
+        .unwrap();
 
         assert!(out.contains("## Diff Coverage:"));
         assert!(out.contains("abc1234"));
@@ -470,9 +1103,10 @@ diff --git a/src/main.rs b/src/main.rs
         let mut conn = test_db();
         seed_coverage(&mut conn);
 
-        let out = cmd_diff_coverage(&conn, "", None, &Style::Text, None).unwrap();
+        let (out, passed) = cmd_diff_coverage(&conn, "", None, &Style::Text, None, None).unwrap();
 
         assert!(out.contains("No added lines found in diff."));
+        assert!(passed);
     }
 
     #[test]
@@ -495,6 +1129,7 @@ diff --git a/src/main.rs b/src/main.rs
                 branches: vec![],
                 functions: vec![],
             }],
+            ..Default::default()
         };
         db::insert_coverage(&mut conn, "prefix-report", "lcov", None, &data, false).unwrap();
 
@@ -507,28 +1142,103 @@ diff --git a/app.rs b/app.rs
 +line two
 ";
 
-        let out = cmd_diff_coverage(&conn, diff_text, Some("project"), &Style::Text, None).unwrap();
+        let (out, _passed) =
+            cmd_diff_coverage(&conn, diff_text, Some("project"), &Style::Text, None, None)
+                .unwrap();
 
         assert!(out.contains("Diff coverage:"));
         assert!(out.contains("1/2"));
     }
 
     #[test]
-    fn test_build_annotations_groups_consecutive_lines() {
+    fn test_cmd_check_project_failure() {
         let mut conn = test_db();
         seed_coverage(&mut conn);
 
-        // Lines 1,2 are covered (hit_count > 0), lines 3,4 are uncovered
-        let diff_text = "\
-diff --git a/src/main.rs b/src/main.rs
---- a/src/main.rs
-+++ b/src/main.rs
-@@ -0,0 +1,4 @@
-+fn main() {
-+    let x = 1;
-+    let y = 2;
-+    let z = 3;
-";
+        let thresholds = report::Thresholds {
+            project_min: Some(90.0),
+            ..Default::default()
+        };
+        let (out, passed) = cmd_check(&conn, &thresholds).unwrap();
+
+        assert!(!passed);
+        assert!(out.contains("FAILED"));
+    }
+
+    #[test]
+    fn test_cmd_check_passes_all() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let thresholds = report::Thresholds {
+            project_min: Some(50.0),
+            ..Default::default()
+        };
+        let (out, passed) = cmd_check(&conn, &thresholds).unwrap();
+
+        assert!(passed);
+        assert!(out.contains("All thresholds passed"));
+    }
+
+    #[test]
+    fn test_cmd_summary_table_includes_all_files_by_default() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let (out, passed) = cmd_summary_table(&conn, &SummaryStyle::Text, &[], &[], None).unwrap();
+
+        assert!(out.contains("src/main.rs"));
+        assert!(out.contains("src/lib.rs"));
+        assert!(passed);
+    }
+
+    #[test]
+    fn test_cmd_summary_table_deny_glob_excludes_file() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let deny = vec!["src/lib.rs".to_string()];
+        let (out, passed) =
+            cmd_summary_table(&conn, &SummaryStyle::Text, &[], &deny, None).unwrap();
+
+        assert!(out.contains("src/main.rs"));
+        assert!(!out.contains("src/lib.rs"));
+        // src/lib.rs's 2/2 covered lines are excluded, leaving only
+        // src/main.rs's 2/4 in the totals.
+        assert!(out.contains("TOTAL  lines 2/4"));
+        assert!(passed);
+    }
+
+    #[test]
+    fn test_cmd_summary_table_fail_under() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let (_, passed) =
+            cmd_summary_table(&conn, &SummaryStyle::Text, &[], &[], Some(90.0)).unwrap();
+        assert!(!passed);
+
+        let (_, passed) =
+            cmd_summary_table(&conn, &SummaryStyle::Text, &[], &[], Some(50.0)).unwrap();
+        assert!(passed);
+    }
+
+    #[test]
+    fn test_build_annotations_groups_consecutive_lines() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        // Lines 1,2 are covered (hit_count > 0), lines 3,4 are uncovered
+        let diff_text = "\
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -0,0 +1,4 @@
++fn main() {
++    let x = 1;
++    let y = 2;
++    let z = 3;
+";
 
         let report = build_diff_report(&conn, diff_text, None, None).unwrap();
         let annotations = build_annotations(&report);
@@ -538,7 +1248,7 @@ diff --git a/src/main.rs b/src/main.rs
         assert_eq!(annotations[0].path, "src/main.rs");
         assert_eq!(annotations[0].start_line, 3);
         assert_eq!(annotations[0].end_line, 4);
-        assert!(annotations[0].message.contains("3-4"));
+        assert!(annotations[0].message.contains("2 diff lines not covered"));
     }
 
     #[test]
@@ -569,6 +1279,7 @@ diff --git a/src/main.rs b/src/main.rs
                 branches: vec![],
                 functions: vec![],
             }],
+            ..Default::default()
         };
         db::insert_coverage(&mut conn, "test", "lcov", None, &data, false).unwrap();
 
@@ -590,11 +1301,69 @@ diff --git a/src/foo.rs b/src/foo.rs
         assert_eq!(annotations.len(), 2);
         assert_eq!(annotations[0].start_line, 2);
         assert_eq!(annotations[0].end_line, 2);
-        assert!(annotations[0].message.contains("Line 2"));
+        assert!(annotations[0].message.contains("1 diff line not covered"));
         assert_eq!(annotations[1].start_line, 4);
         assert_eq!(annotations[1].end_line, 4);
     }
 
+    #[test]
+    fn test_build_annotations_reports_partially_covered_branch() {
+        let mut conn = test_db();
+
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: "src/foo.rs".to_string(),
+                lines: vec![LineCoverage {
+                    line_number: 1,
+                    hit_count: 1,
+                }],
+                branches: vec![
+                    BranchCoverage {
+                        line_number: 1,
+                        branch_index: 0,
+                        hit_count: 1,
+                        group_id: Some(1),
+                        kind: BranchKind::Unknown,
+                        arm_line: None,
+                    },
+                    BranchCoverage {
+                        line_number: 1,
+                        branch_index: 1,
+                        hit_count: 0,
+                        group_id: Some(1),
+                        kind: BranchKind::Unknown,
+                        arm_line: None,
+                    },
+                ],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "test", "lcov", None, &data, false).unwrap();
+
+        let diff_text = "\
+diff --git a/src/foo.rs b/src/foo.rs
+--- a/src/foo.rs
++++ b/src/foo.rs
+@@ -0,0 +1,1 @@
++line 1
+";
+
+        let report = build_diff_report(&conn, diff_text, None, None).unwrap();
+        let annotations = build_annotations(&report);
+
+        let branch_annotation = annotations
+            .iter()
+            .find(|a| a.message.contains("branch taken"))
+            .unwrap();
+        assert_eq!(branch_annotation.start_line, 1);
+        assert_eq!(branch_annotation.end_line, 1);
+        assert_eq!(
+            branch_annotation.message,
+            "Line 1: branch taken 1/2 times"
+        );
+    }
+
     #[test]
     fn test_build_annotations_empty_when_all_covered() {
         let mut conn = test_db();
@@ -616,6 +1385,54 @@ diff --git a/src/lib.rs b/src/lib.rs
         assert!(annotations.is_empty());
     }
 
+    #[test]
+    fn test_build_annotations_counts_only_missed_lines_in_bridged_range() {
+        let mut conn = test_db();
+
+        // Line 3 has no coverage entry at all (e.g. a blank line), so a
+        // missed-line gap of just that one line gets bridged into a single
+        // range — but the message should still report 2 missed lines, not
+        // the full 2-4 span.
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: "src/bar.rs".to_string(),
+                lines: vec![
+                    LineCoverage {
+                        line_number: 2,
+                        hit_count: 0,
+                    },
+                    LineCoverage {
+                        line_number: 4,
+                        hit_count: 0,
+                    },
+                ],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "test", "lcov", None, &data, false).unwrap();
+
+        let diff_text = "\
+diff --git a/src/bar.rs b/src/bar.rs
+--- a/src/bar.rs
++++ b/src/bar.rs
+@@ -0,0 +1,4 @@
++line 1
++line 2
++line 3
++line 4
+";
+
+        let report = build_diff_report(&conn, diff_text, None, None).unwrap();
+        let annotations = build_annotations(&report);
+
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].start_line, 2);
+        assert_eq!(annotations[0].end_line, 4);
+        assert!(annotations[0].message.contains("2 diff lines not covered"));
+    }
+
     #[test]
     fn test_cmd_ingest() {
         let mut conn = test_db();
@@ -624,7 +1441,7 @@ diff --git a/src/lib.rs b/src/lib.rs
         let lcov_path = dir.path().join("test.lcov");
         std::fs::write(&lcov_path, "SF:src/foo.rs\nDA:1,5\nDA:2,0\nend_of_record\n").unwrap();
 
-        let out = cmd_ingest(&mut conn, &lcov_path, None, Some("my-report"), false, None).unwrap();
+        let out = cmd_ingest(&mut conn, &lcov_path, None, Some("my-report"), false, None, false, None, None, None, None).unwrap();
 
         assert!(out.contains("Ingested"));
         assert!(out.contains("lcov"));
@@ -635,4 +1452,589 @@ diff --git a/src/lib.rs b/src/lib.rs
         assert_eq!(reports.len(), 1);
         assert_eq!(reports[0].name, "my-report");
     }
+
+    #[test]
+    fn test_cmd_ingest_exclude_glob_drops_matching_files() {
+        let mut conn = test_db();
+
+        let dir = tempfile::tempdir().unwrap();
+        let lcov_path = dir.path().join("test.lcov");
+        std::fs::write(
+            &lcov_path,
+            "SF:src/lib.rs\nDA:1,1\nend_of_record\nSF:vendor/dep.rs\nDA:1,1\nend_of_record\n",
+        )
+        .unwrap();
+
+        let exclude = vec!["vendor/**".to_string()];
+        cmd_ingest(
+            &mut conn,
+            &lcov_path,
+            None,
+            Some("filtered"),
+            false,
+            None,
+            false,
+            None,
+            None,
+            Some(&exclude),
+            None,
+        )
+        .unwrap();
+
+        let data = db::get_report_coverage(&conn, "filtered").unwrap();
+        assert_eq!(data.files.len(), 1);
+        assert_eq!(data.files[0].path, "src/lib.rs");
+    }
+
+    #[test]
+    fn test_cmd_ingest_source_maps_remaps_onto_original_file() {
+        let mut conn = test_db();
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("bundle.js"),
+            "console.log('hi');\n//# sourceMappingURL=bundle.js.map\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("bundle.js.map"),
+            r#"{"sources":["orig.ts"],"mappings":"AAAA"}"#,
+        )
+        .unwrap();
+
+        let lcov_path = dir.path().join("coverage.lcov");
+        std::fs::write(&lcov_path, "SF:bundle.js\nDA:1,5\nend_of_record\n").unwrap();
+
+        cmd_ingest(
+            &mut conn,
+            &lcov_path,
+            None,
+            Some("mapped"),
+            false,
+            Some(dir.path()),
+            true,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let data = db::get_report_coverage(&conn, "mapped").unwrap();
+        assert_eq!(data.files.len(), 1);
+        assert_eq!(data.files[0].path, "orig.ts");
+        assert_eq!(data.files[0].lines[0].hit_count, 5);
+    }
+
+    #[test]
+    fn test_cmd_ingest_source_maps_carries_branches_through() {
+        let mut conn = test_db();
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("bundle.js"),
+            "console.log('hi');\n//# sourceMappingURL=bundle.js.map\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("bundle.js.map"),
+            r#"{"sources":["orig.ts"],"mappings":"AAAA"}"#,
+        )
+        .unwrap();
+
+        let lcov_path = dir.path().join("coverage.lcov");
+        std::fs::write(
+            &lcov_path,
+            "SF:bundle.js\nDA:1,5\nBRDA:1,0,0,5\nBRDA:1,0,1,0\nend_of_record\n",
+        )
+        .unwrap();
+
+        cmd_ingest(
+            &mut conn,
+            &lcov_path,
+            None,
+            Some("mapped"),
+            false,
+            Some(dir.path()),
+            true,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let data = db::get_report_coverage(&conn, "mapped").unwrap();
+        assert_eq!(data.files.len(), 1);
+        assert_eq!(data.files[0].path, "orig.ts");
+        // Both BRDA arms on generated line 1 remap onto original line 1 —
+        // previously dropped entirely by `remap_file`.
+        assert_eq!(data.files[0].branches.len(), 2);
+        assert!(data.files[0]
+            .branches
+            .iter()
+            .any(|b| b.line_number == 1 && b.hit_count == 5));
+        assert!(data.files[0]
+            .branches
+            .iter()
+            .any(|b| b.line_number == 1 && b.hit_count == 0));
+    }
+
+    #[test]
+    fn test_cmd_ingest_merge_into_accumulates_into_one_report() {
+        let mut conn = test_db();
+
+        let dir = tempfile::tempdir().unwrap();
+        let shard1 = dir.path().join("shard1.lcov");
+        std::fs::write(&shard1, "SF:src/foo.rs\nDA:1,1\nDA:2,0\nend_of_record\n").unwrap();
+        let shard2 = dir.path().join("shard2.lcov");
+        std::fs::write(&shard2, "SF:src/foo.rs\nDA:1,0\nDA:2,2\nend_of_record\n").unwrap();
+
+        cmd_ingest(&mut conn, &shard1, None, None, false, None, false, Some("suite"), None, None, None).unwrap();
+        let out = cmd_ingest(&mut conn, &shard2, None, None, false, None, false, Some("suite"), None, None, None).unwrap();
+
+        assert!(out.contains("'suite'"));
+
+        // Still a single report, with hit counts summed across both shards.
+        let reports = db::list_reports(&conn).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].name, "suite");
+
+        let data = db::get_report_coverage(&conn, "suite").unwrap();
+        assert_eq!(data.files.len(), 1);
+        let line1 = data.files[0]
+            .lines
+            .iter()
+            .find(|l| l.line_number == 1)
+            .unwrap();
+        assert_eq!(line1.hit_count, 1);
+        let line2 = data.files[0]
+            .lines
+            .iter()
+            .find(|l| l.line_number == 2)
+            .unwrap();
+        assert_eq!(line2.hit_count, 2);
+    }
+
+    #[test]
+    fn test_cmd_ingest_source_root_resolves_v8_script_from_disk() {
+        let mut conn = test_db();
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("f.js"), b"function f() {\n  used();\n}\n").unwrap();
+
+        let v8_path = dir.path().join("coverage.json");
+        std::fs::write(
+            &v8_path,
+            br#"[{"scriptId":"1","url":"file:///f.js","functions":[{"functionName":"f","isBlockCoverage":false,"ranges":[{"startOffset":0,"endOffset":29,"count":1}]}]}]"#,
+        )
+        .unwrap();
+
+        cmd_ingest(
+            &mut conn,
+            &v8_path,
+            None,
+            Some("v8-report"),
+            false,
+            None,
+            false,
+            None,
+            None,
+            None,
+            Some(dir.path()),
+        )
+        .unwrap();
+
+        let data = db::get_report_coverage(&conn, "v8-report").unwrap();
+        assert_eq!(data.files.len(), 1);
+        let line2 = data.files[0]
+            .lines
+            .iter()
+            .find(|l| l.line_number == 2)
+            .unwrap();
+        assert_eq!(line2.hit_count, 1);
+    }
+
+    #[test]
+    fn test_cmd_html() {
+        let mut conn = test_db();
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/main.rs"),
+            "fn main() {\n    let x = 1;\n    let y = 2;\n}\n",
+        )
+        .unwrap();
+
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: "src/main.rs".to_string(),
+                lines: vec![
+                    LineCoverage {
+                        line_number: 1,
+                        hit_count: 1,
+                    },
+                    LineCoverage {
+                        line_number: 2,
+                        hit_count: 1,
+                    },
+                    LineCoverage {
+                        line_number: 3,
+                        hit_count: 0,
+                    },
+                ],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "report", "lcov", None, &data, false).unwrap();
+
+        let out_dir = dir.path().join("htmlcov");
+        let out = cmd_html(&conn, &out_dir, Some(dir.path()), None).unwrap();
+        assert!(out.contains("Wrote HTML coverage report"));
+
+        let index = std::fs::read_to_string(out_dir.join("index.html")).unwrap();
+        assert!(index.contains("src/main.rs"));
+        assert!(index.contains("66.7%"));
+
+        let page = std::fs::read_to_string(out_dir.join("src_main.rs.html")).unwrap();
+        assert!(page.contains("let x = 1;"));
+        assert!(page.contains("class=\"line hit\""));
+        assert!(page.contains("class=\"line miss\""));
+    }
+
+    #[test]
+    fn test_cmd_html_scoped_to_one_report() {
+        let mut conn = test_db();
+
+        let data_a = CoverageData {
+            files: vec![FileCoverage {
+                path: "a.rs".to_string(),
+                lines: vec![LineCoverage {
+                    line_number: 1,
+                    hit_count: 1,
+                }],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "report-a", "lcov", None, &data_a, false).unwrap();
+
+        let data_b = CoverageData {
+            files: vec![FileCoverage {
+                path: "b.rs".to_string(),
+                lines: vec![LineCoverage {
+                    line_number: 1,
+                    hit_count: 0,
+                }],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "report-b", "lcov", None, &data_b, false).unwrap();
+
+        let out_dir = tempfile::tempdir().unwrap();
+        cmd_html(&conn, out_dir.path(), None, Some("report-a")).unwrap();
+
+        let index = std::fs::read_to_string(out_dir.path().join("index.html")).unwrap();
+        assert!(index.contains("a.rs"));
+        assert!(!index.contains("b.rs"));
+    }
+
+    #[test]
+    fn test_cmd_html_missing_source_gets_placeholder_not_a_failure() {
+        let mut conn = test_db();
+
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: "does/not/exist.rs".to_string(),
+                lines: vec![LineCoverage {
+                    line_number: 1,
+                    hit_count: 1,
+                }],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "report", "lcov", None, &data, false).unwrap();
+
+        let out_dir = tempfile::tempdir().unwrap();
+        cmd_html(&conn, out_dir.path(), None, None).unwrap();
+
+        let index = std::fs::read_to_string(out_dir.path().join("index.html")).unwrap();
+        assert!(index.contains("exist.rs"));
+
+        let page =
+            std::fs::read_to_string(out_dir.path().join("does_not_exist.rs.html")).unwrap();
+        assert!(page.contains("Source unavailable"));
+    }
+
+    #[test]
+    fn test_cmd_merge_unions_lines_across_reports() {
+        let mut conn = test_db();
+        db::insert_coverage(
+            &mut conn,
+            "shard-a",
+            "lcov",
+            None,
+            &CoverageData {
+                files: vec![FileCoverage {
+                    path: "src/main.rs".to_string(),
+                    lines: vec![
+                        LineCoverage {
+                            line_number: 1,
+                            hit_count: 1,
+                        },
+                        LineCoverage {
+                            line_number: 2,
+                            hit_count: 0,
+                        },
+                    ],
+                    branches: vec![],
+                    functions: vec![],
+                }],
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+        db::insert_coverage(
+            &mut conn,
+            "shard-b",
+            "lcov",
+            None,
+            &CoverageData {
+                files: vec![FileCoverage {
+                    path: "src/main.rs".to_string(),
+                    lines: vec![
+                        LineCoverage {
+                            line_number: 1,
+                            hit_count: 0,
+                        },
+                        LineCoverage {
+                            line_number: 2,
+                            hit_count: 3,
+                        },
+                    ],
+                    branches: vec![],
+                    functions: vec![],
+                }],
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+
+        let out = cmd_merge(
+            &mut conn,
+            &["shard-a".to_string(), "shard-b".to_string()],
+            "merged",
+        )
+        .unwrap();
+        assert!(out.contains("Merged 2 report(s) into 'merged'"));
+        assert!(out.contains("1 file(s)"));
+
+        // Line 2 is missed in shard-a but hit in shard-b, so it must show
+        // as covered in the merged report.
+        let lines = db::get_report_coverage(&conn, "merged").unwrap();
+        let file = &lines.files[0];
+        let line2 = file.lines.iter().find(|l| l.line_number == 2).unwrap();
+        assert!(line2.hit_count > 0);
+    }
+
+    #[test]
+    fn test_cmd_export_lcov_round_trip() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let exported = cmd_export(&conn, "test-report", &ExportFormat::Lcov, None).unwrap();
+        let reparsed = crate::parsers::lcov::parse(exported.as_bytes()).unwrap();
+
+        let original = db::get_report_coverage(&conn, "test-report").unwrap();
+        assert_eq!(reparsed.files.len(), original.files.len());
+        for (a, b) in original.files.iter().zip(reparsed.files.iter()) {
+            assert_eq!(a.path, b.path);
+            let a_hits: Vec<u64> = a.lines.iter().map(|l| l.hit_count).collect();
+            let b_hits: Vec<u64> = b.lines.iter().map(|l| l.hit_count).collect();
+            assert_eq!(a_hits, b_hits);
+        }
+    }
+
+    #[test]
+    fn test_cmd_export_cobertura() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let out = cmd_export(&conn, "test-report", &ExportFormat::Cobertura, None).unwrap();
+
+        assert!(out.starts_with("<?xml"));
+        assert!(out.contains("<packages>"));
+        assert!(out.contains("src/main.rs"));
+    }
+
+    #[test]
+    fn test_cmd_export_gitlab_json() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let out = cmd_export(&conn, "test-report", &ExportFormat::GitlabJson, None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+        assert!(parsed.get("src/main.rs").is_some());
+    }
+
+    #[test]
+    fn test_cmd_export_unknown_report() {
+        let conn = test_db();
+
+        let err = cmd_export(&conn, "does-not-exist", &ExportFormat::Lcov, None).unwrap_err();
+        assert!(err.to_string().contains("Report not found"));
+    }
+
+    #[test]
+    fn test_cmd_export_with_out_writes_file_and_returns_confirmation() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let dir = tempfile::tempdir().unwrap();
+        let out_path = dir.path().join("coverage.lcov");
+
+        let message = cmd_export(
+            &conn,
+            "test-report",
+            &ExportFormat::Lcov,
+            Some(&out_path),
+        )
+        .unwrap();
+
+        assert!(message.contains("Exported"));
+        assert!(message.contains("test-report"));
+
+        let written = std::fs::read_to_string(&out_path).unwrap();
+        assert!(written.starts_with("SF:"));
+    }
+
+    #[test]
+    fn test_cmd_compare_shows_regression_between_two_reports() {
+        let mut conn = test_db();
+        let base_data = CoverageData {
+            files: vec![FileCoverage {
+                path: "src/main.rs".to_string(),
+                lines: vec![
+                    LineCoverage {
+                        line_number: 1,
+                        hit_count: 1,
+                    },
+                    LineCoverage {
+                        line_number: 2,
+                        hit_count: 1,
+                    },
+                ],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        let head_data = CoverageData {
+            files: vec![FileCoverage {
+                path: "src/main.rs".to_string(),
+                lines: vec![
+                    LineCoverage {
+                        line_number: 1,
+                        hit_count: 1,
+                    },
+                    LineCoverage {
+                        line_number: 2,
+                        hit_count: 0,
+                    },
+                ],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "base", "lcov", None, &base_data, false).unwrap();
+        db::insert_coverage(&mut conn, "head", "lcov", None, &head_data, false).unwrap();
+
+        let out = cmd_compare(&conn, "base", "head").unwrap();
+
+        assert!(out.contains("Line coverage:     100.0% -> 50.0% (-50.0)"));
+        assert!(out.contains("src/main.rs"));
+        assert!(out.contains("newly missed:  2"));
+    }
+
+    #[test]
+    fn test_cmd_compare_unknown_report() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let err = cmd_compare(&conn, "test-report", "does-not-exist").unwrap_err();
+        assert!(err.to_string().contains("Report not found"));
+    }
+
+    #[test]
+    fn test_cmd_compact() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        let out = cmd_compact(&mut conn).unwrap();
+        assert!(out.contains("Removed 0 orphaned file(s)"));
+    }
+
+    #[test]
+    fn test_get_summary_merge_modes() {
+        let mut conn = test_db();
+        seed_coverage(&mut conn);
+
+        // Second report: src/main.rs line 1 hit twice (5 in the first), line 3 now
+        // covered once, line 4 still never hit.
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: "src/main.rs".to_string(),
+                lines: vec![
+                    LineCoverage {
+                        line_number: 1,
+                        hit_count: 2,
+                    },
+                    LineCoverage {
+                        line_number: 2,
+                        hit_count: 0,
+                    },
+                    LineCoverage {
+                        line_number: 3,
+                        hit_count: 1,
+                    },
+                    LineCoverage {
+                        line_number: 4,
+                        hit_count: 0,
+                    },
+                ],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+        db::insert_coverage(&mut conn, "second-report", "lcov", None, &data, false).unwrap();
+
+        // Union: a line is covered if any report hit it — src/main.rs lines
+        // 1-3 plus both src/lib.rs lines (only present in the first report).
+        let union = db::get_summary(&conn, db::MergeMode::Union).unwrap();
+        assert_eq!(union.covered_lines, 5);
+
+        // Intersection: src/lib.rs lines are only present in one of the two
+        // reports, so they don't count; only src/main.rs line 1 is covered
+        // by both.
+        let intersection = db::get_summary(&conn, db::MergeMode::Intersection).unwrap();
+        assert_eq!(intersection.covered_lines, 1);
+
+        // Sum: true cross-report execution totals for src/main.rs line 1 (5 + 2).
+        let lines = db::get_lines(&conn, "src/main.rs", db::MergeMode::Sum).unwrap();
+        let line1 = lines.iter().find(|l| l.line_number == 1).unwrap();
+        assert_eq!(line1.hit_count, 7);
+    }
 }
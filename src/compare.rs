@@ -0,0 +1,434 @@
+//! Compare coverage between two points in time — typically a PR's head
+//! report against its base branch. `covrs compare` renders the result (see
+//! [`crate::report::format_compare`]); `post_comment` does not use it.
+
+use crate::model::{rate, Annotation, CoverageData};
+
+/// A single file's before/after line coverage. `before`/`after` are `None`
+/// when the file is absent from that side (newly added or removed).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileCoverageDelta {
+    pub path: String,
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+    /// Branch coverage rate on each side, `None` when the file has no
+    /// branch data there (absent entirely, or the format doesn't emit it).
+    pub branch_before: Option<f64>,
+    pub branch_after: Option<f64>,
+    /// Function coverage rate on each side, same `None` convention as
+    /// `branch_before`/`branch_after`.
+    pub function_before: Option<f64>,
+    pub function_after: Option<f64>,
+    /// Lines that were missed in `base` and are covered in `head`.
+    pub newly_covered: Vec<u32>,
+    /// Lines that were covered in `base` and are missed in `head`.
+    pub newly_missed: Vec<u32>,
+}
+
+impl FileCoverageDelta {
+    /// A file regressed if it existed on both sides and its rate dropped.
+    /// A newly added or removed file is never a regression on its own.
+    #[must_use]
+    pub fn regressed(&self) -> bool {
+        match (self.before, self.after) {
+            (Some(before), Some(after)) => after < before,
+            _ => false,
+        }
+    }
+}
+
+/// The result of comparing a base and head coverage report (`covrs
+/// compare`): overall rate change plus a per-file breakdown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageDelta {
+    pub before_rate: f64,
+    pub after_rate: f64,
+    /// Aggregate branch coverage rate on each side, `None` when neither
+    /// report has any branch data at all.
+    pub before_branch_rate: Option<f64>,
+    pub after_branch_rate: Option<f64>,
+    /// Aggregate function coverage rate on each side, same `None`
+    /// convention as `before_branch_rate`/`after_branch_rate`.
+    pub before_function_rate: Option<f64>,
+    pub after_function_rate: Option<f64>,
+    pub files: Vec<FileCoverageDelta>,
+}
+
+impl CoverageDelta {
+    /// Overall line coverage rate change in percentage points (negative
+    /// means regression).
+    #[must_use]
+    pub fn change(&self) -> f64 {
+        (self.after_rate - self.before_rate) * 100.0
+    }
+
+    /// Overall branch coverage rate change in percentage points, or `None`
+    /// when either side has no branch data (nothing to compare against).
+    #[must_use]
+    pub fn branch_change(&self) -> Option<f64> {
+        Some((self.after_branch_rate? - self.before_branch_rate?) * 100.0)
+    }
+
+    /// Overall function coverage rate change in percentage points, or
+    /// `None` when either side has no function data (nothing to compare
+    /// against).
+    #[must_use]
+    pub fn function_change(&self) -> Option<f64> {
+        Some((self.after_function_rate? - self.before_function_rate?) * 100.0)
+    }
+
+    /// Files whose coverage dropped between base and head, worst first.
+    #[must_use]
+    pub fn regressions(&self) -> Vec<&FileCoverageDelta> {
+        let mut regressed: Vec<&FileCoverageDelta> =
+            self.files.iter().filter(|f| f.regressed()).collect();
+        regressed.sort_by(|a, b| {
+            let a_drop = a.before.unwrap_or(0.0) - a.after.unwrap_or(0.0);
+            let b_drop = b.before.unwrap_or(0.0) - b.after.unwrap_or(0.0);
+            b_drop.partial_cmp(&a_drop).unwrap()
+        });
+        regressed
+    }
+}
+
+/// Compare `base` against `head`, unioning files by path.
+#[must_use]
+pub fn compare(base: &CoverageData, head: &CoverageData) -> CoverageDelta {
+    let mut paths: std::collections::BTreeSet<&str> = std::collections::BTreeSet::new();
+    paths.extend(base.files.iter().map(|f| f.path.as_str()));
+    paths.extend(head.files.iter().map(|f| f.path.as_str()));
+
+    let files: Vec<FileCoverageDelta> = paths
+        .into_iter()
+        .map(|path| {
+            let before_file = base.files.iter().find(|f| f.path == path);
+            let after_file = head.files.iter().find(|f| f.path == path);
+            let (newly_covered, newly_missed) = line_set_diff(before_file, after_file);
+            FileCoverageDelta {
+                path: path.to_string(),
+                before: before_file.map(line_rate),
+                after: after_file.map(line_rate),
+                branch_before: before_file.and_then(branch_rate),
+                branch_after: after_file.and_then(branch_rate),
+                function_before: before_file.and_then(function_rate),
+                function_after: after_file.and_then(function_rate),
+                newly_covered,
+                newly_missed,
+            }
+        })
+        .collect();
+
+    CoverageDelta {
+        before_rate: aggregate_line_rate(base),
+        after_rate: aggregate_line_rate(head),
+        before_branch_rate: aggregate_branch_rate(base),
+        after_branch_rate: aggregate_branch_rate(head),
+        before_function_rate: aggregate_function_rate(base),
+        after_function_rate: aggregate_function_rate(head),
+        files,
+    }
+}
+
+/// A single file's line coverage rate.
+fn line_rate(file: &crate::model::FileCoverage) -> f64 {
+    let total = file.lines.len() as u64;
+    let covered = file.lines.iter().filter(|l| l.hit_count > 0).count() as u64;
+    rate(covered, total)
+}
+
+/// A single file's branch coverage rate, or `None` when it has no branches.
+fn branch_rate(file: &crate::model::FileCoverage) -> Option<f64> {
+    if file.branches.is_empty() {
+        return None;
+    }
+    let total = file.branches.len() as u64;
+    let covered = file.branches.iter().filter(|b| b.hit_count > 0).count() as u64;
+    Some(rate(covered, total))
+}
+
+/// A single file's function coverage rate, or `None` when it has no functions.
+fn function_rate(file: &crate::model::FileCoverage) -> Option<f64> {
+    if file.functions.is_empty() {
+        return None;
+    }
+    let total = file.functions.len() as u64;
+    let covered = file.functions.iter().filter(|f| f.hit_count > 0).count() as u64;
+    Some(rate(covered, total))
+}
+
+/// Lines that flipped coverage state between `before`/`after`, as
+/// `(newly_covered, newly_missed)`. A line absent from either side (not
+/// instrumentable there) never counts as a flip. Computed in a single pass
+/// over `before`'s hit counts since both lists need it.
+fn line_set_diff(
+    before: Option<&crate::model::FileCoverage>,
+    after: Option<&crate::model::FileCoverage>,
+) -> (Vec<u32>, Vec<u32>) {
+    let (Some(before), Some(after)) = (before, after) else {
+        return (Vec::new(), Vec::new());
+    };
+    let before_hits: std::collections::HashMap<u32, u64> = before
+        .lines
+        .iter()
+        .map(|l| (l.line_number, l.hit_count))
+        .collect();
+
+    let mut newly_covered = Vec::new();
+    let mut newly_missed = Vec::new();
+    for line in &after.lines {
+        let Some(&before_hit_count) = before_hits.get(&line.line_number) else {
+            continue;
+        };
+        let was_covered = before_hit_count > 0;
+        let is_covered = line.hit_count > 0;
+        if !was_covered && is_covered {
+            newly_covered.push(line.line_number);
+        } else if was_covered && !is_covered {
+            newly_missed.push(line.line_number);
+        }
+    }
+    newly_covered.sort_unstable();
+    newly_missed.sort_unstable();
+    (newly_covered, newly_missed)
+}
+
+/// Line coverage rate across every file in a report.
+fn aggregate_line_rate(data: &CoverageData) -> f64 {
+    let total: u64 = data.files.iter().map(|f| f.lines.len() as u64).sum();
+    let covered: u64 = data
+        .files
+        .iter()
+        .flat_map(|f| &f.lines)
+        .filter(|l| l.hit_count > 0)
+        .count() as u64;
+    rate(covered, total)
+}
+
+/// Branch coverage rate across every file in a report, or `None` when no
+/// file in the report has any branch data.
+fn aggregate_branch_rate(data: &CoverageData) -> Option<f64> {
+    let total: u64 = data.files.iter().map(|f| f.branches.len() as u64).sum();
+    if total == 0 {
+        return None;
+    }
+    let covered: u64 = data
+        .files
+        .iter()
+        .flat_map(|f| &f.branches)
+        .filter(|b| b.hit_count > 0)
+        .count() as u64;
+    Some(rate(covered, total))
+}
+
+/// Function coverage rate across every file in a report, or `None` when no
+/// file in the report has any function data.
+fn aggregate_function_rate(data: &CoverageData) -> Option<f64> {
+    let total: u64 = data.files.iter().map(|f| f.functions.len() as u64).sum();
+    if total == 0 {
+        return None;
+    }
+    let covered: u64 = data
+        .files
+        .iter()
+        .flat_map(|f| &f.functions)
+        .filter(|f| f.hit_count > 0)
+        .count() as u64;
+    Some(rate(covered, total))
+}
+
+/// Build annotations for lines that are both uncovered in `head` *and*
+/// newly changed according to `diff_lines` (path -> added line numbers,
+/// e.g. from [`crate::diff::parse_diff`]), so reviewers see regressions on
+/// the lines the PR actually touched rather than every pre-existing gap.
+#[must_use]
+pub fn annotate_new_regressions(
+    head: &CoverageData,
+    diff_lines: &std::collections::HashMap<String, Vec<u32>>,
+) -> Vec<Annotation> {
+    let mut annotations = Vec::new();
+
+    for file in &head.files {
+        let Some(changed_lines) = diff_lines.get(&file.path) else {
+            continue;
+        };
+
+        for line in &file.lines {
+            if line.hit_count == 0 && changed_lines.contains(&line.line_number) {
+                annotations.push(Annotation {
+                    path: file.path.clone(),
+                    start_line: line.line_number,
+                    end_line: line.line_number,
+                    message: format!(
+                        "Line {} is uncovered and was changed in this diff",
+                        line.line_number
+                    ),
+                });
+            }
+        }
+    }
+
+    annotations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{FileCoverage, LineCoverage};
+
+    fn file(path: &str, hit_counts: &[u64]) -> FileCoverage {
+        FileCoverage {
+            path: path.to_string(),
+            lines: hit_counts
+                .iter()
+                .enumerate()
+                .map(|(i, &hit_count)| LineCoverage {
+                    line_number: (i + 1) as u32,
+                    hit_count,
+                })
+                .collect(),
+            branches: vec![],
+            functions: vec![],
+        }
+    }
+
+    #[test]
+    fn test_compare_detects_regression() {
+        let base = CoverageData {
+            files: vec![file("src/foo.rs", &[1, 1, 1, 1])],
+            ..Default::default()
+        };
+        let head = CoverageData {
+            files: vec![file("src/foo.rs", &[1, 0, 0, 1])],
+            ..Default::default()
+        };
+
+        let delta = compare(&base, &head);
+
+        assert_eq!(delta.before_rate, 1.0);
+        assert_eq!(delta.after_rate, 0.5);
+        assert!(delta.change() < 0.0);
+        assert_eq!(delta.regressions().len(), 1);
+        assert_eq!(delta.regressions()[0].path, "src/foo.rs");
+    }
+
+    #[test]
+    fn test_compare_new_file_is_not_a_regression() {
+        let base = CoverageData {
+            files: vec![],
+            ..Default::default()
+        };
+        let head = CoverageData {
+            files: vec![file("src/new.rs", &[0, 0])],
+            ..Default::default()
+        };
+
+        let delta = compare(&base, &head);
+
+        assert!(delta.regressions().is_empty());
+        let new_file = delta.files.iter().find(|f| f.path == "src/new.rs").unwrap();
+        assert_eq!(new_file.before, None);
+        assert_eq!(new_file.after, Some(0.0));
+    }
+
+    #[test]
+    fn test_compare_passes_when_coverage_improves() {
+        let base = CoverageData {
+            files: vec![file("src/foo.rs", &[1, 0])],
+            ..Default::default()
+        };
+        let head = CoverageData {
+            files: vec![file("src/foo.rs", &[1, 1])],
+            ..Default::default()
+        };
+
+        let delta = compare(&base, &head);
+
+        assert!(delta.regressions().is_empty());
+        assert!(delta.change() > 0.0);
+    }
+
+    #[test]
+    fn test_annotate_new_regressions_only_flags_changed_uncovered_lines() {
+        let head = CoverageData {
+            files: vec![file("src/foo.rs", &[1, 0, 0])],
+            ..Default::default()
+        };
+        let mut diff_lines = std::collections::HashMap::new();
+        diff_lines.insert("src/foo.rs".to_string(), vec![2]);
+
+        let annotations = annotate_new_regressions(&head, &diff_lines);
+
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].start_line, 2);
+    }
+
+    fn file_with_branches(path: &str, hit_counts: &[u64], branch_hits: &[u64]) -> FileCoverage {
+        let mut f = file(path, hit_counts);
+        f.branches = branch_hits
+            .iter()
+            .enumerate()
+            .map(|(i, &hit_count)| crate::model::BranchCoverage {
+                line_number: (i + 1) as u32,
+                branch_index: 0,
+                hit_count,
+                group_id: None,
+                kind: crate::model::BranchKind::Unknown,
+                arm_line: None,
+            })
+            .collect();
+        f
+    }
+
+    #[test]
+    fn test_compare_newly_covered_and_newly_missed_lines() {
+        let base = CoverageData {
+            files: vec![file("src/foo.rs", &[1, 0, 1, 0])],
+            ..Default::default()
+        };
+        let head = CoverageData {
+            files: vec![file("src/foo.rs", &[0, 1, 1, 0])],
+            ..Default::default()
+        };
+
+        let delta = compare(&base, &head);
+        let f = delta.files.iter().find(|f| f.path == "src/foo.rs").unwrap();
+
+        assert_eq!(f.newly_covered, vec![2]);
+        assert_eq!(f.newly_missed, vec![1]);
+    }
+
+    #[test]
+    fn test_compare_branch_rate_change() {
+        let base = CoverageData {
+            files: vec![file_with_branches("src/foo.rs", &[1, 1], &[1, 0])],
+            ..Default::default()
+        };
+        let head = CoverageData {
+            files: vec![file_with_branches("src/foo.rs", &[1, 1], &[1, 1])],
+            ..Default::default()
+        };
+
+        let delta = compare(&base, &head);
+
+        assert_eq!(delta.before_branch_rate, Some(0.5));
+        assert_eq!(delta.after_branch_rate, Some(1.0));
+        assert_eq!(delta.branch_change(), Some(50.0));
+    }
+
+    #[test]
+    fn test_compare_branch_rate_none_when_no_branch_data() {
+        let base = CoverageData {
+            files: vec![file("src/foo.rs", &[1, 1])],
+            ..Default::default()
+        };
+        let head = CoverageData {
+            files: vec![file("src/foo.rs", &[1, 1])],
+            ..Default::default()
+        };
+
+        let delta = compare(&base, &head);
+
+        assert_eq!(delta.before_branch_rate, None);
+        assert_eq!(delta.branch_change(), None);
+    }
+}
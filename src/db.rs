@@ -2,16 +2,48 @@ use chrono::Utc;
 use rusqlite::{params, Connection, Transaction};
 use std::collections::HashMap;
 use std::path::Path;
-
-use anyhow::{bail, Result};
+use thiserror::Error;
 
 use crate::model::{
-    CoverageData, FileCoverage, FileDiffCoverage, FileSummary, LineDetail, ReportInfo,
-    ReportSummary,
+    merge_files, BranchCoverage, BranchKind, CoverageData, FileBranchDiffCoverage, FileCoverage,
+    FileDiffCoverage, FileFunctionDiffCoverage, FileSummary, FunctionCoverage, LineCoverage,
+    LineDetail, ReportInfo, ReportSummary,
 };
 
 const SCHEMA: &str = include_str!("../schema.sql");
 
+/// Errors produced by the database layer. Callers that only care about
+/// propagating failures can keep using `anyhow::Result` and `?` — this type
+/// implements `std::error::Error`, so `anyhow::Error::from` picks it up for
+/// free. Callers that need to branch on a specific failure (e.g.
+/// [`merge_into_report`] treating a missing target report as empty) can
+/// match on the variant instead of parsing the message.
+#[derive(Error, Debug)]
+pub enum CovrsDbError {
+    #[error("Report '{0}' already exists. Use --name to choose a different name, or delete it first.")]
+    ReportExists(String),
+
+    #[error("No reports in database. Run 'covrs ingest' first.")]
+    NoReports,
+
+    #[error("Source file not found: {0}")]
+    SourceFileNotFound(String),
+
+    #[error("Report not found: {0}")]
+    ReportNotFound(String),
+
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// Bridges an upstream failure (e.g. a parser error surfacing through
+    /// [`insert_coverage_streaming`]'s `with_files` callback) into this type
+    /// without needing a dedicated variant for every possible cause.
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+pub type Result<T> = std::result::Result<T, CovrsDbError>;
+
 /// Open (or create) the covrs database at the given path.
 pub fn open(path: &Path) -> Result<Connection> {
     let conn = Connection::open(path)?;
@@ -40,7 +72,7 @@ pub fn insert_coverage(
     data: &CoverageData,
     overwrite: bool,
 ) -> Result<i64> {
-    insert_coverage_streaming(conn, name, source_format, source_file, overwrite, |emit| {
+    insert_coverage_streaming(conn, name, source_format, source_file, overwrite, None, |emit| {
         for file in &data.files {
             emit(file)?;
         }
@@ -53,12 +85,19 @@ pub fn insert_coverage(
 /// The closure should call `emit` once per source file. This lets callers
 /// pipe parsed files directly into the database without collecting them
 /// all into memory first.
+///
+/// When `max_reports` is `Some(n)`, reports beyond the newest `n` (by
+/// `created_at`, including the one just inserted) are pruned in the same
+/// transaction — see [`prune_reports`] for a standalone equivalent. This
+/// keeps CI pipelines that ingest a report per commit from growing the
+/// database without bound.
 pub fn insert_coverage_streaming(
     conn: &mut Connection,
     name: &str,
     source_format: &str,
     source_file: Option<&str>,
     overwrite: bool,
+    max_reports: Option<u32>,
     with_files: impl FnOnce(&mut dyn FnMut(&FileCoverage) -> Result<()>) -> Result<()>,
 ) -> Result<i64> {
     let tx = conn.transaction()?;
@@ -71,22 +110,112 @@ pub fn insert_coverage_streaming(
 
     if overwrite {
         // Clean up source files orphaned by the delete
-        tx.execute(
-            "DELETE FROM source_file WHERE id NOT IN (
-                 SELECT DISTINCT source_file_id FROM line_coverage
-                 UNION
-                 SELECT DISTINCT source_file_id FROM branch_coverage
-                 UNION
-                 SELECT DISTINCT source_file_id FROM function_coverage
-             )",
-            [],
-        )?;
+        tx.execute(DELETE_ORPHANED_SOURCE_FILES_SQL, [])?;
+    }
+
+    if let Some(n) = max_reports {
+        prune_reports_tx(&tx, &PruneBy::NewestN(n))?;
     }
 
     tx.commit()?;
     Ok(report_id)
 }
 
+/// How to select which reports to keep when pruning — see [`prune_reports`].
+#[derive(Debug, Clone)]
+pub enum PruneBy {
+    /// Keep only the newest `n` reports (by `created_at`); delete the rest.
+    NewestN(u32),
+    /// Delete any report created strictly before this RFC 3339 timestamp.
+    OlderThan(String),
+}
+
+/// Delete reports per `keep`, then garbage collect any `source_file` rows
+/// that deletion orphaned. Runs in a single transaction so
+/// `get_summary`/`diff_coverage` — which combine hit counts across all
+/// reports — never observe a half-deleted report. Returns the number of
+/// reports deleted.
+pub fn prune_reports(conn: &mut Connection, keep: PruneBy) -> Result<u64> {
+    let tx = conn.transaction()?;
+    let deleted = prune_reports_tx(&tx, &keep)?;
+    tx.commit()?;
+    Ok(deleted)
+}
+
+fn prune_reports_tx(tx: &Transaction, keep: &PruneBy) -> Result<u64> {
+    let deleted = match keep {
+        PruneBy::NewestN(n) => tx.execute(
+            "DELETE FROM report WHERE id NOT IN (
+                 SELECT id FROM report ORDER BY created_at DESC LIMIT ?1
+             )",
+            params![n],
+        )?,
+        PruneBy::OlderThan(ts) => {
+            tx.execute("DELETE FROM report WHERE created_at < ?1", params![ts])?
+        }
+    };
+
+    if deleted > 0 {
+        tx.execute(DELETE_ORPHANED_SOURCE_FILES_SQL, [])?;
+    }
+
+    Ok(deleted as u64)
+}
+
+/// Deletes `source_file` rows no longer referenced by any coverage table —
+/// the anti-join also used after `overwrite` deletes a report, and by
+/// [`compact`] to garbage-collect orphans left by any other deletion.
+const DELETE_ORPHANED_SOURCE_FILES_SQL: &str = "DELETE FROM source_file WHERE id NOT IN (
+     SELECT DISTINCT source_file_id FROM line_coverage
+     UNION
+     SELECT DISTINCT source_file_id FROM branch_coverage
+     UNION
+     SELECT DISTINCT source_file_id FROM function_coverage
+ )";
+
+/// Before/after disk usage and rows removed by [`compact`].
+#[derive(Debug)]
+pub struct CompactStats {
+    pub orphaned_files_removed: u64,
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+/// Reclaim space left behind by deleted or overwritten reports: garbage
+/// collect `source_file` rows no longer referenced by any coverage table,
+/// refresh the query planner statistics the union subqueries rely on, and
+/// `VACUUM` to shrink the database file on disk.
+///
+/// `VACUUM` rebuilds the whole file and cannot run inside a transaction, so
+/// the GC step runs in its own transaction first and `VACUUM`/`ANALYZE`
+/// follow once it commits.
+pub fn compact(conn: &mut Connection) -> Result<CompactStats> {
+    let size_before = conn.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? as u64;
+
+    let tx = conn.transaction()?;
+    let orphaned_files_removed = tx.execute(DELETE_ORPHANED_SOURCE_FILES_SQL, [])? as u64;
+    tx.commit()?;
+
+    conn.execute_batch("ANALYZE;")?;
+    conn.execute_batch("VACUUM;")?;
+
+    let size_after = conn.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? as u64;
+
+    Ok(CompactStats {
+        orphaned_files_removed,
+        size_before,
+        size_after,
+    })
+}
+
 /// Maximum rows per multi-row INSERT batch. Kept well under SQLite's
 /// default `SQLITE_MAX_VARIABLE_NUMBER` (32 766 for bundled builds).
 /// 2 000 rows × 6 params (the widest statement) = 12 000 parameters.
@@ -194,11 +323,9 @@ fn insert_coverage_tx(
         rusqlite::Error::SqliteFailure(ref err, _)
             if err.code == rusqlite::ErrorCode::ConstraintViolation =>
         {
-            anyhow::anyhow!(
-                "Report '{name}' already exists. Use --name to choose a different name, or delete it first."
-            )
+            CovrsDbError::ReportExists(name.to_string())
         }
-        other => anyhow::Error::from(other),
+        other => CovrsDbError::Sqlite(other),
     })?;
     let report_id = tx.last_insert_rowid();
 
@@ -212,9 +339,9 @@ fn insert_coverage_tx(
     );
     let mut branches = BatchInsert::new(
         tx,
-        "INSERT OR REPLACE INTO branch_coverage (report_id, source_file_id, line_number, branch_index, hit_count) VALUES",
+        "INSERT OR REPLACE INTO branch_coverage (report_id, source_file_id, line_number, branch_index, hit_count, group_id) VALUES",
         "",
-        5,
+        6,
     );
     let mut functions = BatchInsert::new(
         tx,
@@ -244,6 +371,7 @@ fn insert_coverage_tx(
                 (branch.line_number as i64).into(),
                 (branch.branch_index as i64).into(),
                 (branch.hit_count as i64).into(),
+                opt_u32(branch.group_id),
             ])?;
         }
         for func in &file_cov.functions {
@@ -311,14 +439,12 @@ fn get_or_insert_source_file_owned(
 
 // ── Query helpers ──────────────────────────────────────────────────────────
 
-/// Returns true when there are multiple reports in the database, meaning
-/// queries must use GROUP BY / MAX(hit_count) to implement union semantics
-/// (a line is covered if ANY report covers it). When there is at most one
-/// report every (source_file_id, line_number) tuple is already unique
-/// (enforced by the primary key) so the grouping can be skipped.
-fn needs_union(conn: &Connection) -> Result<bool> {
-    let count: u32 = conn.query_row("SELECT COUNT(*) FROM report", [], |row| row.get(0))?;
-    Ok(count > 1)
+/// Number of reports currently in the database. When there is at most one,
+/// every (source_file_id, line_number) tuple is already unique (enforced by
+/// the primary key), so query helpers can skip the GROUP BY / merge-mode
+/// subqueries in [`union_source`] entirely.
+fn report_count(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("SELECT COUNT(*) FROM report", [], |row| row.get(0))?)
 }
 
 /// Which coverage table to build a union source for.
@@ -333,32 +459,82 @@ enum UnionKind {
 }
 
 /// Returns a SQL fragment (table name or subquery) that collapses duplicate
-/// rows via MAX(hit_count) when `union` is true, or the raw table when false.
-fn union_source(union: bool, kind: UnionKind) -> &'static str {
-    match (union, kind) {
-        (false, UnionKind::Line | UnionKind::LinePerFile) => "line_coverage",
-        (true, UnionKind::Line) => {
-            "(SELECT source_file_id, MAX(hit_count) AS hit_count \
-              FROM line_coverage GROUP BY source_file_id, line_number)"
+/// rows across reports according to `mode` (see [`MergeMode`]), or the raw
+/// table when there's at most one report to begin with.
+///
+/// `Intersection` normalizes over the set of keys present in at least one
+/// report (same `GROUP BY` as `Union`/`Sum`, so the row isn't dropped) but
+/// only keeps the combined hit count when every report contributed a row
+/// for that key (`COUNT(*) = report_count`); otherwise it reports 0
+/// (uncovered) rather than spuriously covered.
+fn union_source(mode: MergeMode, kind: UnionKind, report_count: u32) -> String {
+    if report_count <= 1 {
+        return match kind {
+            UnionKind::Line | UnionKind::LinePerFile => "line_coverage",
+            UnionKind::Branch | UnionKind::BranchPerFile => "branch_coverage",
+            UnionKind::Function => "function_coverage",
         }
-        (true, UnionKind::LinePerFile) => {
-            "(SELECT source_file_id, line_number, MAX(hit_count) AS hit_count \
+        .to_string();
+    }
+
+    if mode == MergeMode::Intersection {
+        return match kind {
+            UnionKind::Line => format!(
+                "(SELECT source_file_id, \
+                         CASE WHEN COUNT(*) = {report_count} THEN MIN(hit_count) ELSE 0 END AS hit_count \
+                  FROM line_coverage GROUP BY source_file_id, line_number)"
+            ),
+            UnionKind::LinePerFile => format!(
+                "(SELECT source_file_id, line_number, \
+                         CASE WHEN COUNT(*) = {report_count} THEN MIN(hit_count) ELSE 0 END AS hit_count \
+                  FROM line_coverage GROUP BY source_file_id, line_number)"
+            ),
+            UnionKind::Branch => format!(
+                "(SELECT CASE WHEN COUNT(*) = {report_count} THEN MIN(hit_count) ELSE 0 END AS hit_count \
+                  FROM branch_coverage GROUP BY source_file_id, line_number, branch_index)"
+            ),
+            UnionKind::BranchPerFile => format!(
+                "(SELECT source_file_id, line_number, branch_index, \
+                         CASE WHEN COUNT(*) = {report_count} THEN MIN(hit_count) ELSE 0 END AS hit_count, \
+                         MAX(group_id) AS group_id \
+                  FROM branch_coverage GROUP BY source_file_id, line_number, branch_index)"
+            ),
+            UnionKind::Function => format!(
+                "(SELECT source_file_id, name, start_line, \
+                         CASE WHEN COUNT(*) = {report_count} THEN MIN(hit_count) ELSE 0 END AS hit_count \
+                  FROM function_coverage GROUP BY source_file_id, name, COALESCE(start_line, -1))"
+            ),
+        };
+    }
+
+    let agg = match mode {
+        MergeMode::Union => "MAX(hit_count)",
+        MergeMode::Sum => "SUM(hit_count)",
+        MergeMode::Intersection => unreachable!("handled above"),
+    };
+
+    match kind {
+        UnionKind::Line => format!(
+            "(SELECT source_file_id, {agg} AS hit_count \
               FROM line_coverage GROUP BY source_file_id, line_number)"
-        }
-        (false, UnionKind::Branch | UnionKind::BranchPerFile) => "branch_coverage",
-        (true, UnionKind::Branch) => {
-            "(SELECT MAX(hit_count) AS hit_count \
+        ),
+        UnionKind::LinePerFile => format!(
+            "(SELECT source_file_id, line_number, {agg} AS hit_count \
+              FROM line_coverage GROUP BY source_file_id, line_number)"
+        ),
+        UnionKind::Branch => format!(
+            "(SELECT {agg} AS hit_count \
               FROM branch_coverage GROUP BY source_file_id, line_number, branch_index)"
-        }
-        (true, UnionKind::BranchPerFile) => {
-            "(SELECT source_file_id, MAX(hit_count) AS hit_count \
+        ),
+        UnionKind::BranchPerFile => format!(
+            "(SELECT source_file_id, line_number, branch_index, {agg} AS hit_count, \
+                     MAX(group_id) AS group_id \
               FROM branch_coverage GROUP BY source_file_id, line_number, branch_index)"
-        }
-        (false, UnionKind::Function) => "function_coverage",
-        (true, UnionKind::Function) => {
-            "(SELECT MAX(hit_count) AS hit_count \
+        ),
+        UnionKind::Function => format!(
+            "(SELECT source_file_id, name, start_line, {agg} AS hit_count \
               FROM function_coverage GROUP BY source_file_id, name, COALESCE(start_line, -1))"
-        }
+        ),
     }
 }
 
@@ -373,20 +549,63 @@ pub fn list_reports(conn: &Connection) -> Result<Vec<ReportInfo>> {
             created_at: row.get(2)?,
         })
     })?;
-    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+/// How to combine hit counts for the same line/branch/function across
+/// multiple reports. Threaded through every read-side query helper in this
+/// module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeMode {
+    /// A line/branch/function is covered if ANY report covers it
+    /// (`MAX(hit_count)`). The default, and covrs's only behavior prior to
+    /// this.
+    #[default]
+    Union,
+    /// Covered only if EVERY report that instrumented the key covers it —
+    /// answers "what's covered by every test suite/platform I ingested."
+    /// A key seen by only some reports does not count as covered (nor, in
+    /// the summary-level helpers, is it dropped from the total — see each
+    /// function's docs for how its denominator is normalized).
+    Intersection,
+    /// Aggregate true cross-report execution totals (`SUM(hit_count)`),
+    /// which matters for hot-line analysis and for faithfully merging
+    /// per-test or per-shard runs.
+    Sum,
+}
+
+impl MergeMode {
+    /// Combine an accumulated hit count with a newly-seen one. Saturates on
+    /// `u64` overflow in `Sum` mode (mirrors grcov's behavior when merging
+    /// `CovResult` line maps). `Intersection` accumulates the minimum across
+    /// reports — callers additionally need to check that every report was
+    /// actually seen (see the `seen`/total-report-count bookkeeping in
+    /// [`diff_coverage`] and [`diff_branch_coverage`]), since a key absent
+    /// from some reports must not be treated as covered just because the
+    /// reports it did appear in all hit it.
+    fn combine(self, acc: u64, next: u64) -> u64 {
+        match self {
+            MergeMode::Union => acc.max(next),
+            MergeMode::Sum => acc.saturating_add(next),
+            MergeMode::Intersection => acc.min(next),
+        }
+    }
 }
 
 /// Compute per-file diff coverage detail for lines touched by a diff,
-/// considering ALL reports in the database. A line is covered if any report
-/// has a hit_count > 0 for it.
+/// considering ALL reports in the database. Hit counts across reports are
+/// combined according to `mode` (see [`MergeMode`]); a line is considered
+/// covered when the combined count is > 0, except in `Intersection` mode
+/// where a line also needs a row from every report to count.
 ///
 /// Returns a vec of per-file results (only files that have at least one
 /// instrumentable diff line), plus (total_covered, total_instrumentable).
 pub fn diff_coverage(
     conn: &Connection,
     diff_lines: &HashMap<String, Vec<u32>>,
+    mode: MergeMode,
 ) -> Result<(Vec<FileDiffCoverage>, usize, usize)> {
-    let union = needs_union(conn)?;
+    let total_reports = report_count(conn)?;
     let mut results: Vec<FileDiffCoverage> = Vec::new();
     let mut total_covered: usize = 0;
     let mut total_instrumentable: usize = 0;
@@ -413,18 +632,14 @@ pub fn diff_coverage(
         for chunk in lines.chunks(BATCH_SIZE) {
             let placeholders: String = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
 
-            let sql = if union {
-                format!(
-                    r#"SELECT line_number, MAX(hit_count) FROM line_coverage
-                     WHERE source_file_id = ? AND line_number IN ({placeholders})
-                     GROUP BY line_number"#
-                )
-            } else {
-                format!(
-                    r#"SELECT line_number, hit_count FROM line_coverage
-                     WHERE source_file_id = ? AND line_number IN ({placeholders})"#
-                )
-            };
+            // Fetch raw (line_number, hit_count) rows — one per report that
+            // instrumented the line — and combine them in Rust according to
+            // `mode`. This lets Sum mode add counts across reports without
+            // risking SQLite's SUM() silently promoting to REAL on overflow.
+            let sql = format!(
+                r#"SELECT line_number, hit_count FROM line_coverage
+                 WHERE source_file_id = ? AND line_number IN ({placeholders})"#
+            );
             let mut stmt = conn.prepare(&sql)?;
 
             let params: Vec<rusqlite::types::Value> =
@@ -440,8 +655,25 @@ pub fn diff_coverage(
                 Ok((row.get::<_, u32>(0)?, row.get::<_, u64>(1)?))
             })?;
 
+            // (reports seen, combined hit count) per line.
+            let mut line_hits: HashMap<u32, (u32, u64)> = HashMap::new();
             for row in rows {
                 let (line_number, hit_count) = row?;
+                line_hits
+                    .entry(line_number)
+                    .and_modify(|(seen, acc)| {
+                        *seen += 1;
+                        *acc = mode.combine(*acc, hit_count);
+                    })
+                    .or_insert((1, hit_count));
+            }
+
+            for (line_number, (seen, acc)) in line_hits {
+                let hit_count = if mode == MergeMode::Intersection && seen < total_reports {
+                    0
+                } else {
+                    acc
+                };
                 if hit_count > 0 {
                     covered.push(line_number);
                 } else {
@@ -472,21 +704,262 @@ pub fn diff_coverage(
     Ok((results, total_covered, total_instrumentable))
 }
 
-/// Summary across all reports (union semantics: a line/branch/function is
-/// covered if ANY report covers it).
-pub fn get_summary(conn: &Connection) -> Result<ReportSummary> {
-    let report_count: u32 = conn.query_row("SELECT COUNT(*) FROM report", [], |row| row.get(0))?;
-    if report_count == 0 {
-        bail!("No reports in database. Run 'covrs ingest' first.");
+/// Identifies which decision a branch arm belongs to: arms sharing a
+/// `group_id` on the same line are one decision (MC/DC-style), while an
+/// arm with no `group_id` is its own decision of one (plain branch
+/// counting) — see [`diff_branch_coverage`].
+#[derive(Hash, Eq, PartialEq)]
+enum DecisionKey {
+    Grouped(u32),
+    Arm(u32),
+}
+
+/// Branch/condition coverage for lines touched by a diff, grouped into
+/// decisions (see [`FileBranchDiffCoverage`]) rather than raw arms. A
+/// decision counts as covered only when every arm belonging to it was
+/// exercised at least once — for a simple two-arm `if` this means both
+/// the true and false outcomes were taken; for a decision too complex to
+/// have been broken into conditions by the source format (no
+/// `group_id`), this degrades to counting each arm on its own.
+///
+/// Mirrors [`diff_coverage`]'s shape and aggregation semantics.
+pub fn diff_branch_coverage(
+    conn: &Connection,
+    diff_lines: &HashMap<String, Vec<u32>>,
+    mode: MergeMode,
+) -> Result<(Vec<FileBranchDiffCoverage>, usize, usize)> {
+    let total_reports = report_count(conn)?;
+    let mut results: Vec<FileBranchDiffCoverage> = Vec::new();
+    let mut total_covered: usize = 0;
+    let mut total_decisions: usize = 0;
+
+    for (path, lines) in diff_lines {
+        let file_id: i64 = match conn.query_row(
+            "SELECT id FROM source_file WHERE path = ?1",
+            params![path],
+            |row| row.get(0),
+        ) {
+            Ok(id) => id,
+            Err(rusqlite::Error::QueryReturnedNoRows) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        if lines.is_empty() {
+            continue;
+        }
+
+        // Aggregate raw per-report arm rows down to one hit count (and its
+        // group_id) per (line_number, branch_index), same approach as
+        // `diff_coverage`.
+        let mut arm_hits: HashMap<(u32, u32), (u32, u64, Option<u32>)> = HashMap::new();
+
+        const BATCH_SIZE: usize = 500;
+        for chunk in lines.chunks(BATCH_SIZE) {
+            let placeholders: String = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+
+            let sql = format!(
+                r#"SELECT line_number, branch_index, hit_count, group_id FROM branch_coverage
+                 WHERE source_file_id = ? AND line_number IN ({placeholders})"#
+            );
+            let mut stmt = conn.prepare(&sql)?;
+
+            let params: Vec<rusqlite::types::Value> =
+                std::iter::once(rusqlite::types::Value::Integer(file_id))
+                    .chain(
+                        chunk
+                            .iter()
+                            .map(|&ln| rusqlite::types::Value::Integer(i64::from(ln))),
+                    )
+                    .collect();
+
+            let rows = stmt.query_map(rusqlite::params_from_iter(&params), |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, u64>(2)?,
+                    row.get::<_, Option<u32>>(3)?,
+                ))
+            })?;
+
+            for row in rows {
+                let (line_number, branch_index, hit_count, group_id) = row?;
+                arm_hits
+                    .entry((line_number, branch_index))
+                    .and_modify(|(seen, acc, _)| {
+                        *seen += 1;
+                        *acc = mode.combine(*acc, hit_count);
+                    })
+                    .or_insert((1, hit_count, group_id));
+            }
+        }
+
+        if arm_hits.is_empty() {
+            continue;
+        }
+
+        let mut decisions: HashMap<(u32, DecisionKey), Vec<u64>> = HashMap::new();
+        for ((line_number, branch_index), (seen, acc, group_id)) in arm_hits {
+            let hit_count = if mode == MergeMode::Intersection && seen < total_reports {
+                0
+            } else {
+                acc
+            };
+            let key = match group_id {
+                Some(g) => DecisionKey::Grouped(g),
+                None => DecisionKey::Arm(branch_index),
+            };
+            decisions.entry((line_number, key)).or_default().push(hit_count);
+        }
+
+        let mut covered: Vec<u32> = Vec::new();
+        let mut missed: Vec<u32> = Vec::new();
+        let mut partial: Vec<(u32, usize, usize)> = Vec::new();
+        for ((line_number, _key), hits) in decisions {
+            let taken = hits.iter().filter(|&&h| h > 0).count();
+            if taken == hits.len() {
+                covered.push(line_number);
+            } else {
+                missed.push(line_number);
+                if taken > 0 {
+                    partial.push((line_number, taken, hits.len()));
+                }
+            }
+        }
+
+        covered.sort();
+        missed.sort();
+        partial.sort_by_key(|&(line_number, ..)| line_number);
+
+        total_covered += covered.len();
+        total_decisions += covered.len() + missed.len();
+
+        results.push(FileBranchDiffCoverage {
+            path: path.clone(),
+            covered_lines: covered,
+            missed_lines: missed,
+            partial,
+        });
+    }
+
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok((results, total_covered, total_decisions))
+}
+
+/// Function coverage for functions *defined* on a diff line (by
+/// `start_line`): was the function exercised at all. Mirrors
+/// [`diff_coverage`]'s shape and aggregation semantics.
+pub fn diff_function_coverage(
+    conn: &Connection,
+    diff_lines: &HashMap<String, Vec<u32>>,
+    mode: MergeMode,
+) -> Result<(Vec<FileFunctionDiffCoverage>, usize, usize)> {
+    let total_reports = report_count(conn)?;
+    let mut results: Vec<FileFunctionDiffCoverage> = Vec::new();
+    let mut total_covered: usize = 0;
+    let mut total_functions: usize = 0;
+
+    for (path, lines) in diff_lines {
+        let file_id: i64 = match conn.query_row(
+            "SELECT id FROM source_file WHERE path = ?1",
+            params![path],
+            |row| row.get(0),
+        ) {
+            Ok(id) => id,
+            Err(rusqlite::Error::QueryReturnedNoRows) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        if lines.is_empty() {
+            continue;
+        }
+
+        // (reports seen, combined hit count) per function start_line.
+        let mut fn_hits: HashMap<u32, (u32, u64)> = HashMap::new();
+
+        const BATCH_SIZE: usize = 500;
+        for chunk in lines.chunks(BATCH_SIZE) {
+            let placeholders: String = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+
+            let sql = format!(
+                r#"SELECT start_line, hit_count FROM function_coverage
+                 WHERE source_file_id = ? AND start_line IN ({placeholders})"#
+            );
+            let mut stmt = conn.prepare(&sql)?;
+
+            let params: Vec<rusqlite::types::Value> =
+                std::iter::once(rusqlite::types::Value::Integer(file_id))
+                    .chain(
+                        chunk
+                            .iter()
+                            .map(|&ln| rusqlite::types::Value::Integer(i64::from(ln))),
+                    )
+                    .collect();
+
+            let rows = stmt.query_map(rusqlite::params_from_iter(&params), |row| {
+                Ok((row.get::<_, u32>(0)?, row.get::<_, u64>(1)?))
+            })?;
+
+            for row in rows {
+                let (start_line, hit_count) = row?;
+                fn_hits
+                    .entry(start_line)
+                    .and_modify(|(seen, acc)| {
+                        *seen += 1;
+                        *acc = mode.combine(*acc, hit_count);
+                    })
+                    .or_insert((1, hit_count));
+            }
+        }
+
+        if fn_hits.is_empty() {
+            continue;
+        }
+
+        let mut covered: Vec<u32> = Vec::new();
+        let mut missed: Vec<u32> = Vec::new();
+        for (start_line, (seen, acc)) in fn_hits {
+            let hit_count = if mode == MergeMode::Intersection && seen < total_reports {
+                0
+            } else {
+                acc
+            };
+            if hit_count > 0 {
+                covered.push(start_line);
+            } else {
+                missed.push(start_line);
+            }
+        }
+
+        covered.sort();
+        missed.sort();
+
+        total_covered += covered.len();
+        total_functions += covered.len() + missed.len();
+
+        results.push(FileFunctionDiffCoverage {
+            path: path.clone(),
+            covered_lines: covered,
+            missed_lines: missed,
+        });
+    }
+
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok((results, total_covered, total_functions))
+}
+
+/// Summary across all reports, combined according to `mode` (see
+/// [`MergeMode`]).
+pub fn get_summary(conn: &Connection, mode: MergeMode) -> Result<ReportSummary> {
+    let count = report_count(conn)?;
+    if count == 0 {
+        return Err(CovrsDbError::NoReports);
     }
 
-    // When there is only one report every (source_file_id, line_number)
-    // tuple is already unique (enforced by the PK) so we can skip the
-    // GROUP BY / MAX(hit_count) subqueries.
-    let union = report_count > 1;
-    let line_src = union_source(union, UnionKind::Line);
-    let branch_src = union_source(union, UnionKind::Branch);
-    let function_src = union_source(union, UnionKind::Function);
+    let line_src = union_source(mode, UnionKind::Line, count);
+    let branch_src = union_source(mode, UnionKind::Branch, count);
+    let function_src = union_source(mode, UnionKind::Function, count);
 
     let (total_files, total_lines, covered_lines): (u64, u64, u64) = conn.query_row(
         &format!(
@@ -518,6 +991,8 @@ pub fn get_summary(conn: &Connection) -> Result<ReportSummary> {
         |row| Ok((row.get(0)?, row.get(1)?)),
     )?;
 
+    let (total_conditions, independently_covered_conditions) = mcdc_totals(conn, mode, count)?;
+
     Ok(ReportSummary {
         total_files,
         total_lines,
@@ -526,14 +1001,81 @@ pub fn get_summary(conn: &Connection) -> Result<ReportSummary> {
         covered_branches,
         total_functions,
         covered_functions,
+        total_conditions,
+        independently_covered_conditions,
     })
 }
 
-/// Per-file coverage summaries across all reports (union semantics).
-pub fn get_file_summaries(conn: &Connection) -> Result<Vec<FileSummary>> {
-    let union = needs_union(conn)?;
-    let line_src = union_source(union, UnionKind::LinePerFile);
-    let branch_src = union_source(union, UnionKind::BranchPerFile);
+/// `(total_conditions, independently_covered_conditions)` across every
+/// decision in the database (see [`crate::model::group_decisions`]).
+/// Arms with no `group_id` — the common case for formats that don't
+/// distinguish conditions within a decision — contribute to neither
+/// count, so `mcdc_rate` stays 0.0 unless at least one ingested report
+/// supplied grouping (currently only the Clover parser does).
+fn mcdc_totals(conn: &Connection, mode: MergeMode, report_count: u32) -> Result<(u64, u64)> {
+    let branch_src = union_source(mode, UnionKind::BranchPerFile, report_count);
+    let sql = format!(
+        "SELECT source_file_id, line_number, group_id, hit_count
+         FROM {branch_src}
+         WHERE group_id IS NOT NULL
+         ORDER BY source_file_id, line_number, group_id, branch_index"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query([])?;
+
+    let mut total_conditions = 0u64;
+    let mut independently_covered = 0u64;
+    let mut current_key: Option<(i64, u32, u32)> = None;
+    let mut pending_arm: Option<u64> = None;
+
+    while let Some(row) = rows.next()? {
+        let key: (i64, u32, u32) = (row.get(0)?, row.get(1)?, row.get(2)?);
+        let hit_count: u64 = row.get(3)?;
+
+        if current_key != Some(key) {
+            pending_arm = None;
+            current_key = Some(key);
+        }
+
+        match pending_arm.take() {
+            // Second arm of a condition — the first was its true arm,
+            // this its false arm (see the Clover parser's push order).
+            Some(true_hit) => {
+                total_conditions += 1;
+                if true_hit > 0 && hit_count > 0 {
+                    independently_covered += 1;
+                }
+            }
+            None => pending_arm = Some(hit_count),
+        }
+    }
+
+    Ok((total_conditions, independently_covered))
+}
+
+/// Per-file coverage summaries across all reports, combined according to
+/// `mode` (see [`MergeMode`]).
+pub fn get_file_summaries(conn: &Connection, mode: MergeMode) -> Result<Vec<FileSummary>> {
+    let mut summaries = Vec::new();
+    for_each_file_summary(conn, mode, |summary| {
+        summaries.push(summary);
+        Ok(())
+    })?;
+    Ok(summaries)
+}
+
+/// Streaming variant of [`get_file_summaries`]: hands each file's summary to
+/// `f` as it is read from the cursor instead of collecting them all into a
+/// `Vec` first, so a renderer walking a very large monorepo never holds more
+/// than one row in memory at a time.
+pub fn for_each_file_summary(
+    conn: &Connection,
+    mode: MergeMode,
+    mut f: impl FnMut(FileSummary) -> Result<()>,
+) -> Result<()> {
+    let count = report_count(conn)?;
+    let line_src = union_source(mode, UnionKind::LinePerFile, count);
+    let branch_src = union_source(mode, UnionKind::BranchPerFile, count);
 
     let sql = format!(
         "SELECT sf.path,
@@ -555,26 +1097,28 @@ pub fn get_file_summaries(conn: &Connection) -> Result<Vec<FileSummary>> {
     );
 
     let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query([])?;
 
-    let rows = stmt.query_map([], |row| {
-        Ok(FileSummary {
+    while let Some(row) = rows.next()? {
+        f(FileSummary {
             path: row.get(0)?,
             total_lines: row.get(1)?,
             covered_lines: row.get(2)?,
             total_branches: row.get(3)?,
             covered_branches: row.get(4)?,
-        })
-    })?;
+        })?;
+    }
 
-    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    Ok(())
 }
 
-/// Total line coverage rate for a single file (union semantics).
+/// Total line coverage rate for a single file, combined according to `mode`
+/// (see [`MergeMode`]).
 ///
 /// Returns `None` if the file is not in the database.
-pub fn get_file_line_rate(conn: &Connection, path: &str) -> Result<Option<f64>> {
-    let union = needs_union(conn)?;
-    let line_src = union_source(union, UnionKind::LinePerFile);
+pub fn get_file_line_rate(conn: &Connection, path: &str, mode: MergeMode) -> Result<Option<f64>> {
+    let count = report_count(conn)?;
+    let line_src = union_source(mode, UnionKind::LinePerFile, count);
 
     let sql = format!(
         "SELECT COUNT(*) AS total,
@@ -594,39 +1138,350 @@ pub fn get_file_line_rate(conn: &Connection, path: &str) -> Result<Option<f64>>
     }
 }
 
-/// Line-level detail for a source file across all reports (union semantics).
-pub fn get_lines(conn: &Connection, source_path: &str) -> Result<Vec<LineDetail>> {
+/// Reconstruct the full `CoverageData` across all reports, combined
+/// according to `mode` (see [`MergeMode`]), for exporters that need to
+/// round-trip the database back to a coverage format (e.g. Cobertura, LCOV).
+pub fn get_full_coverage(conn: &Connection, mode: MergeMode) -> Result<CoverageData> {
+    let count = report_count(conn)?;
+    let line_src = union_source(mode, UnionKind::LinePerFile, count);
+    let branch_src = union_source(mode, UnionKind::BranchPerFile, count);
+
+    let mut files: HashMap<i64, FileCoverage> = HashMap::new();
+    let mut order: Vec<i64> = Vec::new();
+
+    {
+        let sql = format!(
+            "SELECT sf.id, sf.path, lc.line_number, lc.hit_count
+             FROM {line_src} lc
+             JOIN source_file sf ON sf.id = lc.source_file_id
+             ORDER BY sf.path, lc.line_number"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u64>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (file_id, path, line_number, hit_count) = row?;
+            let file = files.entry(file_id).or_insert_with(|| {
+                order.push(file_id);
+                FileCoverage::new(path)
+            });
+            file.lines.push(LineCoverage {
+                line_number,
+                hit_count,
+            });
+        }
+    }
+
+    {
+        let sql = format!(
+            "SELECT source_file_id, line_number, branch_index, hit_count, group_id
+             FROM {branch_src}
+             ORDER BY source_file_id, line_number, branch_index"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u64>(3)?,
+                row.get::<_, Option<u32>>(4)?,
+            ))
+        })?;
+        for row in rows {
+            let (file_id, line_number, branch_index, hit_count, group_id) = row?;
+            if let Some(file) = files.get_mut(&file_id) {
+                file.branches.push(BranchCoverage {
+                    line_number,
+                    branch_index,
+                    hit_count,
+                    group_id,
+                    kind: BranchKind::Unknown, // not yet a stored column
+                    arm_line: None, // not yet a stored column
+                });
+            }
+        }
+    }
+
+    {
+        let function_src = union_source(mode, UnionKind::Function, count);
+        let sql = format!(
+            "SELECT fc.source_file_id, fc.name, fc.start_line, fc.end_line, x.hit_count
+             FROM function_coverage fc
+             JOIN {function_src} x ON x.source_file_id = fc.source_file_id
+                 AND x.name = fc.name
+                 AND COALESCE(x.start_line, -1) = COALESCE(fc.start_line, -1)
+             GROUP BY fc.source_file_id, fc.name, fc.start_line"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<u32>>(2)?,
+                row.get::<_, Option<u32>>(3)?,
+                row.get::<_, u64>(4)?,
+            ))
+        })?;
+        for row in rows {
+            let (file_id, name, start_line, end_line, hit_count) = row?;
+            if let Some(file) = files.get_mut(&file_id) {
+                file.functions.push(FunctionCoverage {
+                    name,
+                    start_line,
+                    end_line,
+                    hit_count,
+                });
+            }
+        }
+    }
+
+    let mut data = CoverageData::new();
+    for id in order {
+        if let Some(file) = files.remove(&id) {
+            data.files.push(file);
+        }
+    }
+    data.files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(data)
+}
+
+/// Load the full coverage data recorded for a single named report, with no
+/// cross-report merging — unlike [`get_full_coverage`], which always
+/// combines every report in the database according to a [`MergeMode`].
+/// Used by [`merge_reports`] to gather the inputs it then combines into a
+/// new report.
+pub fn get_report_coverage(conn: &Connection, report_name: &str) -> Result<CoverageData> {
+    let report_id: i64 = conn
+        .query_row(
+            "SELECT id FROM report WHERE name = ?1",
+            params![report_name],
+            |row| row.get(0),
+        )
+        .map_err(|_| CovrsDbError::ReportNotFound(report_name.to_string()))?;
+
+    let mut files: HashMap<i64, FileCoverage> = HashMap::new();
+    let mut order: Vec<i64> = Vec::new();
+
+    {
+        let mut stmt = conn.prepare(
+            "SELECT sf.id, sf.path, lc.line_number, lc.hit_count
+             FROM line_coverage lc
+             JOIN source_file sf ON sf.id = lc.source_file_id
+             WHERE lc.report_id = ?1
+             ORDER BY sf.path, lc.line_number",
+        )?;
+        let rows = stmt.query_map(params![report_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u64>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (file_id, path, line_number, hit_count) = row?;
+            let file = files.entry(file_id).or_insert_with(|| {
+                order.push(file_id);
+                FileCoverage::new(path)
+            });
+            file.lines.push(LineCoverage {
+                line_number,
+                hit_count,
+            });
+        }
+    }
+
+    {
+        let mut stmt = conn.prepare(
+            "SELECT source_file_id, line_number, branch_index, hit_count, group_id
+             FROM branch_coverage
+             WHERE report_id = ?1
+             ORDER BY source_file_id, line_number, branch_index",
+        )?;
+        let rows = stmt.query_map(params![report_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u64>(3)?,
+                row.get::<_, Option<u32>>(4)?,
+            ))
+        })?;
+        for row in rows {
+            let (file_id, line_number, branch_index, hit_count, group_id) = row?;
+            if let Some(file) = files.get_mut(&file_id) {
+                file.branches.push(BranchCoverage {
+                    line_number,
+                    branch_index,
+                    hit_count,
+                    group_id,
+                    kind: BranchKind::Unknown, // not yet a stored column
+                    arm_line: None, // not yet a stored column
+                });
+            }
+        }
+    }
+
+    {
+        let mut stmt = conn.prepare(
+            "SELECT source_file_id, name, start_line, end_line, hit_count
+             FROM function_coverage
+             WHERE report_id = ?1",
+        )?;
+        let rows = stmt.query_map(params![report_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<u32>>(2)?,
+                row.get::<_, Option<u32>>(3)?,
+                row.get::<_, u64>(4)?,
+            ))
+        })?;
+        for row in rows {
+            let (file_id, name, start_line, end_line, hit_count) = row?;
+            if let Some(file) = files.get_mut(&file_id) {
+                file.functions.push(FunctionCoverage {
+                    name,
+                    start_line,
+                    end_line,
+                    hit_count,
+                });
+            }
+        }
+    }
+
+    let mut data = CoverageData::new();
+    for id in order {
+        if let Some(file) = files.remove(&id) {
+            data.files.push(file);
+        }
+    }
+    data.files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(data)
+}
+
+/// Combine several already-ingested reports (e.g. per-shard CI runs, or
+/// the same suite across OSes) into one new report under `new_name`: for
+/// files sharing a path, lines are unioned by `line_number`, branches by
+/// `(line_number, branch_index)`, and functions by `(name, start_line)`,
+/// each case summing hit counts (see [`crate::model::merge_files`]).
+/// Lines/branches/functions present in only one input are carried through
+/// unchanged. The result is inserted like any other report, so
+/// `get_summary`/`get_lines` work on it unchanged afterwards.
+///
+/// Returns `(report_id, file_count, line_count)` for the merged report.
+pub fn merge_reports(
+    conn: &mut Connection,
+    report_names: &[&str],
+    new_name: &str,
+) -> Result<(i64, usize, usize)> {
+    let mut files = Vec::new();
+    for name in report_names {
+        files.extend(get_report_coverage(conn, name)?.files);
+    }
+    let files = merge_files(files);
+
+    let file_count = files.len();
+    let line_count: usize = files.iter().map(|f| f.lines.len()).sum();
+
+    let data = CoverageData {
+        files,
+        ..Default::default()
+    };
+    let report_id = insert_coverage(conn, new_name, "merged", None, &data, false)?;
+
+    Ok((report_id, file_count, line_count))
+}
+
+/// Fold `incoming` into the named report `target_name`, replacing it —
+/// backs `covrs ingest --merge-into` so a freshly parsed coverage file
+/// (e.g. the next shard of a sharded test run) accumulates into an
+/// existing report row instead of being inserted as a separate one. Same
+/// per-file merge semantics as [`merge_reports`]; if no report named
+/// `target_name` exists yet, this is equivalent to inserting `incoming`
+/// under that name.
+///
+/// Returns `(report_id, file_count, line_count)` for the resulting report.
+pub fn merge_into_report(
+    conn: &mut Connection,
+    target_name: &str,
+    source_format: &str,
+    source_file: Option<&str>,
+    incoming: &CoverageData,
+) -> Result<(i64, usize, usize)> {
+    let mut files = match get_report_coverage(conn, target_name) {
+        Ok(existing) => existing.files,
+        Err(CovrsDbError::ReportNotFound(_)) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    files.extend(incoming.files.clone());
+    let files = merge_files(files);
+
+    let file_count = files.len();
+    let line_count: usize = files.iter().map(|f| f.lines.len()).sum();
+
+    let data = CoverageData {
+        files,
+        ..Default::default()
+    };
+    let report_id = insert_coverage(conn, target_name, source_format, source_file, &data, true)?;
+
+    Ok((report_id, file_count, line_count))
+}
+
+/// Line-level detail for a source file across all reports, combined
+/// according to `mode` (see [`MergeMode`]).
+pub fn get_lines(conn: &Connection, source_path: &str, mode: MergeMode) -> Result<Vec<LineDetail>> {
+    let mut lines = Vec::new();
+    for_each_line(conn, source_path, mode, |line| {
+        lines.push(line);
+        Ok(())
+    })?;
+    Ok(lines)
+}
+
+/// Streaming variant of [`get_lines`]: hands each line to `f` as it is read
+/// from the cursor instead of collecting them all into a `Vec` first, so a
+/// renderer walking a very large file never holds more than one row in
+/// memory at a time.
+pub fn for_each_line(
+    conn: &Connection,
+    source_path: &str,
+    mode: MergeMode,
+    mut f: impl FnMut(LineDetail) -> Result<()>,
+) -> Result<()> {
     let source_file_id: i64 = conn
         .query_row(
             "SELECT id FROM source_file WHERE path = ?1",
             params![source_path],
             |row| row.get(0),
         )
-        .map_err(|_| anyhow::anyhow!("Source file not found: {source_path}"))?;
-
-    let mut stmt = if needs_union(conn)? {
-        conn.prepare(
-            "SELECT line_number, MAX(hit_count) AS hit_count
-             FROM line_coverage
-             WHERE source_file_id = ?1
-             GROUP BY line_number
-             ORDER BY line_number",
-        )?
-    } else {
-        conn.prepare(
-            "SELECT line_number, hit_count
-             FROM line_coverage
-             WHERE source_file_id = ?1
-             ORDER BY line_number",
-        )?
-    };
+        .map_err(|_| CovrsDbError::SourceFileNotFound(source_path.to_string()))?;
+
+    let count = report_count(conn)?;
+    let line_src = union_source(mode, UnionKind::LinePerFile, count);
+    let sql = format!(
+        "SELECT line_number, hit_count
+         FROM {line_src}
+         WHERE source_file_id = ?1
+         ORDER BY line_number"
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(params![source_file_id])?;
 
-    let rows = stmt.query_map(params![source_file_id], |row| {
-        Ok(LineDetail {
+    while let Some(row) = rows.next()? {
+        f(LineDetail {
             line_number: row.get(0)?,
             hit_count: row.get(1)?,
-        })
-    })?;
+        })?;
+    }
 
-    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    Ok(())
 }
@@ -0,0 +1,131 @@
+//! Demangling of mangled Rust/C++ function names in coverage reports.
+//!
+//! Some coverage formats surface raw linker symbols as function names
+//! (e.g. Rust's legacy `_ZN4core...` / v0 `_RNv...` mangling, or Itanium
+//! C++ mangling). This is an opt-in transform over `CoverageData` (applied
+//! per file, before DB insertion, same shape as [`crate::fixup::apply`])
+//! that rewrites `FunctionCoverage::name` to its demangled form. Only Rust
+//! symbols (legacy and v0) are demangled; C++ symbols that are not valid
+//! Rust mangling are left as they are.
+//!
+//! Names that don't look mangled (including the Clover parser's
+//! `<anonymous@N>` placeholder for missing signatures) are passed through
+//! unchanged, and demangling an already-readable name is a no-op, so the
+//! transform is idempotent.
+use crate::model::FileCoverage;
+
+/// Options controlling how a mangled name is rendered.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DemangleOptions {
+    /// Strip the trailing hash suffix some mangling schemes append to
+    /// disambiguate generic instantiations (e.g. Rust's
+    /// `::h1234567890abcdef`). Only a suffix of at least 16 hex digits is
+    /// stripped.
+    pub strip_hash: bool,
+    /// Render with `rustc_demangle`'s alternate format, which drops the
+    /// hash suffix. Generic/template arguments are kept as they are
+    /// (`Vec<T>` still renders as `Vec<T>`).
+    pub hide_template_args: bool,
+}
+
+/// Apply the demangling pass to `file`, rewriting each function's name in
+/// place.
+pub fn apply(file: &mut FileCoverage, opts: DemangleOptions) {
+    for function in &mut file.functions {
+        function.name = demangle_name(&function.name, opts);
+    }
+}
+
+/// Demangle a single function name, passing through anything that doesn't
+/// look like a mangled symbol (including placeholder names like
+/// `<anonymous@12>`).
+#[must_use]
+pub fn demangle_name(name: &str, opts: DemangleOptions) -> String {
+    let Ok(demangled) = rustc_demangle::try_demangle(name) else {
+        return name.to_string();
+    };
+
+    let mut rendered = if opts.hide_template_args {
+        format!("{demangled:#}")
+    } else {
+        format!("{demangled}")
+    };
+
+    if opts.strip_hash {
+        if let Some(idx) = rendered.rfind("::h") {
+            if rendered[idx + 3..]
+                .chars()
+                .all(|c| c.is_ascii_hexdigit())
+                && rendered[idx + 3..].len() >= 16
+            {
+                rendered.truncate(idx);
+            }
+        }
+    }
+
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::FunctionCoverage;
+
+    fn function(name: &str) -> FunctionCoverage {
+        FunctionCoverage {
+            name: name.to_string(),
+            start_line: Some(1),
+            end_line: None,
+            hit_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_passes_through_anonymous_placeholder() {
+        let opts = DemangleOptions::default();
+        assert_eq!(demangle_name("<anonymous@12>", opts), "<anonymous@12>");
+    }
+
+    #[test]
+    fn test_passes_through_plain_name() {
+        let opts = DemangleOptions::default();
+        assert_eq!(demangle_name("do_stuff()", opts), "do_stuff()");
+    }
+
+    #[test]
+    fn test_demangles_legacy_mangled_name() {
+        let opts = DemangleOptions::default();
+        let mangled = "_ZN4core3fmt5Write9write_fmt17h1234567890abcdefE";
+        let demangled = demangle_name(mangled, opts);
+        assert!(demangled.contains("core::fmt::Write::write_fmt"));
+    }
+
+    #[test]
+    fn test_demangles_v0_mangled_name() {
+        let opts = DemangleOptions::default();
+        let mangled = "_RNvC7mycrate3foo";
+        let demangled = demangle_name(mangled, opts);
+        assert!(demangled.contains("mycrate::foo"));
+    }
+
+    #[test]
+    fn test_is_idempotent() {
+        let opts = DemangleOptions::default();
+        let mangled = "_ZN4core3fmt5Write9write_fmt17h1234567890abcdefE";
+        let once = demangle_name(mangled, opts);
+        let twice = demangle_name(&once, opts);
+        assert_eq!(once, twice);
+    }
+
+    #[test]
+    fn test_apply_rewrites_function_names_in_place() {
+        let mut file = FileCoverage::new("f.rs".to_string());
+        file.functions.push(function("_ZN4core3fmt5Write9write_fmt17h1234567890abcdefE"));
+        file.functions.push(function("<anonymous@5>"));
+
+        apply(&mut file, DemangleOptions::default());
+
+        assert!(file.functions[0].name.contains("core::fmt::Write::write_fmt"));
+        assert_eq!(file.functions[1].name, "<anonymous@5>");
+    }
+}
@@ -5,11 +5,13 @@
 /// Also provides a [`DiffSource`] trait that abstracts over different
 /// ways to obtain a diff (stdin, git, GitHub API).
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Command;
 
 use anyhow::{Context, Result};
 
-use crate::github;
+use crate::github::{self, ReviewPlatform};
+use crate::gitlab;
 
 // ---------------------------------------------------------------------------
 // Diff sources
@@ -24,6 +26,15 @@ pub trait DiffSource {
     fn sha(&self) -> Option<&str> {
         None
     }
+
+    /// Added line numbers per file, keyed by the new file's path — the same
+    /// shape [`parse_diff`] produces. The default implementation parses
+    /// [`DiffSource::fetch_diff`]'s unified diff text; sources with
+    /// structured access to the diff (e.g. [`Libgit2Diff`]) can override this
+    /// to skip that text round-trip entirely.
+    fn diff_lines(&self) -> Result<HashMap<String, Vec<u32>>> {
+        Ok(parse_diff(&self.fetch_diff()?))
+    }
 }
 
 /// Diff from stdin.
@@ -59,6 +70,104 @@ impl DiffSource for GitDiff {
     }
 }
 
+/// Diff computed in-process against a local git repository via `libgit2`,
+/// without shelling out to the `git` binary or reparsing patch text (see
+/// [`DiffSource::diff_lines`]).
+pub struct Libgit2Diff {
+    /// Path to the repository (a working directory or a bare repo).
+    pub repo_path: PathBuf,
+    /// Rev-spec to diff against: a single spec (e.g. `HEAD~1`, a branch
+    /// name) diffs that commit's tree against the working directory; a
+    /// `from..to` range diffs the two resolved trees directly.
+    pub rev_spec: String,
+}
+
+impl Libgit2Diff {
+    /// Resolve `self.rev_spec` against `repo` and compute the underlying
+    /// `git2::Diff`, shared by [`fetch_diff`](DiffSource::fetch_diff) and
+    /// [`diff_lines`](DiffSource::diff_lines).
+    fn resolve_diff<'repo>(&self, repo: &'repo git2::Repository) -> Result<git2::Diff<'repo>> {
+        if let Some((from, to)) = self.rev_spec.split_once("..") {
+            let old_tree = resolve_tree(repo, from)?;
+            let new_tree = resolve_tree(repo, to)?;
+            repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)
+                .context("Failed to diff git trees")
+        } else {
+            let tree = resolve_tree(repo, &self.rev_spec)?;
+            repo.diff_tree_to_workdir_with_index(Some(&tree), None)
+                .context("Failed to diff git tree to working directory")
+        }
+    }
+
+    fn open_repo(&self) -> Result<git2::Repository> {
+        git2::Repository::open(&self.repo_path)
+            .with_context(|| format!("Failed to open git repository at {}", self.repo_path.display()))
+    }
+}
+
+/// Resolve a rev-spec (commit, branch, tag, `HEAD~N`, ...) to its tree.
+fn resolve_tree<'repo>(repo: &'repo git2::Repository, rev_spec: &str) -> Result<git2::Tree<'repo>> {
+    repo.revparse_single(rev_spec)
+        .with_context(|| format!("Failed to resolve rev-spec '{rev_spec}'"))?
+        .peel_to_tree()
+        .with_context(|| format!("'{rev_spec}' does not resolve to a tree"))
+}
+
+/// Render a single diff content line (origin `+`/`-`/` `) with its prefix
+/// character restored, matching plain `git diff` patch text.
+fn format_diff_line(line: &git2::DiffLine) -> Vec<u8> {
+    let mut out = Vec::new();
+    if matches!(line.origin(), '+' | '-' | ' ') {
+        out.push(line.origin() as u8);
+    }
+    out.extend_from_slice(line.content());
+    out
+}
+
+impl DiffSource for Libgit2Diff {
+    fn fetch_diff(&self) -> Result<String> {
+        let repo = self.open_repo()?;
+        let diff = self.resolve_diff(&repo)?;
+
+        let mut out = Vec::new();
+        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+            out.extend_from_slice(&format_diff_line(&line));
+            true
+        })
+        .context("Failed to format git diff")?;
+
+        String::from_utf8(out).context("git diff output not valid UTF-8")
+    }
+
+    fn diff_lines(&self) -> Result<HashMap<String, Vec<u32>>> {
+        let repo = self.open_repo()?;
+        let diff = self.resolve_diff(&repo)?;
+
+        let mut result: HashMap<String, Vec<u32>> = HashMap::new();
+        diff.foreach(
+            &mut |_delta, _progress| true,
+            None,
+            None,
+            Some(&mut |delta, _hunk, line| {
+                if line.origin() == '+' {
+                    if let (Some(path), Some(new_lineno)) =
+                        (delta.new_file().path(), line.new_lineno())
+                    {
+                        result
+                            .entry(path.to_string_lossy().into_owned())
+                            .or_default()
+                            .push(new_lineno);
+                    }
+                }
+                true
+            }),
+        )
+        .context("Failed to walk git diff")?;
+
+        Ok(result)
+    }
+}
+
 /// Diff from a GitHub pull request.
 pub struct GitHubDiff {
     /// The resolved GitHub context.
@@ -79,7 +188,76 @@ impl DiffSource for GitHubDiff {
     }
 
     fn sha(&self) -> Option<&str> {
-        self.context.sha.as_deref()
+        self.context.sha()
+    }
+}
+
+/// Diff from a GitLab merge request.
+pub struct GitLabDiff {
+    /// The resolved GitLab context.
+    pub context: gitlab::Context,
+}
+
+impl GitLabDiff {
+    /// Create from environment variables.
+    pub fn from_env() -> Result<Self> {
+        let context = gitlab::Context::from_env()?;
+        Ok(Self { context })
+    }
+}
+
+impl DiffSource for GitLabDiff {
+    fn fetch_diff(&self) -> Result<String> {
+        self.context.fetch_diff()
+    }
+
+    fn sha(&self) -> Option<&str> {
+        self.context.sha()
+    }
+}
+
+/// Which CI platform to talk to for fetching diffs and posting coverage
+/// feedback (`--platform` CLI flag, or autodetected from the CI
+/// environment via [`Platform::from_env`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    GitHub,
+    GitLab,
+}
+
+impl Platform {
+    /// Detect the platform from CI environment variables: `GITLAB_CI` for
+    /// GitLab CI, `GITHUB_ACTIONS` for GitHub Actions. Returns `None` when
+    /// neither is set, so callers can fall back to [`StdinDiff`]/[`GitDiff`].
+    pub fn from_env() -> Option<Self> {
+        if std::env::var_os("GITLAB_CI").is_some() {
+            Some(Platform::GitLab)
+        } else if std::env::var_os("GITHUB_ACTIONS").is_some() {
+            Some(Platform::GitHub)
+        } else {
+            None
+        }
+    }
+
+    /// Resolve the full [`ReviewPlatform`] for this platform from its
+    /// environment variables, for posting comments/annotations.
+    pub fn context_from_env(&self) -> Result<Box<dyn ReviewPlatform>> {
+        match self {
+            Platform::GitHub => Ok(Box::new(github::Context::from_env()?)),
+            Platform::GitLab => Ok(Box::new(gitlab::Context::from_env()?)),
+        }
+    }
+}
+
+impl std::str::FromStr for Platform {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "github" => Ok(Platform::GitHub),
+            "gitlab" => Ok(Platform::GitLab),
+            _ => anyhow::bail!("Unknown platform: '{s}'. Supported: github, gitlab"),
+        }
     }
 }
 
@@ -99,66 +277,381 @@ pub fn apply_path_prefix(
         .collect()
 }
 
+/// Which side of a diff a [`DiffLine`] belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    /// Unchanged line, present on both sides of the hunk.
+    Context,
+    /// Line added in the new file.
+    Added,
+    /// Line removed from the old file.
+    Deleted,
+}
+
+/// A single line within a [`Hunk`], tagged with its kind and content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// The line's text, with the leading ` `/`+`/`-` marker stripped.
+    pub content: String,
+    /// Line number in the new file, for `Added`/`Context` lines.
+    pub new_line: Option<u32>,
+    /// Line number in the old file, for `Deleted`/`Context` lines.
+    pub old_line: Option<u32>,
+}
+
+/// A single `@@ -old_start,old_count +new_start,new_count @@` hunk.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_count: u32,
+    pub new_start: u32,
+    pub new_count: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+/// One file's hunks within a parsed diff.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FileDiff {
+    pub path: String,
+    pub hunks: Vec<Hunk>,
+}
+
+/// A fully parsed unified diff: every file's hunks, plus any detected
+/// renames/copies. [`parse_diff`] projects this down to just the added line
+/// numbers that most callers need.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParsedDiff {
+    pub files: Vec<FileDiff>,
+    /// Detected `(old_path, new_path)` renames/copies (see
+    /// [`parse_diff_with_renames`]).
+    pub renames: Vec<(String, String)>,
+}
+
+impl ParsedDiff {
+    /// Total `(added, deleted)` line counts across every hunk in every file.
+    #[must_use]
+    pub fn churn(&self) -> (u32, u32) {
+        let mut added = 0u32;
+        let mut deleted = 0u32;
+        for line in self
+            .files
+            .iter()
+            .flat_map(|f| &f.hunks)
+            .flat_map(|h| &h.lines)
+        {
+            match line.kind {
+                DiffLineKind::Added => added += 1,
+                DiffLineKind::Deleted => deleted += 1,
+                DiffLineKind::Context => {}
+            }
+        }
+        (added, deleted)
+    }
+}
+
 /// Parse a unified diff (e.g., `git diff`) and return a map of
 /// file path -> list of added line numbers (in the new file).
+///
+/// This is a thin wrapper around [`parse_diff_with_renames`] for callers that
+/// don't need rename/copy tracking.
 pub fn parse_diff(diff_text: &str) -> HashMap<String, Vec<u32>> {
+    parse_diff_with_renames(diff_text).0
+}
+
+/// Like [`parse_diff`], but also returns detected file renames and copies as
+/// `(old_path, new_path)` pairs, so callers can fall back to a file's
+/// pre-rename path when it has no coverage history under its new name.
+///
+/// A thin projection over [`parse_structured_diff`], keeping added line
+/// numbers for files that have at least one.
+pub fn parse_diff_with_renames(diff_text: &str) -> (HashMap<String, Vec<u32>>, Vec<(String, String)>) {
+    let parsed = parse_structured_diff(diff_text);
+
     let mut result: HashMap<String, Vec<u32>> = HashMap::new();
-    let mut current_file: Option<String> = None;
+    for file in &parsed.files {
+        let added: Vec<u32> = file
+            .hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .filter(|line| line.kind == DiffLineKind::Added)
+            .filter_map(|line| line.new_line)
+            .collect();
+        if !added.is_empty() {
+            result.insert(file.path.clone(), added);
+        }
+    }
+
+    (result, parsed.renames)
+}
+
+/// Parse a unified diff into a [`ParsedDiff`]: every file's hunks with their
+/// context/added/deleted lines, plus detected renames/copies.
+///
+/// Convenience wrapper around [`parse_structured_diff_bytes`] for diff text
+/// already known to be UTF-8.
+pub fn parse_structured_diff(diff_text: &str) -> ParsedDiff {
+    parse_structured_diff_bytes(diff_text.as_bytes())
+}
+
+/// Parse a unified diff into a [`ParsedDiff`]: every file's hunks with their
+/// context/added/deleted lines, plus detected renames/copies.
+///
+/// Renames/copies are recognized from the extended header lines git emits
+/// before the `--- `/`+++ ` pair: `rename from <old>`, `rename to <new>`,
+/// `copy from <old>`, `copy to <new>` (`similarity index N%` is recognized
+/// but otherwise ignored). A pure rename with no content changes has no
+/// `--- `/`+++ ` lines at all, but is still registered even though it
+/// contributes no hunks.
+///
+/// Takes raw bytes rather than `&str` because diffed file content (and, with
+/// `core.quotePath` off, paths themselves) need not be valid UTF-8; only the
+/// diff's structural markers (`@@ `, `+++ `, ...) are assumed ASCII, which
+/// git always emits literally. Path and line content are decoded lossily
+/// where the underlying bytes aren't valid UTF-8, matching how coverage file
+/// paths are stored elsewhere as `String`.
+pub fn parse_structured_diff_bytes(diff_bytes: &[u8]) -> ParsedDiff {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut renames: Vec<(String, String)> = Vec::new();
+    let mut rename_from: Option<String> = None;
+    let mut old_path: Option<String> = None;
+    let mut old_line_number: u32 = 0;
     let mut new_line_number: u32 = 0;
+    // Number of leading marker columns per content line: 1 for an ordinary
+    // two-way hunk (`+`/`-`/` `), N for an N-parent combined-diff hunk.
+    let mut marker_width: usize = 1;
 
-    for line in diff_text.lines() {
-        if let Some(rest) = line.strip_prefix("+++ ") {
-            if rest == "/dev/null" {
-                current_file = None; // File was deleted
-            } else {
-                // Strip common VCS prefixes: "b/" (default git), "a/" (some tools).
-                // Also handles --no-prefix diffs where no prefix is present.
-                let path = rest
-                    .strip_prefix("b/")
-                    .or_else(|| rest.strip_prefix("a/"))
-                    .unwrap_or(rest);
-                current_file = Some(path.to_string());
+    for raw_line in diff_bytes.split(|&b| b == b'\n') {
+        let line = raw_line.strip_suffix(b"\r").unwrap_or(raw_line);
+
+        if line.starts_with(b"diff --git ") {
+            rename_from = None;
+            old_path = None;
+        } else if let Some(rest) = strip_bytes_prefix(line, b"rename from ")
+            .or_else(|| strip_bytes_prefix(line, b"copy from "))
+        {
+            rename_from = Some(decode_diff_path_bytes(rest));
+        } else if let Some(rest) = strip_bytes_prefix(line, b"rename to ")
+            .or_else(|| strip_bytes_prefix(line, b"copy to "))
+        {
+            if let Some(old) = rename_from.take() {
+                renames.push((old, decode_diff_path_bytes(rest)));
             }
-        } else if line.starts_with("@@ ") {
-            // Hunk header: @@ -old_start[,old_count] +new_start[,new_count] @@
-            if let Some(new_range) = parse_hunk_header(line) {
-                new_line_number = new_range;
+        } else if strip_bytes_prefix(line, b"similarity index ").is_some() {
+            // Recognized but not otherwise needed.
+        } else if let Some(rest) = strip_bytes_prefix(line, b"--- ") {
+            old_path = strip_diff_path_prefix(rest);
+        } else if let Some(rest) = strip_bytes_prefix(line, b"+++ ") {
+            let new_path = strip_diff_path_prefix(rest);
+            if let Some(path) = new_path.or_else(|| old_path.clone()) {
+                files.push(FileDiff {
+                    path,
+                    hunks: Vec::new(),
+                });
             }
-        } else if let Some(ref file) = current_file {
-            if line.starts_with('\\') {
+        } else if let Some(at_count) =
+            std::str::from_utf8(line).ok().and_then(hunk_header_at_count)
+        {
+            // Two-way hunk: @@ -old_start[,old_count] +new_start[,new_count] @@
+            // Combined (merge-commit) hunk: @@@ -a -b +new_start[,new_count] @@@,
+            // with one `-` range per parent.
+            if let Some((old_start, old_count, new_start, new_count)) =
+                std::str::from_utf8(line).ok().and_then(parse_hunk_header_full)
+            {
+                old_line_number = old_start;
+                new_line_number = new_start;
+                marker_width = at_count - 1;
+                if let Some(file) = files.last_mut() {
+                    file.hunks.push(Hunk {
+                        old_start,
+                        old_count,
+                        new_start,
+                        new_count,
+                        lines: Vec::new(),
+                    });
+                }
+            }
+        } else if let Some(hunk) = files.last_mut().and_then(|f| f.hunks.last_mut()) {
+            if line.first() == Some(&b'\\') {
                 // "\ No newline at end of file" — diff metadata, not a real line
-            } else if line.starts_with('+') && !line.starts_with("+++") {
-                // Added line
-                result
-                    .entry(file.clone())
-                    .or_default()
-                    .push(new_line_number);
-                new_line_number += 1;
-            } else if line.starts_with('-') && !line.starts_with("---") {
-                // Deleted line — doesn't advance new line counter
             } else {
-                // Context line or other
-                new_line_number += 1;
+                let markers = line.get(..marker_width).unwrap_or(&[]);
+                let content = String::from_utf8_lossy(line.get(marker_width..).unwrap_or(line))
+                    .into_owned();
+                if markers.contains(&b'+') {
+                    // Added relative to the new file; for a combined diff this
+                    // is a line that differs from at least one parent.
+                    hunk.lines.push(DiffLine {
+                        kind: DiffLineKind::Added,
+                        content,
+                        new_line: Some(new_line_number),
+                        old_line: None,
+                    });
+                    new_line_number += 1;
+                } else if markers.contains(&b'-') {
+                    hunk.lines.push(DiffLine {
+                        kind: DiffLineKind::Deleted,
+                        content,
+                        new_line: None,
+                        old_line: Some(old_line_number),
+                    });
+                    old_line_number += 1;
+                } else {
+                    hunk.lines.push(DiffLine {
+                        kind: DiffLineKind::Context,
+                        content,
+                        new_line: Some(new_line_number),
+                        old_line: Some(old_line_number),
+                    });
+                    new_line_number += 1;
+                    old_line_number += 1;
+                }
             }
         }
     }
 
-    result
+    ParsedDiff { files, renames }
 }
 
 /// Parse "new" start line from a hunk header like "@@ -10,5 +20,8 @@"
 fn parse_hunk_header(line: &str) -> Option<u32> {
-    // Find the +N part
-    let after_at = line.strip_prefix("@@ ")?;
-    let parts: Vec<&str> = after_at.split(' ').collect();
-    // parts[0] = "-old_start,old_count"
-    // parts[1] = "+new_start,new_count" or "+new_start"
-    if parts.len() < 2 {
+    parse_hunk_header_full(line).map(|(_, _, new_start, _)| new_start)
+}
+
+/// Number of leading `@` characters if `line` opens a hunk header (a
+/// two-way `@@ ` or an N-parent combined-diff `@@@...@@@ ` header),
+/// otherwise `None`.
+fn hunk_header_at_count(line: &str) -> Option<usize> {
+    let at_count = line.bytes().take_while(|&b| b == b'@').count();
+    if at_count >= 2 && line[at_count..].starts_with(' ') {
+        Some(at_count)
+    } else {
+        None
+    }
+}
+
+/// Parse a hunk header into `(old_start, old_count, new_start, new_count)`.
+/// Handles both an ordinary two-way header like `"@@ -10,5 +20,8 @@"` and a
+/// combined-diff (merge commit) header like `"@@@ -1,2 -1,2 +1,3 @@@"`,
+/// which carries one `-` range per parent; the `-` range closest to the
+/// `+` range (i.e. the last one) is used as `old_start`/`old_count`. A bare
+/// `-N`/`+N` (no comma) implies a count of 1.
+fn parse_hunk_header_full(line: &str) -> Option<(u32, u32, u32, u32)> {
+    let at_count = hunk_header_at_count(line)?;
+    let rest = &line[at_count..];
+
+    let mut old_range: Option<(u32, u32)> = None;
+    let mut new_range: Option<(u32, u32)> = None;
+    for token in rest.split_whitespace() {
+        if let Some(range) = token.strip_prefix('-') {
+            old_range = parse_range(range);
+        } else if let Some(range) = token.strip_prefix('+') {
+            new_range = parse_range(range);
+            break;
+        } else {
+            break;
+        }
+    }
+
+    let (old_start, old_count) = old_range?;
+    let (new_start, new_count) = new_range?;
+    Some((old_start, old_count, new_start, new_count))
+}
+
+/// Parse one side of a hunk header range, e.g. `"10,5"` or `"20"` (implying
+/// a count of 1), into `(start, count)`.
+fn parse_range(range: &str) -> Option<(u32, u32)> {
+    let mut parts = range.split(',');
+    let start = parts.next()?.parse::<u32>().ok()?;
+    let count = match parts.next() {
+        Some(count_str) => count_str.parse::<u32>().ok()?,
+        None => 1,
+    };
+    Some((start, count))
+}
+
+/// Strip `prefix` from the start of `line`, byte-wise.
+fn strip_bytes_prefix<'a>(line: &'a [u8], prefix: &[u8]) -> Option<&'a [u8]> {
+    line.starts_with(prefix).then(|| &line[prefix.len()..])
+}
+
+/// Strip the common `a/`/`b/` VCS prefix from a `--- `/`+++ ` path, or
+/// return `None` for `/dev/null` (added/deleted file markers).
+fn strip_diff_path_prefix(rest: &[u8]) -> Option<String> {
+    if rest == b"/dev/null" {
         return None;
     }
-    let new_part = parts[1].strip_prefix('+')?;
-    let start_str = new_part.split(',').next()?;
-    start_str.parse::<u32>().ok()
+    Some(decode_diff_path_bytes(rest))
+}
+
+/// Decode a path extracted from a diff header (`--- `, `+++ `, `rename
+/// from `, ...), unquoting a git C-style-quoted path if present and
+/// stripping the `a/`/`b/` VCS prefix. Bytes that aren't valid UTF-8 (as can
+/// happen for filenames outside the current locale, or after decoding an
+/// octal byte escape) are replaced lossily, matching how coverage file
+/// paths are stored elsewhere as `String`.
+fn decode_diff_path_bytes(rest: &[u8]) -> String {
+    let unquoted = unquote_git_path_bytes(rest);
+    let path = strip_bytes_prefix(&unquoted, b"b/")
+        .or_else(|| strip_bytes_prefix(&unquoted, b"a/"))
+        .unwrap_or(&unquoted);
+    String::from_utf8_lossy(path).into_owned()
+}
+
+/// Undo git's C-style quoting of a path containing spaces, tabs, quotes, or
+/// non-ASCII bytes (as produced with `core.quotePath` on, e.g.
+/// `"b/dir/na\tme.rs"`), decoding `\t`, `\n`, `\"`, `\\`, and octal `\NNN`
+/// byte escapes. Paths with no special characters are emitted unquoted and
+/// pass through unchanged.
+fn unquote_git_path_bytes(raw: &[u8]) -> Vec<u8> {
+    if raw.len() < 2 || raw[0] != b'"' || raw[raw.len() - 1] != b'"' {
+        return raw.to_vec();
+    }
+    let inner = &raw[1..raw.len() - 1];
+
+    let mut out = Vec::with_capacity(inner.len());
+    let mut i = 0;
+    while i < inner.len() {
+        if inner[i] != b'\\' || i + 1 >= inner.len() {
+            out.push(inner[i]);
+            i += 1;
+            continue;
+        }
+        match inner[i + 1] {
+            b't' => {
+                out.push(b'\t');
+                i += 2;
+            }
+            b'n' => {
+                out.push(b'\n');
+                i += 2;
+            }
+            b'"' => {
+                out.push(b'"');
+                i += 2;
+            }
+            b'\\' => {
+                out.push(b'\\');
+                i += 2;
+            }
+            digit @ b'0'..=b'7' => {
+                let mut value = u32::from(digit - b'0');
+                let mut consumed = 1;
+                while consumed < 3 && matches!(inner.get(i + 1 + consumed), Some(b'0'..=b'7')) {
+                    value = value * 8 + u32::from(inner[i + 1 + consumed] - b'0');
+                    consumed += 1;
+                }
+                out.push(value as u8);
+                i += 1 + consumed;
+            }
+            other => {
+                out.push(b'\\');
+                out.push(other);
+                i += 2;
+            }
+        }
+    }
+    out
 }
 
 #[cfg(test)]
@@ -218,4 +711,232 @@ mod tests {
         assert_eq!(result.get("a.rs").unwrap(), &[2]);
         assert_eq!(result.get("b.rs").unwrap(), &[2]);
     }
+
+    #[test]
+    fn test_parse_diff_with_renames_detects_rename_with_modification() {
+        let diff = "\
+diff --git a/old_name.rs b/new_name.rs
+similarity index 90%
+rename from old_name.rs
+rename to new_name.rs
+index 1111111..2222222 100644
+--- a/old_name.rs
++++ b/new_name.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    old_behavior();
++    new_behavior();
+ }
+";
+        let (lines, renames) = parse_diff_with_renames(diff);
+
+        assert_eq!(renames, vec![("old_name.rs".to_string(), "new_name.rs".to_string())]);
+        assert_eq!(lines.get("new_name.rs").unwrap(), &[2]);
+    }
+
+    #[test]
+    fn test_parse_diff_with_renames_pure_rename_has_no_added_lines() {
+        let diff = "\
+diff --git a/a.rs b/b.rs
+similarity index 100%
+rename from a.rs
+rename to b.rs
+";
+        let (lines, renames) = parse_diff_with_renames(diff);
+
+        assert_eq!(renames, vec![("a.rs".to_string(), "b.rs".to_string())]);
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn test_parse_diff_with_renames_detects_copy() {
+        let diff = "\
+diff --git a/template.rs b/template_copy.rs
+similarity index 100%
+copy from template.rs
+copy to template_copy.rs
+";
+        let (lines, renames) = parse_diff_with_renames(diff);
+
+        assert_eq!(renames, vec![("template.rs".to_string(), "template_copy.rs".to_string())]);
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn test_parse_diff_without_renames_returns_empty_vec() {
+        let diff = "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,1 +1,1 @@\n-old\n+new\n";
+        let (_, renames) = parse_diff_with_renames(diff);
+        assert!(renames.is_empty());
+    }
+
+    // -- Structured diff tests -----------------------------------------------
+
+    #[test]
+    fn test_parse_hunk_header_full_parses_counts() {
+        assert_eq!(parse_hunk_header_full("@@ -10,5 +20,8 @@"), Some((10, 5, 20, 8)));
+        assert_eq!(parse_hunk_header_full("@@ -5 +5 @@"), Some((5, 1, 5, 1)));
+    }
+
+    #[test]
+    fn test_parse_structured_diff_tags_context_added_deleted_lines() {
+        let diff = "\
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    old_behavior();
++    new_behavior();
+ }
+";
+        let parsed = parse_structured_diff(diff);
+        assert_eq!(parsed.files.len(), 1);
+        let file = &parsed.files[0];
+        assert_eq!(file.path, "src/main.rs");
+        assert_eq!(file.hunks.len(), 1);
+
+        let hunk = &file.hunks[0];
+        assert_eq!(hunk.old_start, 1);
+        assert_eq!(hunk.old_count, 3);
+        assert_eq!(hunk.new_start, 1);
+        assert_eq!(hunk.new_count, 3);
+
+        assert_eq!(hunk.lines[0].kind, DiffLineKind::Context);
+        assert_eq!(hunk.lines[0].old_line, Some(1));
+        assert_eq!(hunk.lines[0].new_line, Some(1));
+
+        assert_eq!(hunk.lines[1].kind, DiffLineKind::Deleted);
+        assert_eq!(hunk.lines[1].old_line, Some(2));
+        assert_eq!(hunk.lines[1].new_line, None);
+        assert_eq!(hunk.lines[1].content, "    old_behavior();");
+
+        assert_eq!(hunk.lines[2].kind, DiffLineKind::Added);
+        assert_eq!(hunk.lines[2].old_line, None);
+        assert_eq!(hunk.lines[2].new_line, Some(2));
+        assert_eq!(hunk.lines[2].content, "    new_behavior();");
+
+        assert_eq!(hunk.lines[3].kind, DiffLineKind::Context);
+        assert_eq!(hunk.lines[3].old_line, Some(3));
+        assert_eq!(hunk.lines[3].new_line, Some(3));
+    }
+
+    #[test]
+    fn test_parsed_diff_churn_counts_added_and_deleted() {
+        let diff = "\
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,2 @@
+ fn main() {
+-    old_behavior();
+-    also_gone();
++    new_behavior();
+ }
+";
+        let parsed = parse_structured_diff(diff);
+        assert_eq!(parsed.churn(), (1, 2));
+    }
+
+    #[test]
+    fn test_parse_structured_diff_carries_renames() {
+        let diff = "\
+diff --git a/old_name.rs b/new_name.rs
+similarity index 90%
+rename from old_name.rs
+rename to new_name.rs
+index 1111111..2222222 100644
+--- a/old_name.rs
++++ b/new_name.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    old_behavior();
++    new_behavior();
+ }
+";
+        let parsed = parse_structured_diff(diff);
+        assert_eq!(
+            parsed.renames,
+            vec![("old_name.rs".to_string(), "new_name.rs".to_string())]
+        );
+        assert_eq!(parsed.files[0].path, "new_name.rs");
+    }
+
+    #[test]
+    fn test_parse_hunk_header_full_parses_combined_diff_header() {
+        assert_eq!(
+            parse_hunk_header_full("@@@ -1,2 -1,2 +1,3 @@@"),
+            Some((1, 2, 1, 3))
+        );
+    }
+
+    #[test]
+    fn test_parse_structured_diff_handles_combined_diff_format() {
+        // A 2-parent combined diff (`git diff -c`): content lines carry one
+        // marker column per parent. A line with any `+` marker is added
+        // relative to the merge result; a line with only `-`/` ` markers and
+        // no `+` is a deletion.
+        let diff = "\
+diff --cc src/main.rs
+index 1111111,2222222..3333333
+--- a/src/main.rs
++++ b/src/main.rs
+@@@ -1,3 -1,3 +1,3 @@@
+  fn main() {
+--    ours();
+--    theirs();
+++    resolved();
+  }
+";
+        let parsed = parse_structured_diff(diff);
+        assert_eq!(parsed.files.len(), 1);
+        let hunk = &parsed.files[0].hunks[0];
+        assert_eq!(hunk.old_start, 1);
+        assert_eq!(hunk.new_start, 1);
+
+        assert_eq!(hunk.lines[0].kind, DiffLineKind::Context);
+        assert_eq!(hunk.lines[1].kind, DiffLineKind::Deleted);
+        assert_eq!(hunk.lines[2].kind, DiffLineKind::Deleted);
+        assert_eq!(hunk.lines[3].kind, DiffLineKind::Added);
+        assert_eq!(hunk.lines[3].content, "    resolved();");
+        assert_eq!(hunk.lines[4].kind, DiffLineKind::Context);
+    }
+
+    #[test]
+    fn test_unquote_git_path_decodes_tab_and_quote_escapes() {
+        assert_eq!(
+            unquote_git_path_bytes(br#""b/dir/na\tme.rs""#),
+            b"b/dir/na\tme.rs"
+        );
+        assert_eq!(unquote_git_path_bytes(br#""b/say \"hi\".rs""#), b"b/say \"hi\".rs");
+    }
+
+    #[test]
+    fn test_unquote_git_path_decodes_octal_byte_escapes() {
+        // \303\251 is the UTF-8 encoding of 'é'.
+        assert_eq!(unquote_git_path_bytes(br#""b/caf\303\251.rs""#), "b/café.rs".as_bytes());
+    }
+
+    #[test]
+    fn test_unquote_git_path_passes_through_unquoted_paths() {
+        assert_eq!(unquote_git_path_bytes(b"b/plain/path.rs"), b"b/plain/path.rs");
+    }
+
+    #[test]
+    fn test_parse_structured_diff_unquotes_tab_in_path() {
+        let diff = "\
+--- \"a/dir/na\\tme.rs\"
++++ \"b/dir/na\\tme.rs\"
+@@ -1,1 +1,1 @@
+-old
++new
+";
+        let parsed = parse_structured_diff(diff);
+        assert_eq!(parsed.files[0].path, "dir/na\tme.rs");
+    }
+
+    #[test]
+    fn test_parse_structured_diff_bytes_matches_str_entry_point() {
+        let diff = b"--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,1 +1,1 @@\n-old\n+new\n";
+        let parsed = parse_structured_diff_bytes(diff);
+        assert_eq!(parsed.files[0].path, "src/main.rs");
+        assert_eq!(parsed.files[0].hunks[0].lines[1].content, "new");
+    }
 }
@@ -0,0 +1,183 @@
+//! Coverage exclusion rules applied during ingest.
+//!
+//! Lines matching a configured regex, or falling inside a start/stop marker
+//! block, are dropped from `FileCoverage.lines`/`.branches` before they
+//! reach the database, so generated or boilerplate code doesn't drag down
+//! diff coverage. Modeled on grcov's `--excl-line`/`--excl-br-line` and
+//! start/stop markers.
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::model::FileCoverage;
+
+/// Patterns that are always excluded, regardless of user configuration.
+const DEFAULT_LINE_PATTERNS: &[&str] = &[r"#\[derive\(", r"//\s*GRCOV_EXCL_LINE"];
+
+/// A set of exclusion rules to apply to parsed coverage against its source.
+pub struct ExclusionRules {
+    line_patterns: Vec<Regex>,
+    start_stop: Option<(Regex, Regex)>,
+}
+
+impl ExclusionRules {
+    /// Build a rule set from the always-on defaults plus an optional extra
+    /// per-line regex and an optional start/stop marker pair (both or
+    /// neither must be given).
+    pub fn new(
+        line_pattern: Option<&str>,
+        start_pattern: Option<&str>,
+        stop_pattern: Option<&str>,
+    ) -> Result<Self> {
+        let mut line_patterns = Vec::with_capacity(DEFAULT_LINE_PATTERNS.len() + 1);
+        for pat in DEFAULT_LINE_PATTERNS {
+            line_patterns.push(Regex::new(pat)?);
+        }
+        if let Some(pat) = line_pattern {
+            line_patterns.push(Regex::new(pat)?);
+        }
+
+        let start_stop = match (start_pattern, stop_pattern) {
+            (Some(start), Some(stop)) => Some((Regex::new(start)?, Regex::new(stop)?)),
+            (None, None) => None,
+            _ => anyhow::bail!("start_pattern and stop_pattern must be given together"),
+        };
+
+        Ok(Self {
+            line_patterns,
+            start_stop,
+        })
+    }
+
+    /// A rule set with only the built-in default patterns active.
+    pub fn defaults() -> Self {
+        Self::new(None, None, None).expect("default patterns are always valid regexes")
+    }
+
+    /// Apply this rule set to `file`, removing excluded lines/branches in
+    /// place. Reads the source file at `file.path` (joined under `root` if
+    /// given); if the source can't be read, no exclusion is applied — the
+    /// caller gets the coverage data unfiltered rather than an error.
+    pub fn apply(&self, file: &mut FileCoverage, root: Option<&Path>) {
+        let path = match root {
+            Some(root) => root.join(&file.path),
+            None => Path::new(&file.path).to_path_buf(),
+        };
+        let source = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+
+        let excluded = self.excluded_lines(&source);
+        if excluded.is_empty() {
+            return;
+        }
+
+        file.lines.retain(|l| !excluded.contains(&l.line_number));
+        file.branches.retain(|b| !excluded.contains(&b.line_number));
+    }
+
+    fn excluded_lines(&self, source: &str) -> HashSet<u32> {
+        let mut excluded = HashSet::new();
+        let mut in_block = false;
+
+        for (idx, text) in source.lines().enumerate() {
+            let line_number = (idx + 1) as u32;
+
+            if let Some((start, stop)) = &self.start_stop {
+                if in_block {
+                    excluded.insert(line_number);
+                    if stop.is_match(text) {
+                        in_block = false;
+                    }
+                    continue;
+                }
+                if start.is_match(text) {
+                    excluded.insert(line_number);
+                    in_block = true;
+                    continue;
+                }
+            }
+
+            if self.line_patterns.iter().any(|re| re.is_match(text)) {
+                excluded.insert(line_number);
+            }
+        }
+
+        excluded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BranchCoverage, BranchKind, LineCoverage};
+
+    fn file_with_lines(n: u32) -> FileCoverage {
+        let mut file = FileCoverage::new("f.rs".to_string());
+        for line_number in 1..=n {
+            file.lines.push(LineCoverage {
+                line_number,
+                hit_count: 1,
+            });
+            file.branches.push(BranchCoverage {
+                line_number,
+                branch_index: 0,
+                hit_count: 1,
+                group_id: None,
+                kind: BranchKind::Unknown,
+                arm_line: None,
+            });
+        }
+        file
+    }
+
+    #[test]
+    fn test_default_derive_exclusion() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f.rs");
+        fs::write(&path, "#[derive(Debug)]\nstruct Foo;\n").unwrap();
+
+        let rules = ExclusionRules::defaults();
+        let mut file = file_with_lines(2);
+        file.path = "f.rs".to_string();
+        rules.apply(&mut file, Some(dir.path()));
+
+        assert_eq!(file.lines.len(), 1);
+        assert_eq!(file.lines[0].line_number, 2);
+        assert_eq!(file.branches.len(), 1);
+        assert_eq!(file.branches[0].line_number, 2);
+    }
+
+    #[test]
+    fn test_start_stop_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f.rs");
+        fs::write(
+            &path,
+            "fn a() {}\n// COV_EXCL_START\nfn b() {}\nfn c() {}\n// COV_EXCL_STOP\nfn d() {}\n",
+        )
+        .unwrap();
+
+        let rules =
+            ExclusionRules::new(None, Some("COV_EXCL_START"), Some("COV_EXCL_STOP")).unwrap();
+        let mut file = file_with_lines(6);
+        file.path = "f.rs".to_string();
+        rules.apply(&mut file, Some(dir.path()));
+
+        let remaining: Vec<u32> = file.lines.iter().map(|l| l.line_number).collect();
+        assert_eq!(remaining, vec![1, 6]);
+    }
+
+    #[test]
+    fn test_unreadable_source_keeps_data() {
+        let rules = ExclusionRules::defaults();
+        let mut file = file_with_lines(2);
+        file.path = "does/not/exist.rs".to_string();
+        rules.apply(&mut file, None);
+        assert_eq!(file.lines.len(), 2);
+    }
+}
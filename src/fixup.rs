@@ -0,0 +1,122 @@
+//! Reclassification of structurally non-executable lines.
+//!
+//! Coverage parsers frequently mark lines like a lone closing brace, a
+//! comment, or a blank line as instrumentable-but-uncovered, which inflates
+//! misses and defeats the gap-bridging in [`crate::report::coalesce_ranges`].
+//! This is an opt-in transform over `CoverageData` (applied per file, before
+//! DB insertion) that drops such lines from the instrumentable set using
+//! lightweight lexical checks against the source text — no full parse is
+//! needed since we only need to know whether a line is executable *at all*,
+//! not its syntax tree. Modeled on the line-classification idea behind
+//! rust-covfix.
+//!
+//! A line is only reclassified when every non-whitespace token on it is one
+//! of: a line comment, a block comment, or a closing delimiter (a run of
+//! `}`, `)`, `]`, optionally followed by `;` or `,`). Anything else on the
+//! line (even alongside a comment) keeps it instrumentable.
+use std::fs;
+use std::path::Path;
+
+use crate::model::FileCoverage;
+
+/// Apply the fixup pass to `file`, removing non-executable lines from its
+/// instrumentable set in place. Reads the source file at `file.path`
+/// (joined under `root` if given); if the source can't be read, no
+/// reclassification is applied — the caller gets the coverage data
+/// unfiltered rather than an error.
+pub fn apply(file: &mut FileCoverage, root: Option<&Path>) {
+    let path = match root {
+        Some(root) => root.join(&file.path),
+        None => Path::new(&file.path).to_path_buf(),
+    };
+    let source = match fs::read_to_string(&path) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    let lines: Vec<&str> = source.lines().collect();
+
+    file.lines
+        .retain(|l| !is_non_executable(&lines, l.line_number));
+}
+
+/// Whether `line_number` (1-indexed) in `source_lines` contains only
+/// non-executable tokens: whitespace, a full-line comment, or a run of
+/// closing delimiters.
+fn is_non_executable(source_lines: &[&str], line_number: u32) -> bool {
+    let Some(text) = source_lines.get(line_number as usize - 1) else {
+        return false;
+    };
+    let trimmed = text.trim();
+
+    trimmed.is_empty()
+        || trimmed.starts_with("//")
+        || (trimmed.starts_with("/*") && trimmed.ends_with("*/"))
+        || is_lone_closing_delimiter(trimmed)
+}
+
+/// Whether `trimmed` is nothing but closing `}`, `)`, or `]` characters,
+/// optionally followed by `;` or `,` (e.g. `}`, `});`, `],`).
+fn is_lone_closing_delimiter(trimmed: &str) -> bool {
+    let body = trimmed.trim_end_matches([';', ',']);
+    !body.is_empty() && body.chars().all(|c| matches!(c, '}' | ')' | ']'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::LineCoverage;
+
+    fn file_with_lines(n: u32) -> FileCoverage {
+        let mut file = FileCoverage::new("f.rs".to_string());
+        for line_number in 1..=n {
+            file.lines.push(LineCoverage {
+                line_number,
+                hit_count: 0,
+            });
+        }
+        file
+    }
+
+    #[test]
+    fn test_drops_blank_and_comment_and_brace_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f.rs");
+        fs::write(
+            &path,
+            "fn main() {\n    do_thing();\n\n    // a comment\n}\n",
+        )
+        .unwrap();
+
+        let mut file = file_with_lines(5);
+        file.path = "f.rs".to_string();
+        apply(&mut file, Some(dir.path()));
+
+        let remaining: Vec<u32> = file.lines.iter().map(|l| l.line_number).collect();
+        assert_eq!(remaining, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_keeps_lines_with_trailing_closing_delimiter_and_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f.rs");
+        fs::write(&path, "foo(|x| {\n    bar(x)\n});\n").unwrap();
+
+        let mut file = file_with_lines(3);
+        file.path = "f.rs".to_string();
+        apply(&mut file, Some(dir.path()));
+
+        // Line 3 ("});") is a lone closing delimiter and gets dropped, but
+        // line 1 has real code before the `{` and must stay instrumentable.
+        let remaining: Vec<u32> = file.lines.iter().map(|l| l.line_number).collect();
+        assert_eq!(remaining, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_unreadable_source_keeps_data() {
+        let mut file = file_with_lines(2);
+        file.path = "does/not/exist.rs".to_string();
+        apply(&mut file, None);
+        assert_eq!(file.lines.len(), 2);
+    }
+}
@@ -1,8 +1,12 @@
 //! GitHub API helpers for posting diff-coverage comments on pull requests
 //! and creating check runs with line-level annotations.
 
+use std::cell::RefCell;
+use std::path::Path;
+use std::time::Duration;
+
 use anyhow::{bail, Context as _, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::model::Annotation;
 
@@ -11,36 +15,375 @@ const COMMENT_MARKER: &str = "<!-- covrs-comment -->";
 /// Maximum annotations per GitHub Check Runs API request.
 const MAX_ANNOTATIONS_PER_REQUEST: usize = 50;
 
-/// Build a ureq request with standard GitHub API headers.
-fn github_request(method: &str, url: &str, token: &str) -> ureq::Request {
-    ureq::request(method, url)
-        .set("Authorization", &format!("Bearer {token}"))
-        .set("Accept", "application/vnd.github+json")
-        .set("User-Agent", "covrs")
-        .set("X-GitHub-Api-Version", "2022-11-28")
+/// Maximum attempts for a single API call before giving up (see
+/// [`call_with_retry`]).
+pub(crate) const MAX_ATTEMPTS: u32 = 5;
+
+/// Upper bound on total time [`call_with_retry`] will spend sleeping
+/// across all attempts, so a busy PR can't hang a CI job indefinitely.
+pub(crate) const MAX_TOTAL_WAIT: Duration = Duration::from_secs(120);
+
+// ---------------------------------------------------------------------------
+// HTTP transport
+// ---------------------------------------------------------------------------
+
+/// An outgoing HTTP request, independent of any particular HTTP client.
+/// `github_request`/`call_with_retry` build these and hand them to an
+/// [`HttpTransport`], so the whole module can be driven by a recording or
+/// replaying transport in tests instead of real `ureq` calls.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<serde_json::Value>,
+}
+
+/// The result of executing an [`HttpRequest`]. Unlike `ureq`, a non-2xx
+/// status is a normal `HttpResponse` rather than an error — callers (like
+/// [`call_with_retry`]) decide what to do with the status themselves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+}
+
+impl HttpResponse {
+    /// Look up a response header by name, case-insensitively.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A transport-level failure (connection refused, DNS, timeout, ...) as
+/// opposed to an HTTP error status, which comes back as an `Ok(HttpResponse)`.
+#[derive(Debug)]
+pub enum HttpError {
+    Transport(String),
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpError::Transport(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// Something that can execute an [`HttpRequest`] and produce an
+/// [`HttpResponse`]. Implemented by [`UreqTransport`] for real traffic and by
+/// [`RecordingTransport`]/[`ReplayTransport`] for offline tests.
+pub trait HttpTransport {
+    fn execute(&self, request: &HttpRequest) -> std::result::Result<HttpResponse, HttpError>;
+}
+
+/// The real transport, backed by `ureq`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UreqTransport;
+
+impl HttpTransport for UreqTransport {
+    fn execute(&self, request: &HttpRequest) -> std::result::Result<HttpResponse, HttpError> {
+        let mut req = ureq::request(&request.method, &request.url);
+        for (name, value) in &request.headers {
+            req = req.set(name, value);
+        }
+
+        let result = match &request.body {
+            Some(body) => req.send_json(body.clone()),
+            None => req.call(),
+        };
+
+        let resp = match result {
+            Ok(resp) => resp,
+            Err(ureq::Error::Status(_, resp)) => resp,
+            Err(ureq::Error::Transport(t)) => return Err(HttpError::Transport(t.to_string())),
+        };
+
+        Ok(ureq_response_to_http(resp))
+    }
+}
+
+fn ureq_response_to_http(resp: ureq::Response) -> HttpResponse {
+    let status = resp.status();
+    let headers: Vec<(String, String)> = resp
+        .headers_names()
+        .into_iter()
+        .filter_map(|name| {
+            let value = resp.header(&name)?.to_string();
+            Some((name, value))
+        })
+        .collect();
+    let body = resp.into_string().unwrap_or_default();
+    HttpResponse {
+        status,
+        body,
+        headers,
+    }
+}
+
+/// One recorded request/response pair, as stored in a `tests/recordings/*.json`
+/// fixture.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedExchange {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<serde_json::Value>,
+    status: u16,
+    #[serde(default)]
+    response_body: String,
+    #[serde(default)]
+    response_headers: Vec<(String, String)>,
+}
+
+/// A transport that forwards requests to [`UreqTransport`] and records every
+/// exchange, so a real session against the live API can be captured once and
+/// replayed offline afterwards with [`ReplayTransport`].
+#[derive(Default)]
+pub struct RecordingTransport {
+    inner: UreqTransport,
+    exchanges: RefCell<Vec<RecordedExchange>>,
+}
+
+impl RecordingTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write every request/response observed so far to `path` as a JSON
+    /// fixture, in the shape [`ReplayTransport::load`] reads.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&*self.exchanges.borrow())
+            .context("Failed to serialize recorded HTTP exchanges")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write recorded fixture {}", path.display()))
+    }
+}
+
+impl HttpTransport for RecordingTransport {
+    fn execute(&self, request: &HttpRequest) -> std::result::Result<HttpResponse, HttpError> {
+        let resp = self.inner.execute(request)?;
+        self.exchanges.borrow_mut().push(RecordedExchange {
+            method: request.method.clone(),
+            url: request.url.clone(),
+            request_body: request.body.clone(),
+            status: resp.status,
+            response_body: resp.body.clone(),
+            response_headers: resp.headers.clone(),
+        });
+        Ok(resp)
+    }
+}
+
+/// A transport that serves responses from a recorded `tests/recordings/*.json`
+/// fixture instead of making real HTTP calls. Requests are matched against
+/// the recording by method and URL, in the order they appear in the fixture;
+/// each recorded exchange is consumed at most once.
+pub struct ReplayTransport {
+    exchanges: Vec<RecordedExchange>,
+    consumed: RefCell<Vec<bool>>,
 }
 
-/// Map a ureq response result into an anyhow error with context.
-fn check_response(
-    result: Result<ureq::Response, ureq::Error>,
+impl ReplayTransport {
+    /// Load a recorded fixture from disk (see [`RecordingTransport::save`]).
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recorded fixture {}", path.display()))?;
+        let exchanges: Vec<RecordedExchange> = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse recorded fixture {}", path.display()))?;
+        let consumed = RefCell::new(vec![false; exchanges.len()]);
+        Ok(Self { exchanges, consumed })
+    }
+}
+
+impl HttpTransport for ReplayTransport {
+    fn execute(&self, request: &HttpRequest) -> std::result::Result<HttpResponse, HttpError> {
+        let mut consumed = self.consumed.borrow_mut();
+        for (i, exchange) in self.exchanges.iter().enumerate() {
+            if !consumed[i] && exchange.method == request.method && exchange.url == request.url {
+                consumed[i] = true;
+                return Ok(HttpResponse {
+                    status: exchange.status,
+                    body: exchange.response_body.clone(),
+                    headers: exchange.response_headers.clone(),
+                });
+            }
+        }
+        Err(HttpError::Transport(format!(
+            "no recorded response for {} {}",
+            request.method, request.url
+        )))
+    }
+}
+
+/// Build a request with standard GitHub API headers.
+fn github_request(method: &str, url: &str, token: &str) -> HttpRequest {
+    HttpRequest {
+        method: method.to_string(),
+        url: url.to_string(),
+        headers: vec![
+            ("Authorization".to_string(), format!("Bearer {token}")),
+            (
+                "Accept".to_string(),
+                "application/vnd.github+json".to_string(),
+            ),
+            ("User-Agent".to_string(), "covrs".to_string()),
+            (
+                "X-GitHub-Api-Version".to_string(),
+                "2022-11-28".to_string(),
+            ),
+        ],
+        body: None,
+    }
+}
+
+/// Issue an API request built by `build_request` against `transport`,
+/// retrying transient failures with exponential backoff and jitter: a 5xx, or
+/// the 403/429 secondary-rate-limit responses. `build_request` is called
+/// again on every attempt so callers can tweak headers per request (see
+/// `fetch_pr_diff`). `platform` (e.g. `"GitHub"`/`"GitLab"`) only affects
+/// error/log message text, so both platform modules can share one retry
+/// loop instead of keeping divergent copies.
+///
+/// On a retryable status, the `Retry-After` (seconds) and
+/// `X-RateLimit-Reset`/`RateLimit-Reset` (epoch seconds) response headers
+/// are checked and the later of the two is used as the wait, falling back
+/// to plain backoff when neither is present. Non-retryable 4xx errors
+/// (401/404/422/...) and the final attempt's error are surfaced
+/// immediately as an `anyhow::Error` carrying the HTTP status and response
+/// body.
+pub(crate) fn call_with_retry(
+    transport: &dyn HttpTransport,
+    platform: &str,
     action: &str,
-) -> Result<ureq::Response> {
-    match result {
-        Ok(resp) => Ok(resp),
-        Err(ureq::Error::Status(code, resp)) => {
-            let body = resp.into_string().unwrap_or_default();
-            bail!("GitHub API error {action} (HTTP {code}): {body}");
+    body: Option<&serde_json::Value>,
+    mut build_request: impl FnMut() -> HttpRequest,
+) -> Result<HttpResponse> {
+    let mut total_waited = Duration::ZERO;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = build_request();
+        request.body = body.cloned();
+
+        match transport.execute(&request) {
+            Ok(resp) if (200..300).contains(&resp.status) => return Ok(resp),
+            Ok(resp) => {
+                let code = resp.status;
+                if !is_retryable_status(code) || attempt == MAX_ATTEMPTS {
+                    bail!("{platform} API error {action} (HTTP {code}): {}", resp.body);
+                }
+
+                let wait = retry_delay(&resp, attempt);
+                if total_waited + wait > MAX_TOTAL_WAIT {
+                    bail!("{platform} API error {action}: giving up after {attempt} attempt(s) (HTTP {code})");
+                }
+                eprintln!(
+                    "{platform} API {action} got HTTP {code}, retrying in {:.1}s (attempt {attempt}/{MAX_ATTEMPTS})",
+                    wait.as_secs_f64()
+                );
+                std::thread::sleep(wait);
+                total_waited += wait;
+            }
+            Err(HttpError::Transport(e)) => {
+                if attempt == MAX_ATTEMPTS {
+                    bail!("Failed to {action} ({platform}): {e}");
+                }
+                let wait = backoff_delay(attempt);
+                std::thread::sleep(wait);
+                total_waited += wait;
+            }
         }
-        Err(e) => bail!("Failed to {action}: {e}"),
+    }
+
+    unreachable!("loop always returns Ok or bails on the final attempt")
+}
+
+/// Whether an HTTP status is worth retrying: a transient 5xx, or a
+/// secondary (403) / primary (429) rate limit, on either GitHub or GitLab.
+pub(crate) fn is_retryable_status(code: u16) -> bool {
+    matches!(code, 403 | 429) || (500..600).contains(&code)
+}
+
+/// How long to wait before the next attempt: the server's own `Retry-After`
+/// or rate-limit-reset hint when present (whichever implies the longer
+/// wait), otherwise plain exponential backoff. Checks both GitHub's
+/// `X-RateLimit-Reset` and GitLab's `RateLimit-Reset` header names.
+pub(crate) fn retry_delay(resp: &HttpResponse, attempt: u32) -> Duration {
+    let retry_after = resp
+        .header("Retry-After")
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs);
+
+    let rate_limit_reset = resp
+        .header("X-RateLimit-Reset")
+        .or_else(|| resp.header("RateLimit-Reset"))
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(|epoch| {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            Duration::from_secs(epoch.saturating_sub(now))
+        });
+
+    match (retry_after, rate_limit_reset) {
+        (Some(a), Some(b)) => a.max(b),
+        (Some(a), None) => a,
+        (None, Some(b)) => b,
+        (None, None) => backoff_delay(attempt),
     }
 }
 
+/// Exponential backoff with jitter: a 500ms base, doubling per attempt,
+/// plus up to 250ms of jitter so concurrent jobs don't retry in lockstep.
+pub(crate) fn backoff_delay(attempt: u32) -> Duration {
+    let base_ms = 500u64.saturating_mul(1u64 << attempt.min(6));
+    Duration::from_millis(base_ms + jitter_ms())
+}
+
+/// A few hundred milliseconds of jitter derived from the current time.
+fn jitter_ms() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    u64::from(nanos % 250)
+}
+
+/// A CI platform that can supply a pull/merge request's diff and receive
+/// coverage feedback on it. Implemented by [`Context`] for GitHub Actions
+/// and by [`crate::gitlab::Context`] for GitLab CI, so callers can post
+/// results without caring which platform they're running on.
+pub trait ReviewPlatform {
+    /// Fetch the unified diff for the current pull/merge request.
+    fn fetch_diff(&self) -> Result<String>;
+
+    /// Create or update the covrs coverage comment.
+    fn post_comment(&self, body: &str) -> Result<()>;
+
+    /// Post per-line annotations for uncovered lines (a check run on
+    /// GitHub, per-line diff discussions on GitLab).
+    fn post_annotations(&self, annotations: &[Annotation]) -> Result<()>;
+
+    /// The resolved head commit SHA, if known.
+    fn sha(&self) -> Option<&str>;
+}
+
 /// Resolved GitHub Actions context, read from environment variables.
 pub struct Context {
     token: String,
     repo: String,
     pr_number: u64,
-    pub sha: Option<String>,
+    sha: Option<String>,
+    transport: Box<dyn HttpTransport>,
 }
 
 impl Context {
@@ -51,13 +394,20 @@ impl Context {
     /// commit rather than using `GITHUB_SHA`, which on `pull_request` events
     /// points to a temporary merge commit instead of the actual PR head.
     pub fn from_env() -> Result<Self> {
+        Self::from_env_with_transport(UreqTransport)
+    }
+
+    /// Like [`Context::from_env`], but issues requests through `transport`
+    /// instead of live `ureq` calls. Tests use this to inject a
+    /// [`ReplayTransport`] loaded from a `tests/recordings/*.json` fixture.
+    pub fn from_env_with_transport(transport: impl HttpTransport + 'static) -> Result<Self> {
         let token = std::env::var("GITHUB_TOKEN")
             .context("GITHUB_TOKEN environment variable is required")?;
         let repo = std::env::var("GITHUB_REPOSITORY")
             .context("GITHUB_REPOSITORY environment variable is required")?;
         let pr_number =
             pr_number_from_ref().context("could not determine PR number from GITHUB_REF")?;
-        let sha = fetch_pr_head_sha(&token, &repo, pr_number)
+        let sha = fetch_pr_head_sha(&transport, &token, &repo, pr_number)
             .map(Some)
             .unwrap_or_else(|e| {
                 eprintln!("Warning: could not fetch PR head SHA: {e}");
@@ -68,21 +418,54 @@ impl Context {
             repo,
             pr_number,
             sha,
+            transport: Box::new(transport),
         })
     }
 
+    /// Build a context directly from its resolved fields, without reading
+    /// environment variables. Used by tests to exercise comment/check-run
+    /// posting against a [`ReplayTransport`] without setting up GitHub
+    /// Actions env vars.
+    pub fn for_testing(
+        repo: impl Into<String>,
+        pr_number: u64,
+        sha: Option<String>,
+        transport: impl HttpTransport + 'static,
+    ) -> Self {
+        Self {
+            token: "test-token".to_string(),
+            repo: repo.into(),
+            pr_number,
+            sha,
+            transport: Box::new(transport),
+        }
+    }
+}
+
+impl ReviewPlatform for Context {
     /// Fetch the unified diff for the pull request.
-    pub fn fetch_diff(&self) -> Result<String> {
+    fn fetch_diff(&self) -> Result<String> {
         eprintln!(
             "Fetching diff for {}/pull/{} ...",
             self.repo, self.pr_number
         );
-        fetch_pr_diff(&self.token, &self.repo, self.pr_number)
+        fetch_pr_diff(
+            self.transport.as_ref(),
+            &self.token,
+            &self.repo,
+            self.pr_number,
+        )
     }
 
     /// Create or update a comment on the pull request.
-    pub fn post_comment(&self, body: &str) -> Result<()> {
-        post_comment(&self.token, &self.repo, self.pr_number, body)?;
+    fn post_comment(&self, body: &str) -> Result<()> {
+        post_comment(
+            self.transport.as_ref(),
+            &self.token,
+            &self.repo,
+            self.pr_number,
+            body,
+        )?;
         eprintln!("Comment posted to {}/pull/{}", self.repo, self.pr_number);
         Ok(())
     }
@@ -92,13 +475,19 @@ impl Context {
     /// Annotations are submitted in batches of 50 (the GitHub API limit).
     /// The check run is created with conclusion `neutral` so it never
     /// blocks merges.
-    pub fn post_annotations(&self, annotations: &[Annotation]) -> Result<()> {
+    fn post_annotations(&self, annotations: &[Annotation]) -> Result<()> {
         let sha = self
             .sha
             .as_deref()
             .context("commit SHA is required for check run annotations")?;
 
-        post_check_run(&self.token, &self.repo, sha, annotations)?;
+        post_check_run(
+            self.transport.as_ref(),
+            &self.token,
+            &self.repo,
+            sha,
+            annotations,
+        )?;
         eprintln!(
             "Check run with {} annotations posted to {}/pull/{}",
             annotations.len(),
@@ -107,6 +496,10 @@ impl Context {
         );
         Ok(())
     }
+
+    fn sha(&self) -> Option<&str> {
+        self.sha.as_deref()
+    }
 }
 
 /// Extract PR number from GITHUB_REF (e.g. "refs/pull/42/merge" â†’ 42).
@@ -120,16 +513,22 @@ fn pr_number_from_ref() -> Option<u64> {
     }
 }
 
-fn fetch_pr_diff(token: &str, repo: &str, pr_number: u64) -> Result<String> {
+fn fetch_pr_diff(
+    transport: &dyn HttpTransport,
+    token: &str,
+    repo: &str,
+    pr_number: u64,
+) -> Result<String> {
     let url = format!("https://api.github.com/repos/{repo}/pulls/{pr_number}");
-    let resp = check_response(
-        github_request("GET", &url, token)
-            .set("Accept", "application/vnd.github.v3.diff")
-            .call(),
-        "fetching PR diff",
-    )?;
-    resp.into_string()
-        .context("Failed to read PR diff response body")
+    let resp = call_with_retry(transport, "GitHub", "fetching PR diff", None, || {
+        let mut request = github_request("GET", &url, token);
+        request.headers.retain(|(name, _)| name != "Accept");
+        request
+            .headers
+            .push(("Accept".to_string(), "application/vnd.github.v3.diff".to_string()));
+        request
+    })?;
+    Ok(resp.body)
 }
 
 #[derive(Deserialize)]
@@ -148,15 +547,18 @@ struct PullRequestHead {
 /// head commit of the PR branch.  This function queries the Pulls API to get
 /// the real head SHA so that check-run annotations and blob permalinks resolve
 /// correctly.
-fn fetch_pr_head_sha(token: &str, repo: &str, pr_number: u64) -> Result<String> {
+fn fetch_pr_head_sha(
+    transport: &dyn HttpTransport,
+    token: &str,
+    repo: &str,
+    pr_number: u64,
+) -> Result<String> {
     let url = format!("https://api.github.com/repos/{repo}/pulls/{pr_number}");
-    let resp = check_response(
-        github_request("GET", &url, token).call(),
-        "fetching PR head SHA",
-    )?;
-    let pr: PullRequest = resp
-        .into_json()
-        .context("Failed to parse pull request JSON")?;
+    let resp = call_with_retry(transport, "GitHub", "fetching PR head SHA", None, || {
+        github_request("GET", &url, token)
+    })?;
+    let pr: PullRequest =
+        serde_json::from_str(&resp.body).context("Failed to parse pull request JSON")?;
     Ok(pr.head.sha)
 }
 
@@ -167,17 +569,23 @@ struct Comment {
 }
 
 /// Find an existing covrs comment on a PR (by our hidden marker).
-fn find_existing_comment(token: &str, repo: &str, pr_number: u64) -> Result<Option<u64>> {
+fn find_existing_comment(
+    transport: &dyn HttpTransport,
+    token: &str,
+    repo: &str,
+    pr_number: u64,
+) -> Result<Option<u64>> {
     let mut page = 1u32;
     loop {
         let url = format!(
             "https://api.github.com/repos/{repo}/issues/{pr_number}/comments?per_page=100&page={page}"
         );
-        let resp = github_request("GET", &url, token)
-            .call()
-            .context("Failed to list PR comments")?;
+        let resp = call_with_retry(transport, "GitHub", "listing PR comments", None, || {
+            github_request("GET", &url, token)
+        })?;
 
-        let comments: Vec<Comment> = resp.into_json().context("Failed to parse comments JSON")?;
+        let comments: Vec<Comment> =
+            serde_json::from_str(&resp.body).context("Failed to parse comments JSON")?;
         if comments.is_empty() {
             break;
         }
@@ -194,21 +602,29 @@ fn find_existing_comment(token: &str, repo: &str, pr_number: u64) -> Result<Opti
 }
 
 /// Create or update the covrs diff-coverage comment on a PR.
-fn post_comment(token: &str, repo: &str, pr_number: u64, body: &str) -> Result<()> {
+fn post_comment(
+    transport: &dyn HttpTransport,
+    token: &str,
+    repo: &str,
+    pr_number: u64,
+    body: &str,
+) -> Result<()> {
     let body_with_marker = format!("{COMMENT_MARKER}\n{body}");
 
-    match find_existing_comment(token, repo, pr_number)? {
+    let payload = serde_json::json!({ "body": body_with_marker });
+
+    match find_existing_comment(transport, token, repo, pr_number)? {
         Some(comment_id) => {
             let url = format!("https://api.github.com/repos/{repo}/issues/comments/{comment_id}");
-            let resp = github_request("PATCH", &url, token)
-                .send_json(serde_json::json!({ "body": body_with_marker }));
-            check_response(resp, "updating comment")?;
+            call_with_retry(transport, "GitHub", "updating comment", Some(&payload), || {
+                github_request("PATCH", &url, token)
+            })?;
         }
         None => {
             let url = format!("https://api.github.com/repos/{repo}/issues/{pr_number}/comments");
-            let resp = github_request("POST", &url, token)
-                .send_json(serde_json::json!({ "body": body_with_marker }));
-            check_response(resp, "creating comment")?;
+            call_with_retry(transport, "GitHub", "creating comment", Some(&payload), || {
+                github_request("POST", &url, token)
+            })?;
         }
     }
 
@@ -256,7 +672,13 @@ fn annotations_to_json(annotations: &[Annotation]) -> Vec<serde_json::Value> {
 /// The check run is created with the first batch, then subsequent batches are
 /// added via PATCH requests. The final request sets the status to `completed`
 /// with conclusion `neutral`.
-fn post_check_run(token: &str, repo: &str, sha: &str, annotations: &[Annotation]) -> Result<()> {
+fn post_check_run(
+    transport: &dyn HttpTransport,
+    token: &str,
+    repo: &str,
+    sha: &str,
+    annotations: &[Annotation],
+) -> Result<()> {
     let url = format!("https://api.github.com/repos/{repo}/check-runs");
     let chunks: Vec<&[Annotation]> = if annotations.is_empty() {
         vec![&[]]
@@ -297,15 +719,13 @@ fn post_check_run(token: &str, repo: &str, sha: &str, annotations: &[Annotation]
         body["status"] = serde_json::json!("in_progress");
     }
 
-    let resp = check_response(
-        github_request("POST", &url, token).send_json(body),
-        "creating check run",
-    )?;
+    let resp = call_with_retry(transport, "GitHub", "creating check run", Some(&body), || {
+        github_request("POST", &url, token)
+    })?;
 
     if !is_single_request {
-        let check_run: CheckRun = resp
-            .into_json()
-            .context("Failed to parse check run response")?;
+        let check_run: CheckRun =
+            serde_json::from_str(&resp.body).context("Failed to parse check run response")?;
 
         let update_url = format!(
             "https://api.github.com/repos/{repo}/check-runs/{}",
@@ -330,12 +750,142 @@ fn post_check_run(token: &str, repo: &str, sha: &str, annotations: &[Annotation]
                 body["conclusion"] = serde_json::json!("neutral");
             }
 
-            check_response(
-                github_request("PATCH", &update_url, token).send_json(body),
-                "updating check run",
-            )?;
+            call_with_retry(transport, "GitHub", "updating check run", Some(&body), || {
+                github_request("PATCH", &update_url, token)
+            })?;
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_retryable_status_retries_5xx_and_rate_limits() {
+        assert!(is_retryable_status(500));
+        assert!(is_retryable_status(503));
+        assert!(is_retryable_status(403));
+        assert!(is_retryable_status(429));
+        assert!(!is_retryable_status(200));
+        assert!(!is_retryable_status(401));
+        assert!(!is_retryable_status(404));
+        assert!(!is_retryable_status(422));
+    }
+
+    fn response(status: u16, headers: &[(&str, &str)]) -> HttpResponse {
+        HttpResponse {
+            status,
+            body: String::new(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_retry_delay_uses_retry_after_header() {
+        let resp = response(429, &[("Retry-After", "7")]);
+        assert_eq!(retry_delay(&resp, 1), Duration::from_secs(7));
+    }
+
+    #[test]
+    fn test_retry_delay_uses_rate_limit_reset_header() {
+        let epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 10;
+        let resp = response(429, &[("X-RateLimit-Reset", &epoch.to_string())]);
+        let wait = retry_delay(&resp, 1);
+        // Allow a second of slack for the time elapsed between computing
+        // `epoch` above and `retry_delay` reading `SystemTime::now()` again.
+        assert!(wait >= Duration::from_secs(9) && wait <= Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_retry_delay_also_recognizes_gitlab_rate_limit_reset_header() {
+        let epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 10;
+        let resp = response(429, &[("RateLimit-Reset", &epoch.to_string())]);
+        let wait = retry_delay(&resp, 1);
+        assert!(wait >= Duration::from_secs(9) && wait <= Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_retry_delay_prefers_the_later_of_retry_after_and_rate_limit_reset() {
+        let epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 20;
+        let resp = response(
+            429,
+            &[
+                ("Retry-After", "2"),
+                ("X-RateLimit-Reset", &epoch.to_string()),
+            ],
+        );
+        let wait = retry_delay(&resp, 1);
+        assert!(wait >= Duration::from_secs(19));
+    }
+
+    #[test]
+    fn test_retry_delay_falls_back_to_backoff_when_no_rate_limit_headers() {
+        let resp = response(500, &[]);
+        let wait = retry_delay(&resp, 2);
+        // backoff_delay(2): 500ms base * 2^2, plus up to 250ms of jitter.
+        assert!(wait >= Duration::from_millis(2000) && wait < Duration::from_millis(2250));
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles_per_attempt_and_caps_at_attempt_six() {
+        assert!(
+            backoff_delay(1) >= Duration::from_millis(1000)
+                && backoff_delay(1) < Duration::from_millis(1250)
+        );
+        assert!(
+            backoff_delay(3) >= Duration::from_millis(4000)
+                && backoff_delay(3) < Duration::from_millis(4250)
+        );
+        // attempt.min(6) caps the exponent, so attempt 6 and attempt 20
+        // both land on the same 32s base.
+        assert!(
+            backoff_delay(6) >= Duration::from_millis(32000)
+                && backoff_delay(6) < Duration::from_millis(32250)
+        );
+        assert!(
+            backoff_delay(20) >= Duration::from_millis(32000)
+                && backoff_delay(20) < Duration::from_millis(32250)
+        );
+    }
+
+    /// Proves `call_with_retry` actually retries: the fixture serves a
+    /// retryable 500 (with `Retry-After: 0` so the test doesn't really
+    /// sleep) followed by a success, and the call must return the success
+    /// rather than bailing on the first attempt.
+    #[test]
+    fn test_call_with_retry_retries_a_retryable_status_then_succeeds() {
+        let transport = ReplayTransport::load(std::path::Path::new(
+            "tests/recordings/retry_then_success.json",
+        ))
+        .unwrap();
+
+        let resp = call_with_retry(&transport, "GitHub", "test request", None, || HttpRequest {
+            method: "GET".to_string(),
+            url: "https://api.github.com/retry-test".to_string(),
+            headers: vec![],
+            body: None,
+        })
+        .unwrap();
+
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body, r#"{"ok":true}"#);
+    }
+}
@@ -0,0 +1,404 @@
+//! GitLab CI helpers for posting diff-coverage feedback on merge requests.
+//!
+//! Mirrors [`crate::github`]'s `Context`, but reads GitLab CI/CD predefined
+//! variables and talks to the GitLab v4 API instead of GitHub's REST API.
+//! GitLab has no Check Runs equivalent, so [`Context::post_annotations`]
+//! posts one diff discussion per annotation instead of a single check run.
+//!
+//! Requests go through the same [`crate::github::HttpTransport`]/
+//! `call_with_retry` machinery GitHub uses, so transient 5xx/rate-limit
+//! responses are retried with backoff here too, and the whole module can be
+//! driven by a [`crate::github::ReplayTransport`] fixture in tests instead
+//! of live `ureq` calls.
+
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+
+use crate::github::{self, HttpRequest, HttpTransport, ReviewPlatform, UreqTransport};
+use crate::model::{Annotation, CoverageData};
+
+const COMMENT_MARKER: &str = "<!-- covrs-comment -->";
+
+/// Serialize coverage as the per-file, per-line hit-count map GitLab's
+/// merge-request diff view uses to paint inline coverage gutters: a JSON
+/// object keyed by file path, each mapping line number (as a string) to hit
+/// count, skipping lines with no coverage data. A GitLab CI job exposes this
+/// as a `coverage_format: cobertura`-style artifact via `artifacts:paths` so
+/// the MR diff shows covered/uncovered lines without a matching GitLab
+/// report format.
+pub fn line_coverage_json(data: &CoverageData) -> String {
+    let files: std::collections::BTreeMap<&str, std::collections::BTreeMap<String, u64>> = data
+        .files
+        .iter()
+        .map(|file| {
+            let lines = file
+                .lines
+                .iter()
+                .map(|line| (line.line_number.to_string(), line.hit_count))
+                .collect();
+            (file.path.as_str(), lines)
+        })
+        .collect();
+
+    serde_json::to_string(&files).expect("line coverage map serialization is infallible")
+}
+
+/// Build a request with the GitLab private-token header.
+fn gitlab_request(method: &str, url: &str, token: &str) -> HttpRequest {
+    HttpRequest {
+        method: method.to_string(),
+        url: url.to_string(),
+        headers: vec![("PRIVATE-TOKEN".to_string(), token.to_string())],
+        body: None,
+    }
+}
+
+/// Percent-encode a GitLab project ID/path for use in a v4 API URL path
+/// segment. GitLab accepts either the numeric project ID or the
+/// `namespace/project` path, and the latter must be percent-encoded (see
+/// the GitLab API patterns in gitlab-cargo-shim).
+fn encode_project_id(id: &str) -> String {
+    let mut out = String::with_capacity(id.len());
+    for b in id.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// Resolved GitLab CI context, read from predefined CI/CD variables.
+pub struct Context {
+    token: String,
+    api_url: String,
+    project_id: String,
+    mr_iid: u64,
+    sha: Option<String>,
+    transport: Box<dyn HttpTransport>,
+}
+
+impl Context {
+    /// Build a context from standard GitLab CI/CD predefined variables
+    /// (`GITLAB_TOKEN`, `CI_API_V4_URL`, `CI_PROJECT_ID`,
+    /// `CI_MERGE_REQUEST_IID`, `CI_MERGE_REQUEST_SOURCE_BRANCH_SHA`).
+    pub fn from_env() -> Result<Self> {
+        Self::from_env_with_transport(UreqTransport)
+    }
+
+    /// Like [`Context::from_env`], but issues requests through `transport`
+    /// instead of live `ureq` calls. Tests use this to inject a
+    /// [`crate::github::ReplayTransport`] loaded from a
+    /// `tests/recordings/*.json` fixture.
+    pub fn from_env_with_transport(transport: impl HttpTransport + 'static) -> Result<Self> {
+        let token = std::env::var("GITLAB_TOKEN")
+            .context("GITLAB_TOKEN environment variable is required")?;
+        let api_url = std::env::var("CI_API_V4_URL")
+            .context("CI_API_V4_URL environment variable is required")?;
+        let project_id = std::env::var("CI_PROJECT_ID")
+            .context("CI_PROJECT_ID environment variable is required")?;
+        let mr_iid = std::env::var("CI_MERGE_REQUEST_IID")
+            .context("CI_MERGE_REQUEST_IID environment variable is required")?
+            .parse()
+            .context("CI_MERGE_REQUEST_IID is not a valid integer")?;
+        let sha = std::env::var("CI_MERGE_REQUEST_SOURCE_BRANCH_SHA").ok();
+
+        Ok(Self {
+            token,
+            api_url,
+            project_id,
+            mr_iid,
+            sha,
+            transport: Box::new(transport),
+        })
+    }
+
+    /// Build a context directly from its resolved fields, without reading
+    /// environment variables. Used by tests to exercise comment/discussion
+    /// posting against a [`crate::github::ReplayTransport`] without setting
+    /// up GitLab CI env vars.
+    pub fn for_testing(
+        api_url: impl Into<String>,
+        project_id: impl Into<String>,
+        mr_iid: u64,
+        sha: Option<String>,
+        transport: impl HttpTransport + 'static,
+    ) -> Self {
+        Self {
+            token: "test-token".to_string(),
+            api_url: api_url.into(),
+            project_id: project_id.into(),
+            mr_iid,
+            sha,
+            transport: Box::new(transport),
+        }
+    }
+
+    /// Base URL for this merge request's API endpoints.
+    fn mr_url(&self) -> String {
+        format!(
+            "{}/projects/{}/merge_requests/{}",
+            self.api_url.trim_end_matches('/'),
+            encode_project_id(&self.project_id),
+            self.mr_iid
+        )
+    }
+
+    /// Find an existing covrs note on this MR (by our hidden marker),
+    /// mirroring [`crate::github`]'s `find_existing_comment`.
+    fn find_existing_comment(&self) -> Result<Option<(String, u64)>> {
+        let url = format!("{}/discussions?per_page=100", self.mr_url());
+        let resp = github::call_with_retry(
+            self.transport.as_ref(),
+            "GitLab",
+            "listing MR discussions",
+            None,
+            || gitlab_request("GET", &url, &self.token),
+        )?;
+        let discussions: Vec<Discussion> =
+            serde_json::from_str(&resp.body).context("Failed to parse discussions JSON")?;
+
+        for discussion in &discussions {
+            for note in &discussion.notes {
+                if let Some(ref body) = note.body {
+                    if body.contains(COMMENT_MARKER) {
+                        return Ok(Some((discussion.id.clone(), note.id)));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl ReviewPlatform for Context {
+    /// Fetch the unified diff for the merge request's changes.
+    fn fetch_diff(&self) -> Result<String> {
+        eprintln!("Fetching diff for merge request !{} ...", self.mr_iid);
+        let url = format!("{}/changes", self.mr_url());
+        let resp = github::call_with_retry(
+            self.transport.as_ref(),
+            "GitLab",
+            "fetching MR changes",
+            None,
+            || gitlab_request("GET", &url, &self.token),
+        )?;
+        let changes: MrChanges =
+            serde_json::from_str(&resp.body).context("Failed to parse MR changes JSON")?;
+        Ok(changes_to_unified_diff(&changes.changes))
+    }
+
+    /// Create or update the covrs note on the merge request, through the
+    /// discussions API, reusing the hidden-marker dedupe logic above.
+    fn post_comment(&self, body: &str) -> Result<()> {
+        let body_with_marker = format!("{COMMENT_MARKER}\n{body}");
+        let payload = serde_json::json!({ "body": body_with_marker });
+
+        match self.find_existing_comment()? {
+            Some((discussion_id, note_id)) => {
+                let url = format!(
+                    "{}/discussions/{discussion_id}/notes/{note_id}",
+                    self.mr_url()
+                );
+                github::call_with_retry(
+                    self.transport.as_ref(),
+                    "GitLab",
+                    "updating MR note",
+                    Some(&payload),
+                    || gitlab_request("PUT", &url, &self.token),
+                )?;
+            }
+            None => {
+                let url = format!("{}/discussions", self.mr_url());
+                github::call_with_retry(
+                    self.transport.as_ref(),
+                    "GitLab",
+                    "creating MR discussion",
+                    Some(&payload),
+                    || gitlab_request("POST", &url, &self.token),
+                )?;
+            }
+        }
+
+        eprintln!("Comment posted to merge request !{}", self.mr_iid);
+        Ok(())
+    }
+
+    /// Post one diff discussion per annotation, since GitLab has no Check
+    /// Runs equivalent to group them under.
+    fn post_annotations(&self, annotations: &[Annotation]) -> Result<()> {
+        let sha = self
+            .sha
+            .as_deref()
+            .context("commit SHA is required for diff discussions")?;
+
+        for annotation in annotations {
+            let url = format!("{}/discussions", self.mr_url());
+            let payload = serde_json::json!({
+                "body": format!("{COMMENT_MARKER}\n{}", annotation.message),
+                "position": {
+                    "position_type": "text",
+                    "new_path": annotation.path,
+                    "new_line": annotation.start_line,
+                    "base_sha": sha,
+                    "start_sha": sha,
+                    "head_sha": sha,
+                },
+            });
+            github::call_with_retry(
+                self.transport.as_ref(),
+                "GitLab",
+                "creating MR diff discussion",
+                Some(&payload),
+                || gitlab_request("POST", &url, &self.token),
+            )?;
+        }
+
+        eprintln!(
+            "{} diff discussion(s) posted to merge request !{}",
+            annotations.len(),
+            self.mr_iid
+        );
+        Ok(())
+    }
+
+    fn sha(&self) -> Option<&str> {
+        self.sha.as_deref()
+    }
+}
+
+#[derive(Deserialize)]
+struct MrChange {
+    old_path: String,
+    new_path: String,
+    deleted_file: bool,
+    diff: String,
+}
+
+#[derive(Deserialize)]
+struct MrChanges {
+    changes: Vec<MrChange>,
+}
+
+/// Reconstruct a unified diff from the GitLab MR changes API, which returns
+/// each file's hunks without the `--- a/...` / `+++ b/...` headers that
+/// [`crate::diff::parse_diff`] looks for.
+fn changes_to_unified_diff(changes: &[MrChange]) -> String {
+    let mut out = String::new();
+    for change in changes {
+        if change.deleted_file {
+            continue;
+        }
+        out.push_str(&format!("--- a/{}\n", change.old_path));
+        out.push_str(&format!("+++ b/{}\n", change.new_path));
+        out.push_str(&change.diff);
+        if !change.diff.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[derive(Deserialize)]
+struct Discussion {
+    id: String,
+    notes: Vec<Note>,
+}
+
+#[derive(Deserialize)]
+struct Note {
+    id: u64,
+    body: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_project_id_passes_through_numeric_ids() {
+        assert_eq!(encode_project_id("12345"), "12345");
+    }
+
+    #[test]
+    fn test_encode_project_id_escapes_path_separators() {
+        assert_eq!(encode_project_id("my-group/my-project"), "my-group%2Fmy-project");
+    }
+
+    #[test]
+    fn test_changes_to_unified_diff_skips_deleted_files() {
+        let changes = vec![
+            MrChange {
+                old_path: "src/foo.rs".to_string(),
+                new_path: "src/foo.rs".to_string(),
+                deleted_file: false,
+                diff: "@@ -1,2 +1,3 @@\n line one\n+line two\n line three\n".to_string(),
+            },
+            MrChange {
+                old_path: "src/gone.rs".to_string(),
+                new_path: "src/gone.rs".to_string(),
+                deleted_file: true,
+                diff: "@@ -1,1 +0,0 @@\n-line one\n".to_string(),
+            },
+        ];
+
+        let diff = changes_to_unified_diff(&changes);
+
+        assert!(diff.contains("+++ b/src/foo.rs"));
+        assert!(!diff.contains("gone.rs"));
+
+        let lines = crate::diff::parse_diff(&diff);
+        assert_eq!(lines.get("src/foo.rs"), Some(&vec![2]));
+    }
+
+    #[test]
+    fn test_line_coverage_json_maps_line_numbers_to_hit_counts() {
+        use crate::model::{FileCoverage, LineCoverage};
+
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: "src/foo.rs".to_string(),
+                lines: vec![
+                    LineCoverage {
+                        line_number: 1,
+                        hit_count: 3,
+                    },
+                    LineCoverage {
+                        line_number: 2,
+                        hit_count: 0,
+                    },
+                ],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+
+        let json = line_coverage_json(&data);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed["src/foo.rs"]["1"], 3);
+        assert_eq!(parsed["src/foo.rs"]["2"], 0);
+    }
+
+    #[test]
+    fn test_line_coverage_json_keeps_files_with_no_lines_as_empty_objects() {
+        use crate::model::FileCoverage;
+
+        let data = CoverageData {
+            files: vec![FileCoverage {
+                path: "src/empty.rs".to_string(),
+                lines: vec![],
+                branches: vec![],
+                functions: vec![],
+            }],
+            ..Default::default()
+        };
+
+        let json = line_coverage_json(&data);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed["src/empty.rs"], serde_json::json!({}));
+    }
+}
@@ -3,12 +3,67 @@ use std::io::{BufReader, Read, Seek};
 use std::path::Path;
 
 use anyhow::{Context, Result};
+use regex::Regex;
 use rusqlite::Connection;
 
 use crate::db;
+use crate::demangle::DemangleOptions;
+use crate::exclude::ExclusionRules;
 use crate::model::FileCoverage;
 use crate::parsers::{self, Format};
 
+/// A compiled set of include/exclude glob patterns (`*` matches within a
+/// path segment, `**` matches across segments), checked against a file's
+/// normalized path during ingest. Exclude always wins over include; an
+/// empty include set means "no restriction" (everything passes) rather
+/// than "nothing passes".
+struct PathFilter {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl PathFilter {
+    fn new(include_globs: Option<&[String]>, exclude_globs: Option<&[String]>) -> Result<Self> {
+        let compile = |globs: Option<&[String]>| -> Result<Vec<Regex>> {
+            globs
+                .unwrap_or(&[])
+                .iter()
+                .map(|g| Regex::new(&glob_to_regex(g)))
+                .collect::<std::result::Result<_, _>>()
+                .context("Invalid glob pattern")
+        };
+        Ok(Self {
+            include: compile(include_globs)?,
+            exclude: compile(exclude_globs)?,
+        })
+    }
+
+    fn allows(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|re| re.is_match(path)))
+            && !self.exclude.iter().any(|re| re.is_match(path))
+    }
+}
+
+/// Translate a glob pattern into an anchored regex: `**` matches any run of
+/// characters (including `/`), `*` matches any run of non-`/` characters,
+/// and everything else is matched literally.
+fn glob_to_regex(glob: &str) -> String {
+    let mut re = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                re.push_str(".*");
+            }
+            '*' => re.push_str("[^/]*"),
+            _ => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    re
+}
+
 /// Normalize a single file's path relative to the project root.
 fn normalize_file_path(file: &mut FileCoverage, root: &Path) {
     let path = Path::new(&file.path);
@@ -33,7 +88,54 @@ const SNIFF_SIZE: usize = 4096;
 /// made relative to the given root directory. Pass `None` to skip
 /// normalization (paths are stored as-is from the coverage file).
 ///
+/// Exclusion rules (see [`ExclusionRules`]) are always applied — pass
+/// `exclude` to customize them beyond the built-in defaults, or `None` to
+/// use the defaults only.
+///
+/// When `source_maps` is true, each generated file is checked for a
+/// `//# sourceMappingURL=` comment or sibling `.map` (see
+/// [`crate::sourcemap`]) and, if found, its coverage is rewritten against
+/// the original sources named by the map instead of the generated file.
+///
+/// When `fixup` is true, lines that are structurally non-executable (a lone
+/// closing delimiter, a comment, or blank) are dropped from the
+/// instrumentable set before insertion (see [`crate::fixup`]), so diff
+/// reports don't flag lines that could never be hit.
+///
+/// When `max_reports` is `Some(n)`, reports beyond the newest `n` are
+/// pruned in the same transaction as this ingest (see
+/// [`db::prune_reports`]), so repeated ingestion (e.g. one report per CI
+/// run) doesn't grow the database without bound.
+///
+/// When `demangle` is `Some`, each function name is rewritten to its
+/// demangled form with those options (see [`crate::demangle`]) before
+/// insertion, so reports from tools that surface raw linker symbols read
+/// the same as anything else.
+///
+/// When `merge_into` is `Some(target)`, this file's coverage is folded into
+/// the existing report named `target` (see [`db::merge_into_report`])
+/// instead of being inserted as a new report — lets a test suite sharded
+/// across machines ingest each shard's coverage file into one accumulating
+/// report rather than ending up with N separate ones. `report_name`,
+/// `overwrite` and `max_reports` are ignored in that case.
+///
+/// When `include_globs`/`exclude_globs` are given, each file's normalized,
+/// root-relative path is checked against them with [`PathFilter`]
+/// (deny takes precedence over allow) right after normalization; files that
+/// don't pass are dropped before exclusion rules, source-map remapping, or
+/// insertion ever see them, and don't count towards the empty-input
+/// warning. Lets e.g. Go or V8 profiles that sweep up `vendor/` or
+/// `node_modules/` be narrowed to the paths a user actually cares about.
+///
+/// When `source_root` is `Some` and the resolved format is [`Format::V8`],
+/// each script's source is read relative to that root (see
+/// [`crate::parsers::v8::V8Parser::with_source_root`]) instead of from its
+/// `url`'s literal local path — lets a precise-coverage capture taken
+/// elsewhere (a CI container, a different checkout layout) be matched up
+/// against the sources on disk here. Ignored for other formats.
+///
 /// Returns (report_id, format, actual_report_name).
+#[allow(clippy::too_many_arguments)]
 pub fn ingest(
     conn: &mut Connection,
     file_path: &Path,
@@ -41,7 +143,24 @@ pub fn ingest(
     report_name: Option<&str>,
     overwrite: bool,
     root: Option<&Path>,
+    exclude: Option<&ExclusionRules>,
+    source_maps: bool,
+    fixup: bool,
+    demangle: Option<DemangleOptions>,
+    max_reports: Option<u32>,
+    merge_into: Option<&str>,
+    include_globs: Option<&[String]>,
+    exclude_globs: Option<&[String]>,
+    source_root: Option<&Path>,
 ) -> Result<(i64, Format, String)> {
+    let default_rules;
+    let exclude = match exclude {
+        Some(rules) => rules,
+        None => {
+            default_rules = ExclusionRules::defaults();
+            &default_rules
+        }
+    };
     let file =
         File::open(file_path).with_context(|| format!("Failed to open {}", file_path.display()))?;
     let mut reader = BufReader::new(file);
@@ -67,6 +186,10 @@ pub fn ingest(
     };
 
     let format = parser.format();
+    let parser: Box<dyn parsers::CoverageParser> = match (format, source_root) {
+        (Format::V8, Some(root)) => Box::new(parsers::v8::V8Parser::with_source_root(root)),
+        _ => parser,
+    };
 
     // Generate report name if not provided
     let name = match report_name {
@@ -79,26 +202,71 @@ pub fn ingest(
     };
 
     let source_file_str = file_path.to_str();
+    let path_filter = PathFilter::new(include_globs, exclude_globs)?;
 
     // Track whether any files were emitted so we can warn on empty input.
     let mut file_count: usize = 0;
 
-    let report_id = db::insert_coverage_streaming(
-        conn,
-        &name,
-        &format.to_string(),
-        source_file_str,
-        overwrite,
-        |emit| {
-            parser.parse_streaming(&mut reader, &mut |mut file_cov| {
-                if let Some(root) = root {
-                    normalize_file_path(&mut file_cov, root);
-                }
-                file_count += 1;
-                emit(&file_cov)
-            })
-        },
-    )?;
+    let mut process_one = |mut file_cov: FileCoverage, out: &mut Vec<FileCoverage>| {
+        if let Some(root) = root {
+            normalize_file_path(&mut file_cov, root);
+        }
+        if !path_filter.allows(&file_cov.path) {
+            return;
+        }
+        exclude.apply(&mut file_cov, root);
+
+        let mut remapped = if source_maps {
+            crate::sourcemap::remap_file(file_cov, root)
+        } else {
+            vec![file_cov]
+        };
+        for mut file_cov in remapped.drain(..) {
+            if fixup {
+                crate::fixup::apply(&mut file_cov, root);
+            }
+            if let Some(opts) = demangle {
+                crate::demangle::apply(&mut file_cov, opts);
+            }
+            file_count += 1;
+            out.push(file_cov);
+        }
+    };
+
+    let report_id = if let Some(target) = merge_into {
+        let mut files = Vec::new();
+        parser.parse_streaming(&mut reader, &mut |file_cov| {
+            process_one(file_cov, &mut files);
+            Ok(())
+        })?;
+        let data = crate::model::CoverageData {
+            files,
+            ..Default::default()
+        };
+        let (report_id, _, _) =
+            db::merge_into_report(conn, target, &format.to_string(), source_file_str, &data)?;
+        report_id
+    } else {
+        db::insert_coverage_streaming(
+            conn,
+            &name,
+            &format.to_string(),
+            source_file_str,
+            overwrite,
+            max_reports,
+            |emit| {
+                parser.parse_streaming(&mut reader, &mut |file_cov| {
+                    let mut out = Vec::new();
+                    process_one(file_cov, &mut out);
+                    for file_cov in &out {
+                        emit(file_cov)?;
+                    }
+                    Ok(())
+                })?;
+                Ok(())
+            },
+        )?
+    };
 
     if file_count == 0 {
         eprintln!(
@@ -107,5 +275,38 @@ pub fn ingest(
         );
     }
 
-    Ok((report_id, format, name))
+    let actual_name = match merge_into {
+        Some(target) => target.to_string(),
+        None => name,
+    };
+
+    Ok((report_id, format, actual_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathFilter;
+
+    #[test]
+    fn test_path_filter_no_patterns_allows_everything() {
+        let filter = PathFilter::new(None, None).unwrap();
+        assert!(filter.allows("src/lib.rs"));
+    }
+
+    #[test]
+    fn test_path_filter_include_restricts_to_matching_paths() {
+        let include = vec!["src/**".to_string()];
+        let filter = PathFilter::new(Some(&include), None).unwrap();
+        assert!(filter.allows("src/lib.rs"));
+        assert!(!filter.allows("vendor/lib.rs"));
+    }
+
+    #[test]
+    fn test_path_filter_exclude_wins_over_include() {
+        let include = vec!["**/*.go".to_string()];
+        let exclude = vec!["**/*_test.go".to_string()];
+        let filter = PathFilter::new(Some(&include), Some(&exclude)).unwrap();
+        assert!(filter.allows("pkg/foo.go"));
+        assert!(!filter.allows("pkg/foo_test.go"));
+    }
 }
@@ -1,7 +1,15 @@
+pub mod compare;
 pub mod db;
+pub mod demangle;
 pub mod detect;
 pub mod diff;
 pub mod error;
+pub mod exclude;
+pub mod fixup;
+pub mod github;
+pub mod gitlab;
 pub mod ingest;
 pub mod model;
+pub mod output;
 pub mod parsers;
+pub mod sourcemap;
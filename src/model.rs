@@ -25,6 +25,123 @@ pub struct BranchCoverage {
     pub line_number: u32,
     pub branch_index: u32,
     pub hit_count: u64,
+    /// Groups arms belonging to the same decision (e.g. the two outcomes
+    /// of an `if`, or each operand of a chained `&&`/`||`), so covrs can
+    /// report MC/DC-style condition coverage rather than treating every
+    /// arm as independent. `None` when the source format gives no
+    /// grouping information.
+    pub group_id: Option<u32>,
+    /// Structural shape of the decision this arm belongs to, where the
+    /// source format exposes enough detail to tell — `Unknown` otherwise.
+    pub kind: BranchKind,
+    /// This arm's own source line, when the format records per-arm
+    /// locations distinct from the decision's line (e.g. Istanbul's
+    /// `branchMap[n].locations`, where an `if`/`else` arm can start on a
+    /// different line than the `if`). `None` when the format only gives a
+    /// single line for the whole decision, in which case `line_number`
+    /// already covers it.
+    pub arm_line: Option<u32>,
+}
+
+/// Coarse structural classification of a branch arm's decision, for
+/// formats that distinguish branch shapes rather than emitting a flat
+/// arm list. Lets downstream reporters show e.g. "2 of 3 switch arms
+/// taken" instead of treating every arm the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BranchKind {
+    If,
+    Switch,
+    Ternary,
+    Assert,
+    #[default]
+    Unknown,
+}
+
+/// One condition within a [`Decision`] — a stable index within the
+/// decision's `group_id`, with whether this condition's true and false
+/// arms were each exercised at least once.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    pub id: u32,
+    pub true_taken: bool,
+    pub false_taken: bool,
+}
+
+impl Condition {
+    /// A condition is "independently covered" when both of its arms have
+    /// been exercised. This approximates true MC/DC independence (a pair
+    /// of test runs differing only in this condition that flip the
+    /// decision's outcome) with the closest signal available from
+    /// formats that only report aggregate true/false counts per
+    /// condition, such as Clover's `truecount`/`falsecount`.
+    #[must_use]
+    pub fn independently_covered(&self) -> bool {
+        self.true_taken && self.false_taken
+    }
+}
+
+/// A single decision (e.g. an `if`, or a Clover `type="cond"` line for a
+/// chained boolean expression) made up of one or more [`Condition`]s that
+/// together determine its outcome. Built from the arms sharing a
+/// `group_id` via [`group_decisions`].
+#[derive(Debug, Clone)]
+pub struct Decision {
+    pub line_number: u32,
+    pub conditions: Vec<Condition>,
+}
+
+impl Decision {
+    /// Fraction of this decision's conditions that are independently
+    /// covered (see [`Condition::independently_covered`]).
+    #[must_use]
+    pub fn mcdc_rate(&self) -> f64 {
+        rate(
+            self.conditions
+                .iter()
+                .filter(|c| c.independently_covered())
+                .count() as u64,
+            self.conditions.len() as u64,
+        )
+    }
+}
+
+/// Group `branches` into [`Decision`]s by `(line_number, group_id)`,
+/// pairing consecutive arms (ordered by `branch_index`) as a condition's
+/// true/false outcome — the convention parsers use when they know the
+/// true/false split of each condition (e.g. Clover pushes a true arm then
+/// a false arm per condition). Arms with no `group_id` belong to no
+/// decision, since the source format gave no grouping information.
+#[must_use]
+pub fn group_decisions(branches: &[BranchCoverage]) -> Vec<Decision> {
+    let mut by_group: std::collections::BTreeMap<(u32, u32), Vec<&BranchCoverage>> =
+        std::collections::BTreeMap::new();
+    for branch in branches {
+        if let Some(group_id) = branch.group_id {
+            by_group
+                .entry((branch.line_number, group_id))
+                .or_default()
+                .push(branch);
+        }
+    }
+
+    let mut decisions = Vec::new();
+    for ((line_number, _group_id), mut arms) in by_group {
+        arms.sort_by_key(|b| b.branch_index);
+        let conditions = arms
+            .chunks(2)
+            .enumerate()
+            .map(|(id, pair)| Condition {
+                id: id as u32,
+                true_taken: pair.first().is_some_and(|b| b.hit_count > 0),
+                false_taken: pair.get(1).is_some_and(|b| b.hit_count > 0),
+            })
+            .collect();
+        decisions.push(Decision {
+            line_number,
+            conditions,
+        });
+    }
+    decisions
 }
 
 /// A function/method that was instrumentable.
@@ -58,12 +175,134 @@ impl FileCoverage {
 #[derive(Debug, Clone, Default)]
 pub struct CoverageData {
     pub files: Vec<FileCoverage>,
+    /// Recorded coverage sessions (e.g. JaCoCo `<sessioninfo>` entries),
+    /// for formats that declare when each run happened.
+    pub sessions: Vec<SessionInfo>,
+    /// Report-level counter totals declared by the source format, if any —
+    /// lets callers reconcile covrs's own computed totals against the
+    /// tool's rather than only trusting line-by-line recomputation.
+    pub summary: Option<CoverageSummary>,
+}
+
+/// A single recorded coverage session (JaCoCo `<sessioninfo id/start/dump>`).
+#[derive(Debug, Clone)]
+pub struct SessionInfo {
+    pub id: String,
+    /// Session start time, epoch milliseconds.
+    pub start: u64,
+    /// Session dump (report generation) time, epoch milliseconds.
+    pub dump: u64,
+}
+
+/// Aggregate missed/covered counts per counter type, taken directly from
+/// the source format's own report-level totals (e.g. JaCoCo's top-level
+/// `<counter>` elements) rather than recomputed from line/branch rows.
+#[derive(Debug, Clone, Default)]
+pub struct CoverageSummary {
+    pub instructions_missed: u64,
+    pub instructions_covered: u64,
+    pub branches_missed: u64,
+    pub branches_covered: u64,
+    pub lines_missed: u64,
+    pub lines_covered: u64,
+    pub methods_missed: u64,
+    pub methods_covered: u64,
+    pub complexity_missed: u64,
+    pub complexity_covered: u64,
 }
 
 impl CoverageData {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Fold `other`'s files into `self`, coalescing entries that share a
+    /// `path` via [`merge_files`]. Lets callers combine several coverage
+    /// dumps (e.g. sharded CI jobs, or JaCoCo `<sessioninfo>` runs that
+    /// re-emit the same file) into one unified model before reporting.
+    pub fn merge(&mut self, other: CoverageData) {
+        self.files.extend(other.files);
+        self.files = merge_files(std::mem::take(&mut self.files));
+    }
+}
+
+/// Coalesce `files` entries that share a `path`: line hit counts are summed
+/// per `line_number`, branches are summed per `(line_number, branch_index)`,
+/// and functions are summed per `(name, start_line)`. This mirrors how
+/// multi-run profilers accumulate `count` across separate coverage dumps.
+#[must_use]
+pub fn merge_files(files: Vec<FileCoverage>) -> Vec<FileCoverage> {
+    let mut merged: std::collections::BTreeMap<String, FileCoverage> = std::collections::BTreeMap::new();
+
+    for file in files {
+        match merged.get_mut(&file.path) {
+            Some(existing) => merge_file_coverage(existing, file),
+            None => {
+                merged.insert(file.path.clone(), file);
+            }
+        }
+    }
+
+    merged.into_values().collect()
+}
+
+/// Add `b` to `a`, saturating at `u64::MAX` rather than panicking/wrapping
+/// on overflow (as grcov does), since a hit count pegged at the max is a
+/// far more useful result than a crash or a silently wrapped-around
+/// near-zero count. Warns on stderr each time an addition saturates, so
+/// the loss is visible rather than silent.
+fn saturating_add_warn(a: u64, b: u64) -> u64 {
+    let (sum, overflowed) = a.overflowing_add(b);
+    if overflowed {
+        eprintln!(
+            "Warning: hit count overflowed while merging ({a} + {b}); saturating at {}",
+            u64::MAX
+        );
+        u64::MAX
+    } else {
+        sum
+    }
+}
+
+/// Fold `incoming`'s lines/branches/functions into `merged` in place,
+/// unioning by key and summing hit counts (see [`merge_files`]).
+fn merge_file_coverage(merged: &mut FileCoverage, incoming: FileCoverage) {
+    for line in incoming.lines {
+        match merged
+            .lines
+            .iter_mut()
+            .find(|l| l.line_number == line.line_number)
+        {
+            Some(existing) => {
+                existing.hit_count = saturating_add_warn(existing.hit_count, line.hit_count);
+            }
+            None => merged.lines.push(line),
+        }
+    }
+
+    for branch in incoming.branches {
+        match merged.branches.iter_mut().find(|b| {
+            b.line_number == branch.line_number && b.branch_index == branch.branch_index
+        }) {
+            Some(existing) => {
+                existing.hit_count = saturating_add_warn(existing.hit_count, branch.hit_count);
+            }
+            None => merged.branches.push(branch),
+        }
+    }
+
+    for func in incoming.functions {
+        match merged
+            .functions
+            .iter_mut()
+            .find(|f| f.name == func.name && f.start_line == func.start_line)
+        {
+            Some(existing) => {
+                existing.hit_count = saturating_add_warn(existing.hit_count, func.hit_count);
+            }
+            None => merged.functions.push(func),
+        }
+    }
 }
 
 /// Summary stats across all reports in the database.
@@ -76,6 +315,13 @@ pub struct ReportSummary {
     pub covered_branches: u64,
     pub total_functions: u64,
     pub covered_functions: u64,
+    /// Number of conditions grouped into a decision via `group_id` (see
+    /// [`group_decisions`]) — 0 when no source format in the database
+    /// supplied decision grouping.
+    pub total_conditions: u64,
+    /// Of `total_conditions`, how many were independently covered (see
+    /// [`Condition::independently_covered`]).
+    pub independently_covered_conditions: u64,
 }
 
 impl ReportSummary {
@@ -93,6 +339,13 @@ impl ReportSummary {
     pub fn function_rate(&self) -> f64 {
         rate(self.covered_functions, self.total_functions)
     }
+
+    /// MC/DC-style condition coverage rate, or 0.0 when no source format
+    /// in the database supplied decision grouping.
+    #[must_use]
+    pub fn mcdc_rate(&self) -> f64 {
+        rate(self.independently_covered_conditions, self.total_conditions)
+    }
 }
 
 /// Per-file summary row.
@@ -149,6 +402,62 @@ impl FileDiffCoverage {
     }
 }
 
+/// Per-file MC/DC-style branch/condition diff coverage detail. Each entry
+/// in `covered_lines`/`missed_lines` is one *decision* — a grouped set of
+/// branch arms sharing a `group_id` (e.g. an `if` or a chained
+/// `&&`/`||`), or a single ungrouped arm when the source format gave no
+/// grouping information, in which case the decision degrades to plain
+/// branch counting.
+#[derive(Debug)]
+pub struct FileBranchDiffCoverage {
+    pub path: String,
+    /// Diff lines whose decision had every arm/condition exercised.
+    pub covered_lines: Vec<u32>,
+    /// Diff lines whose decision left at least one arm/condition untaken.
+    pub missed_lines: Vec<u32>,
+    /// `(line_number, arms_taken, arms_total)` for entries in `missed_lines`
+    /// whose decision had SOME but not all arms taken — used to report
+    /// "branch taken 1/2 times" rather than a flat miss. Decisions with
+    /// zero arms taken are omitted, since that case is already fully
+    /// described by `missed_lines`.
+    pub partial: Vec<(u32, usize, usize)>,
+}
+
+impl FileBranchDiffCoverage {
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.covered_lines.len() + self.missed_lines.len()
+    }
+
+    #[must_use]
+    pub fn rate(&self) -> f64 {
+        rate(self.covered_lines.len() as u64, self.total() as u64)
+    }
+}
+
+/// Per-file function diff coverage detail: which functions *defined* on a
+/// diff line (by `start_line`) were exercised at all.
+#[derive(Debug)]
+pub struct FileFunctionDiffCoverage {
+    pub path: String,
+    /// Start lines (on the diff) of functions hit at least once.
+    pub covered_lines: Vec<u32>,
+    /// Start lines (on the diff) of functions never hit.
+    pub missed_lines: Vec<u32>,
+}
+
+impl FileFunctionDiffCoverage {
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.covered_lines.len() + self.missed_lines.len()
+    }
+
+    #[must_use]
+    pub fn rate(&self) -> f64 {
+        rate(self.covered_lines.len() as u64, self.total() as u64)
+    }
+}
+
 /// A single annotation to attach to a GitHub check run.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct Annotation {
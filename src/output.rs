@@ -0,0 +1,408 @@
+//! Coverage export writers: serialize `CoverageData` back into on-disk
+//! coverage report formats — the inverse of the parsers in
+//! `crate::parsers`. Lets covrs convert between formats (ingest Cobertura,
+//! export Clover, etc.) rather than only ever flowing one direction.
+use std::io::Cursor;
+
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::Writer;
+use serde_json::json;
+
+use crate::model::{BranchCoverage, CoverageData, FileCoverage};
+
+/// Serializes `CoverageData` into a specific on-disk coverage format.
+pub trait CoverageWriter {
+    /// Render `data` as this writer's format.
+    fn write(&self, data: &CoverageData) -> String;
+}
+
+/// Cobertura XML writer. Delegates to the existing exporter (see [`crate::parsers::cobertura::export_data`]) so callers
+/// that pick a writer generically (e.g. dispatching on [`crate::parsers::Format`])
+/// get the same output as `covrs export --format cobertura`.
+pub struct CoberturaWriter;
+
+impl CoverageWriter for CoberturaWriter {
+    fn write(&self, data: &CoverageData) -> String {
+        crate::parsers::cobertura::export_data(data)
+    }
+}
+
+/// Clover XML writer — the inverse of `crate::parsers::clover`'s parse
+/// logic: each `LineCoverage` becomes a `<line type="stmt">`, each
+/// `FunctionCoverage` a `<line type="method">`, and `BranchCoverage` arms
+/// sharing a line are paired back up into a `<line type="cond"
+/// truecount=".." falsecount=".."/>`. Like the Cobertura exporter (and
+/// unlike the string-built LCOV exporter), this one is built with
+/// `quick_xml::Writer`; it interleaves computed `<metrics>` aggregates into
+/// the tree as it's written rather than emit a flat sequence of lines.
+pub struct CloverWriter;
+
+impl CoverageWriter for CloverWriter {
+    fn write(&self, data: &CoverageData) -> String {
+        write_clover(data)
+    }
+}
+
+/// Istanbul/NYC `coverage-final.json` writer — the inverse of
+/// `crate::parsers::istanbul`'s parse logic. Since the model only tracks
+/// per-line and per-function hit counts (not the original statement/function
+/// source spans), statements are synthesized one-per-line with a
+/// zero-width `{start,end}` location, and `fnMap` locations are
+/// reconstructed from `FunctionCoverage::start_line`/`end_line` rather than
+/// recovered byte-for-byte from the original `coverage-final.json`.
+pub struct IstanbulWriter;
+
+impl CoverageWriter for IstanbulWriter {
+    fn write(&self, data: &CoverageData) -> String {
+        write_istanbul(data)
+    }
+}
+
+fn write_istanbul(data: &CoverageData) -> String {
+    let mut root = serde_json::Map::new();
+    for file in &data.files {
+        root.insert(file.path.clone(), istanbul_file_entry(file));
+    }
+    serde_json::to_string_pretty(&root).expect("writer only emits values built from our own model")
+}
+
+/// Build one file's `{ statementMap, s, fnMap, f, branchMap, b }` entry.
+fn istanbul_file_entry(file: &FileCoverage) -> serde_json::Value {
+    let mut statement_map = serde_json::Map::new();
+    let mut s = serde_json::Map::new();
+    for (i, line) in file.lines.iter().enumerate() {
+        let idx = i.to_string();
+        statement_map.insert(
+            idx.clone(),
+            json!({
+                "start": { "line": line.line_number, "column": 0 },
+                "end": { "line": line.line_number, "column": 0 },
+            }),
+        );
+        s.insert(idx, json!(line.hit_count));
+    }
+
+    let mut fn_map = serde_json::Map::new();
+    let mut f = serde_json::Map::new();
+    for (i, function) in file.functions.iter().enumerate() {
+        let idx = i.to_string();
+        let start_line = function.start_line.unwrap_or(0);
+        let end_line = function.end_line.unwrap_or(start_line);
+        fn_map.insert(
+            idx.clone(),
+            json!({
+                "name": function.name,
+                "decl": {
+                    "start": { "line": start_line, "column": 0 },
+                    "end": { "line": start_line, "column": 0 },
+                },
+                "loc": {
+                    "start": { "line": start_line, "column": 0 },
+                    "end": { "line": end_line, "column": 0 },
+                },
+            }),
+        );
+        f.insert(idx, json!(function.hit_count));
+    }
+
+    let mut branch_map = serde_json::Map::new();
+    let mut b = serde_json::Map::new();
+    for (i, (line_number, arms)) in group_branch_arms(&file.branches).into_iter().enumerate() {
+        let idx = i.to_string();
+        let locations: Vec<serde_json::Value> = arms
+            .iter()
+            .map(|_| {
+                json!({
+                    "start": { "line": line_number, "column": 0 },
+                    "end": { "line": line_number, "column": 0 },
+                })
+            })
+            .collect();
+        branch_map.insert(
+            idx.clone(),
+            json!({
+                "type": "branch",
+                "loc": {
+                    "start": { "line": line_number, "column": 0 },
+                    "end": { "line": line_number, "column": 0 },
+                },
+                "locations": locations,
+            }),
+        );
+        b.insert(
+            idx,
+            json!(arms.iter().map(|branch| branch.hit_count).collect::<Vec<_>>()),
+        );
+    }
+
+    json!({
+        "path": file.path,
+        "statementMap": statement_map,
+        "s": s,
+        "fnMap": fn_map,
+        "f": f,
+        "branchMap": branch_map,
+        "b": b,
+    })
+}
+
+type XmlWriter = Writer<Cursor<Vec<u8>>>;
+
+fn write_clover(data: &CoverageData) -> String {
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+
+    let mut coverage = BytesStart::new("coverage");
+    coverage.push_attribute(("generated", "0"));
+    coverage.push_attribute(("clover", "4.4.1"));
+    writer.write_event(Event::Start(coverage)).unwrap();
+
+    let mut project = BytesStart::new("project");
+    project.push_attribute(("timestamp", "0"));
+    writer.write_event(Event::Start(project)).unwrap();
+    write_metrics(&mut writer, &data.files);
+
+    let mut package = BytesStart::new("package");
+    package.push_attribute(("name", "default"));
+    writer.write_event(Event::Start(package)).unwrap();
+    write_metrics(&mut writer, &data.files);
+
+    for file in &data.files {
+        write_file(&mut writer, file);
+    }
+
+    writer
+        .write_event(Event::End(BytesEnd::new("package")))
+        .unwrap();
+    writer
+        .write_event(Event::End(BytesEnd::new("project")))
+        .unwrap();
+    writer
+        .write_event(Event::End(BytesEnd::new("coverage")))
+        .unwrap();
+
+    let bytes = writer.into_inner().into_inner();
+    String::from_utf8(bytes).expect("writer only emits text we built from &str/String")
+}
+
+/// Emit a `<metrics>` element aggregating `files` — used at project,
+/// package, and per-file scope (matching Clover's own nesting, see
+/// [`crate::parsers::clover`]'s module doc).
+fn write_metrics(writer: &mut XmlWriter, files: &[FileCoverage]) {
+    let statements: usize = files.iter().map(|f| f.lines.len()).sum();
+    let covered_statements = files
+        .iter()
+        .flat_map(|f| &f.lines)
+        .filter(|l| l.hit_count > 0)
+        .count();
+    let methods: usize = files.iter().map(|f| f.functions.len()).sum();
+    let covered_methods = files
+        .iter()
+        .flat_map(|f| &f.functions)
+        .filter(|f| f.hit_count > 0)
+        .count();
+    let conditionals: usize = files.iter().map(|f| f.branches.len()).sum();
+    let covered_conditionals = files
+        .iter()
+        .flat_map(|f| &f.branches)
+        .filter(|b| b.hit_count > 0)
+        .count();
+
+    let mut metrics = BytesStart::new("metrics");
+    metrics.push_attribute(("files", files.len().to_string().as_str()));
+    metrics.push_attribute(("statements", statements.to_string().as_str()));
+    metrics.push_attribute(("coveredstatements", covered_statements.to_string().as_str()));
+    metrics.push_attribute(("methods", methods.to_string().as_str()));
+    metrics.push_attribute(("coveredmethods", covered_methods.to_string().as_str()));
+    metrics.push_attribute(("conditionals", conditionals.to_string().as_str()));
+    metrics.push_attribute((
+        "coveredconditionals",
+        covered_conditionals.to_string().as_str(),
+    ));
+    writer.write_event(Event::Empty(metrics)).unwrap();
+}
+
+fn write_file(writer: &mut XmlWriter, file: &FileCoverage) {
+    let mut file_el = BytesStart::new("file");
+    file_el.push_attribute(("path", file.path.as_str()));
+    file_el.push_attribute(("name", file_name(&file.path).as_str()));
+    writer.write_event(Event::Start(file_el)).unwrap();
+
+    write_metrics(writer, std::slice::from_ref(file));
+
+    for line in &file.lines {
+        let mut el = BytesStart::new("line");
+        el.push_attribute(("num", line.line_number.to_string().as_str()));
+        el.push_attribute(("count", line.hit_count.to_string().as_str()));
+        el.push_attribute(("type", "stmt"));
+        writer.write_event(Event::Empty(el)).unwrap();
+    }
+
+    for function in &file.functions {
+        let Some(start_line) = function.start_line else {
+            continue;
+        };
+        let mut el = BytesStart::new("line");
+        el.push_attribute(("num", start_line.to_string().as_str()));
+        el.push_attribute(("count", function.hit_count.to_string().as_str()));
+        el.push_attribute(("type", "method"));
+        el.push_attribute(("signature", function.name.as_str()));
+        writer.write_event(Event::Empty(el)).unwrap();
+    }
+
+    for (line_number, arms) in group_branch_arms(&file.branches) {
+        let truecount = arms.iter().step_by(2).filter(|b| b.hit_count > 0).count();
+        let falsecount = arms
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .filter(|b| b.hit_count > 0)
+            .count();
+        let count: u64 = arms.iter().map(|b| b.hit_count).sum();
+
+        let mut el = BytesStart::new("line");
+        el.push_attribute(("num", line_number.to_string().as_str()));
+        el.push_attribute(("count", count.to_string().as_str()));
+        el.push_attribute(("type", "cond"));
+        el.push_attribute(("truecount", truecount.to_string().as_str()));
+        el.push_attribute(("falsecount", falsecount.to_string().as_str()));
+        writer.write_event(Event::Empty(el)).unwrap();
+    }
+
+    writer
+        .write_event(Event::End(BytesEnd::new("file")))
+        .unwrap();
+}
+
+/// Group branch arms by `line_number`, preserving their relative order —
+/// the inverse of the Clover parser's true-arm/false-arm-per-condition
+/// push order, so consecutive arms pair back up as `truecount`/`falsecount`.
+fn group_branch_arms(branches: &[BranchCoverage]) -> Vec<(u32, Vec<&BranchCoverage>)> {
+    let mut by_line: Vec<(u32, Vec<&BranchCoverage>)> = Vec::new();
+    for branch in branches {
+        match by_line
+            .iter_mut()
+            .find(|(line_number, _)| *line_number == branch.line_number)
+        {
+            Some((_, arms)) => arms.push(branch),
+            None => by_line.push((branch.line_number, vec![branch])),
+        }
+    }
+    by_line
+}
+
+fn file_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{FunctionCoverage, LineCoverage};
+
+    fn sample_data() -> CoverageData {
+        CoverageData {
+            files: vec![FileCoverage {
+                path: "src/main.rs".to_string(),
+                lines: vec![
+                    LineCoverage {
+                        line_number: 1,
+                        hit_count: 3,
+                    },
+                    LineCoverage {
+                        line_number: 2,
+                        hit_count: 0,
+                    },
+                ],
+                branches: vec![
+                    BranchCoverage {
+                        line_number: 5,
+                        branch_index: 0,
+                        hit_count: 1,
+                        group_id: Some(5),
+                        kind: crate::model::BranchKind::Unknown,
+                        arm_line: None,
+                    },
+                    BranchCoverage {
+                        line_number: 5,
+                        branch_index: 1,
+                        hit_count: 0,
+                        group_id: Some(5),
+                        kind: crate::model::BranchKind::Unknown,
+                        arm_line: None,
+                    },
+                ],
+                functions: vec![FunctionCoverage {
+                    name: "main".to_string(),
+                    start_line: Some(1),
+                    end_line: None,
+                    hit_count: 3,
+                }],
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_clover_writer_emits_expected_line_and_condition_elements() {
+        let data = sample_data();
+        let xml = CloverWriter.write(&data);
+
+        assert!(xml.contains("clover=\"4.4.1\""));
+        assert!(xml.contains("path=\"src/main.rs\""));
+        assert!(xml.contains(r#"<line num="1" count="3" type="stmt"/>"#));
+        assert!(xml.contains(r#"<line num="2" count="0" type="stmt"/>"#));
+        assert!(xml.contains(r#"type="method" signature="main""#));
+
+        // 1 condition (2 paired arms) on line 5: true arm hit, false arm
+        // missed.
+        assert!(xml.contains(r#"<line num="5" count="1" type="cond" truecount="1" falsecount="0"/>"#));
+    }
+
+    #[test]
+    fn test_clover_writer_emits_metrics_aggregates() {
+        let data = sample_data();
+        let xml = CloverWriter.write(&data);
+
+        assert!(xml.contains("statements=\"2\""));
+        assert!(xml.contains("coveredstatements=\"1\""));
+        assert!(xml.contains("methods=\"1\""));
+        assert!(xml.contains("coveredmethods=\"1\""));
+        assert!(xml.contains("conditionals=\"2\""));
+        assert!(xml.contains("coveredconditionals=\"1\""));
+    }
+
+    #[test]
+    fn test_cobertura_writer_delegates_to_export_data() {
+        let data = sample_data();
+        let xml = CoberturaWriter.write(&data);
+        assert_eq!(xml, crate::parsers::cobertura::export_data(&data));
+    }
+
+    #[test]
+    fn test_istanbul_writer_round_trips_through_the_parser() {
+        let data = sample_data();
+        let json = IstanbulWriter.write(&data);
+
+        let reparsed = crate::parsers::istanbul::parse(json.as_bytes()).unwrap();
+        assert_eq!(reparsed.files.len(), 1);
+        let file = &reparsed.files[0];
+        assert_eq!(file.path, "src/main.rs");
+
+        assert_eq!(file.lines.len(), 2);
+        let line1 = file.lines.iter().find(|l| l.line_number == 1).unwrap();
+        assert_eq!(line1.hit_count, 3);
+        let line2 = file.lines.iter().find(|l| l.line_number == 2).unwrap();
+        assert_eq!(line2.hit_count, 0);
+
+        assert_eq!(file.functions.len(), 1);
+        assert_eq!(file.functions[0].name, "main");
+        assert_eq!(file.functions[0].hit_count, 3);
+
+        assert_eq!(file.branches.len(), 2);
+        assert_eq!(file.branches[0].hit_count, 1);
+        assert_eq!(file.branches[1].hit_count, 0);
+    }
+}
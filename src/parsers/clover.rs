@@ -179,6 +179,13 @@ fn parse_streaming(
                                     let fc = falsecount.unwrap_or(0);
                                     let num_conditions = tc.max(fc);
                                     let idx = branch_indices.entry(line_number).or_insert(0);
+                                    // All conditions on this line are one
+                                    // decision — group them so downstream
+                                    // MC/DC reporting (see
+                                    // `model::group_decisions`) pairs each
+                                    // condition's true/false arms rather
+                                    // than treating every arm independently.
+                                    let decision_id = line_number;
 
                                     for i in 0..num_conditions {
                                         // True arm for condition i
@@ -187,6 +194,9 @@ fn parse_streaming(
                                             line_number,
                                             branch_index: *idx,
                                             hit_count: true_hit,
+                                            group_id: Some(decision_id),
+                                            kind: BranchKind::Unknown,
+                                            arm_line: None,
                                         });
                                         *idx += 1;
 
@@ -196,6 +206,9 @@ fn parse_streaming(
                                             line_number,
                                             branch_index: *idx,
                                             hit_count: false_hit,
+                                            group_id: Some(decision_id),
+                                            kind: BranchKind::Unknown,
+                                            arm_line: None,
                                         });
                                         *idx += 1;
                                     }
@@ -348,5 +361,17 @@ mod tests {
         assert_eq!(file.branches.len(), 2);
         assert_eq!(file.branches[0].hit_count, 1); // true arm
         assert_eq!(file.branches[1].hit_count, 0); // false arm
+
+        // Both arms belong to the same decision (this line).
+        assert_eq!(file.branches[0].group_id, Some(5));
+        assert_eq!(file.branches[1].group_id, Some(5));
+
+        // The condition's false arm was never exercised, so it isn't
+        // independently covered under MC/DC even though the line's
+        // overall hit_count is nonzero.
+        let decisions = group_decisions(&file.branches);
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].conditions.len(), 1);
+        assert!(!decisions[0].conditions[0].independently_covered());
     }
 }
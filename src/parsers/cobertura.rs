@@ -14,7 +14,11 @@
 ///             </methods>
 ///             <lines>
 ///               <line number="..." hits="..." branch="true|false"
-///                     condition-coverage="50% (1/2)" />
+///                     condition-coverage="50% (1/2)">
+///                 <conditions>
+///                   <condition number="0" type="jump" coverage="50%"/>
+///                 </conditions>
+///               </line>
 ///             </lines>
 ///           </class>
 ///         </classes>
@@ -22,12 +26,16 @@
 ///     </packages>
 ///   </coverage>
 use std::collections::HashMap;
+use std::io::{BufRead, Cursor};
 use std::path::Path;
 use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
-use quick_xml::events::Event;
+use quick_xml::events::{BytesDecl, BytesStart, BytesText, Event};
+use quick_xml::Writer;
 use regex::Regex;
+use rusqlite::Connection;
 
 /// Pre-compiled regex for condition-coverage attributes like "75% (3/4)".
 static BRANCH_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\((\d+)/(\d+)\)").unwrap());
@@ -48,16 +56,31 @@ impl CoverageParser for CoberturaParser {
         super::looks_like_xml(&head) && head.contains("<coverage")
     }
 
-    fn parse(&self, input: &[u8]) -> Result<CoverageData> {
-        parse(input)
+    fn parse_streaming(
+        &self,
+        reader: &mut dyn BufRead,
+        emit: &mut dyn FnMut(FileCoverage) -> Result<()>,
+    ) -> Result<()> {
+        parse_streaming(reader, emit)
     }
 }
 
 /// Parse Cobertura XML coverage data from raw bytes.
 pub fn parse(input: &[u8]) -> Result<CoverageData> {
-    let mut reader = super::xml_reader(input);
-
     let mut data = CoverageData::new();
+    parse_streaming(&mut &*input, &mut |file| {
+        data.files.push(file);
+        Ok(())
+    })?;
+    Ok(data)
+}
+
+/// Streaming Cobertura parser — calls `emit` once per `</class>`.
+fn parse_streaming(
+    reader: &mut dyn BufRead,
+    emit: &mut dyn FnMut(FileCoverage) -> Result<()>,
+) -> Result<()> {
+    let mut reader = super::xml_reader(reader);
     let mut buf = Vec::new();
 
     // State tracking
@@ -69,6 +92,14 @@ pub fn parse(input: &[u8]) -> Result<CoverageData> {
     let mut branch_indices: HashMap<u32, u32> = HashMap::new();
     let mut line_index_map: HashMap<u32, usize> = HashMap::new();
 
+    // A branch-carrying `<line>` that's still open (has an End event
+    // coming) defers its arm synthesis there, so any nested <condition>
+    // children get a chance to be collected first — see the `b"line"` End
+    // handler below.
+    let mut current_line_number: Option<u32> = None;
+    let mut current_line_cond_cov: Option<String> = None;
+    let mut current_line_conditions: Vec<(u32, String)> = Vec::new();
+
     // Source prefix from <source> elements
     let mut sources: Vec<String> = Vec::new();
     let mut in_source = false;
@@ -174,35 +205,39 @@ pub fn parse(input: &[u8]) -> Result<CoverageData> {
                                 // Branch coverage — only process on first
                                 // encounter of this line to avoid double-counting
                                 // when the same line appears in both <method> and
-                                // <class> blocks.
+                                // <class> blocks. A self-closing <line/> (Empty)
+                                // can't carry nested <condition> children, so it's
+                                // always synthesized from condition-coverage right
+                                // away; an open <line> (Start) defers to its End
+                                // event so any <condition> children are seen first.
                                 if is_branch && !branch_indices.contains_key(&line_number) {
-                                    if let Some(cond) = cond_cov.as_deref() {
-                                        if let Some(caps) = branch_re.captures(cond) {
-                                            let covered: u32 = caps[1].parse().unwrap_or(0);
-                                            let total: u32 = caps[2].parse().unwrap_or(0);
-
-                                            for i in 0..total {
-                                                // Cobertura's condition-coverage only tells
-                                                // us how many branches were taken, not per-
-                                                // branch execution counts. Use 1 for covered
-                                                // arms and 0 for uncovered.
-                                                let branch_hit: u64 =
-                                                    if i < covered { 1 } else { 0 };
-                                                let idx =
-                                                    branch_indices.entry(line_number).or_insert(0);
-                                                file.branches.push(BranchCoverage {
-                                                    line_number,
-                                                    branch_index: *idx,
-                                                    hit_count: branch_hit,
-                                                });
-                                                *idx += 1;
-                                            }
-                                        }
+                                    if is_start_event {
+                                        current_line_number = Some(line_number);
+                                        current_line_cond_cov = cond_cov;
+                                        current_line_conditions.clear();
+                                    } else if let Some(cond) = cond_cov.as_deref() {
+                                        emit_flat_condition_branches(
+                                            file,
+                                            &mut branch_indices,
+                                            branch_re,
+                                            line_number,
+                                            cond,
+                                        );
                                     }
                                 }
                             }
                         }
                     }
+                    b"condition" => {
+                        if current_line_number.is_some() {
+                            let number = get_attr(e, b"number")
+                                .and_then(|v| v.parse().ok())
+                                .unwrap_or(current_line_conditions.len() as u32);
+                            let coverage =
+                                get_attr(e, b"coverage").unwrap_or_else(|| "0%".to_string());
+                            current_line_conditions.push((number, coverage));
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -218,9 +253,35 @@ pub fn parse(input: &[u8]) -> Result<CoverageData> {
                 b"source" => {
                     in_source = false;
                 }
+                b"line" => {
+                    if let Some(line_number) = current_line_number.take() {
+                        if let Some(file) = current_file.as_mut() {
+                            if current_line_conditions.is_empty() {
+                                if let Some(cond) = current_line_cond_cov.take() {
+                                    emit_flat_condition_branches(
+                                        file,
+                                        &mut branch_indices,
+                                        branch_re,
+                                        line_number,
+                                        &cond,
+                                    );
+                                }
+                            } else {
+                                emit_grouped_condition_branches(
+                                    file,
+                                    &mut branch_indices,
+                                    line_number,
+                                    std::mem::take(&mut current_line_conditions),
+                                );
+                            }
+                        }
+                        current_line_cond_cov = None;
+                    }
+                }
                 b"class" => {
-                    if let Some(file) = current_file.take() {
-                        data.files.push(file);
+                    if let Some(mut file) = current_file.take() {
+                        file.lines.sort_by_key(|l| l.line_number);
+                        emit(file)?;
                     }
                 }
                 b"method" => {
@@ -247,17 +308,225 @@ pub fn parse(input: &[u8]) -> Result<CoverageData> {
     }
 
     // Handle unclosed file
-    if let Some(file) = current_file.take() {
-        data.files.push(file);
+    if let Some(mut file) = current_file.take() {
+        file.lines.sort_by_key(|l| l.line_number);
+        emit(file)?;
     }
 
-    // Sort lines within each file by line number for consistent output,
-    // since lines may have been collected from both <method> and <class> blocks.
-    for file in &mut data.files {
-        file.lines.sort_by_key(|l| l.line_number);
+    Ok(())
+}
+
+/// Write the full contents of the database out as Cobertura XML.
+///
+/// Classes are grouped into packages by the directory portion of their
+/// path (matching how Cobertura itself splits `com/example/Foo.java` into
+/// package `com.example` and class `Foo`). Per-class and per-package
+/// `line-rate`/`branch-rate` attributes are computed the same way
+/// `db::get_file_summaries` aggregates coverage, so the exported XML is
+/// consistent with what `covrs summary`/`covrs files` report.
+pub fn export(conn: &Connection) -> Result<String> {
+    let data = crate::db::get_full_coverage(conn, crate::db::MergeMode::Union)?;
+    Ok(export_data(&data))
+}
+
+/// Render already-loaded coverage data as Cobertura XML (see [`export`]),
+/// with no `<sources>` prefix. Split out so callers with a specific
+/// [`CoverageData`] in hand — e.g. [`crate::cli::cmd_export`] exporting a
+/// single named report via [`crate::db::get_report_coverage`] — don't need
+/// a full-database union.
+pub fn export_data(data: &CoverageData) -> String {
+    export_data_with_sources(data, &[])
+}
+
+/// Render `data` as Cobertura XML, same as [`export_data`] but also
+/// emitting a `<sources>` block built from `sources` — the base path(s)
+/// Cobertura-aware tools prepend to each class's `filename` attribute
+/// when it's relative.
+pub fn export_data_with_sources(data: &CoverageData, sources: &[String]) -> String {
+    // Group files into packages by directory.
+    let mut packages: Vec<(String, Vec<&FileCoverage>)> = Vec::new();
+    for file in &data.files {
+        let package = Path::new(&file.path)
+            .parent()
+            .map(|p| p.to_string_lossy().replace(['/', '\\'], "."))
+            .unwrap_or_default();
+        match packages.iter_mut().find(|(name, _)| name == &package) {
+            Some((_, files)) => files.push(file),
+            None => packages.push((package, vec![file])),
+        }
     }
 
-    Ok(data)
+    let (total_lines, total_covered) = totals(&data.files);
+    let (total_branches, total_branch_hits) = branch_totals(&data.files);
+
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+    writer
+        .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))
+        .unwrap();
+    writer
+        .write_event(Event::DocType(BytesText::from_escaped(
+            "coverage SYSTEM \"http://cobertura.sourceforge.net/xml/coverage-04.dtd\"",
+        )))
+        .unwrap();
+
+    let mut coverage = BytesStart::new("coverage");
+    coverage.push_attribute(("line-rate", format!("{:.4}", rate(total_covered, total_lines)).as_str()));
+    coverage.push_attribute((
+        "branch-rate",
+        format!("{:.4}", rate(total_branch_hits, total_branches)).as_str(),
+    ));
+    coverage.push_attribute(("version", "1.0"));
+    coverage.push_attribute(("timestamp", unix_timestamp_millis().to_string().as_str()));
+    writer.write_event(Event::Start(coverage)).unwrap();
+
+    writer
+        .write_event(Event::Start(BytesStart::new("sources")))
+        .unwrap();
+    for source in sources {
+        write_text_element(&mut writer, "source", source);
+    }
+    writer
+        .write_event(Event::End(quick_xml::events::BytesEnd::new("sources")))
+        .unwrap();
+
+    writer
+        .write_event(Event::Start(BytesStart::new("packages")))
+        .unwrap();
+
+    for (package, files) in &packages {
+        let (pkg_lines, pkg_covered) = totals(files.iter().copied());
+        let (pkg_branches, pkg_branch_hits) = branch_totals(files.iter().copied());
+
+        let mut pkg_el = BytesStart::new("package");
+        pkg_el.push_attribute(("name", package.as_str()));
+        pkg_el.push_attribute((
+            "line-rate",
+            format!("{:.4}", rate(pkg_covered, pkg_lines)).as_str(),
+        ));
+        pkg_el.push_attribute((
+            "branch-rate",
+            format!("{:.4}", rate(pkg_branch_hits, pkg_branches)).as_str(),
+        ));
+        writer.write_event(Event::Start(pkg_el)).unwrap();
+        writer
+            .write_event(Event::Start(BytesStart::new("classes")))
+            .unwrap();
+
+        for file in files {
+            let (lines, covered) = totals(std::iter::once(*file));
+            let (branches, branch_hits) = branch_totals(std::iter::once(*file));
+            let class_name = Path::new(&file.path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| file.path.clone());
+
+            let mut class_el = BytesStart::new("class");
+            class_el.push_attribute(("name", class_name.as_str()));
+            class_el.push_attribute(("filename", file.path.as_str()));
+            class_el.push_attribute(("line-rate", format!("{:.4}", rate(covered, lines)).as_str()));
+            class_el.push_attribute((
+                "branch-rate",
+                format!("{:.4}", rate(branch_hits, branches)).as_str(),
+            ));
+            writer.write_event(Event::Start(class_el)).unwrap();
+            writer
+                .write_event(Event::Start(BytesStart::new("lines")))
+                .unwrap();
+
+            for line in &file.lines {
+                let arms: Vec<&BranchCoverage> = file
+                    .branches
+                    .iter()
+                    .filter(|b| b.line_number == line.line_number)
+                    .collect();
+
+                let mut line_el = BytesStart::new("line");
+                line_el.push_attribute(("number", line.line_number.to_string().as_str()));
+                line_el.push_attribute(("hits", line.hit_count.to_string().as_str()));
+                if arms.is_empty() {
+                    writer.write_event(Event::Empty(line_el)).unwrap();
+                    continue;
+                }
+                let covered_arms = arms.iter().filter(|b| b.hit_count > 0).count();
+                line_el.push_attribute(("branch", "true"));
+                line_el.push_attribute((
+                    "condition-coverage",
+                    format!(
+                        "{:.0}% ({covered_arms}/{})",
+                        rate(covered_arms as u64, arms.len() as u64) * 100.0,
+                        arms.len()
+                    )
+                    .as_str(),
+                ));
+                writer.write_event(Event::Empty(line_el)).unwrap();
+            }
+
+            writer
+                .write_event(Event::End(quick_xml::events::BytesEnd::new("lines")))
+                .unwrap();
+            writer
+                .write_event(Event::End(quick_xml::events::BytesEnd::new("class")))
+                .unwrap();
+        }
+
+        writer
+            .write_event(Event::End(quick_xml::events::BytesEnd::new("classes")))
+            .unwrap();
+        writer
+            .write_event(Event::End(quick_xml::events::BytesEnd::new("package")))
+            .unwrap();
+    }
+
+    writer
+        .write_event(Event::End(quick_xml::events::BytesEnd::new("packages")))
+        .unwrap();
+    writer
+        .write_event(Event::End(quick_xml::events::BytesEnd::new("coverage")))
+        .unwrap();
+
+    let bytes = writer.into_inner().into_inner();
+    String::from_utf8(bytes).expect("writer only emits text we built from &str/String")
+}
+
+fn write_text_element(writer: &mut Writer<Cursor<Vec<u8>>>, name: &str, text: &str) {
+    writer
+        .write_event(Event::Start(BytesStart::new(name)))
+        .unwrap();
+    writer
+        .write_event(Event::Text(BytesText::new(text)))
+        .unwrap();
+    writer
+        .write_event(Event::End(quick_xml::events::BytesEnd::new(name)))
+        .unwrap();
+}
+
+/// Milliseconds since the Unix epoch, for the root `<coverage timestamp>`
+/// attribute — matches what `cobertura`'s own reporters emit.
+fn unix_timestamp_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+fn totals<'a>(files: impl IntoIterator<Item = &'a FileCoverage>) -> (u64, u64) {
+    let mut total = 0u64;
+    let mut covered = 0u64;
+    for file in files {
+        total += file.lines.len() as u64;
+        covered += file.lines.iter().filter(|l| l.hit_count > 0).count() as u64;
+    }
+    (total, covered)
+}
+
+fn branch_totals<'a>(files: impl IntoIterator<Item = &'a FileCoverage>) -> (u64, u64) {
+    let mut total = 0u64;
+    let mut hits = 0u64;
+    for file in files {
+        total += file.branches.len() as u64;
+        hits += file.branches.iter().filter(|b| b.hit_count > 0).count() as u64;
+    }
+    (total, hits)
 }
 
 /// Resolve a filename against the list of `<source>` prefixes.
@@ -278,6 +547,86 @@ fn resolve_source_path(filename: &str, sources: &[String]) -> String {
     filename.to_string()
 }
 
+/// Synthesize branch arms for a `<line>` whose only branch signal is the
+/// summary `condition-coverage="50% (1/2)"` attribute — no nested
+/// `<condition>` children to group by (see [`emit_grouped_condition_branches`]
+/// for generators that do emit them). All arms are flattened into one
+/// ungrouped list since there's no way to tell which condition each one
+/// belongs to.
+fn emit_flat_condition_branches(
+    file: &mut FileCoverage,
+    branch_indices: &mut HashMap<u32, u32>,
+    branch_re: &Regex,
+    line_number: u32,
+    cond_cov: &str,
+) {
+    let Some(caps) = branch_re.captures(cond_cov) else {
+        return;
+    };
+    let covered: u32 = caps[1].parse().unwrap_or(0);
+    let total: u32 = caps[2].parse().unwrap_or(0);
+
+    for i in 0..total {
+        // Cobertura's condition-coverage only tells us how many branches
+        // were taken, not per-branch execution counts. Use 1 for covered
+        // arms and 0 for uncovered.
+        let branch_hit: u64 = if i < covered { 1 } else { 0 };
+        let idx = branch_indices.entry(line_number).or_insert(0);
+        file.branches.push(BranchCoverage {
+            line_number,
+            branch_index: *idx,
+            hit_count: branch_hit,
+            group_id: None,
+            kind: BranchKind::Unknown,
+            arm_line: None,
+        });
+        *idx += 1;
+    }
+}
+
+/// Synthesize branch arms for a `<line>` that enumerates its individual
+/// MC/DC `<condition number="N" coverage="P%"/>` children — each condition
+/// becomes its own [`Decision`] (`group_id` = the condition's `number`)
+/// with a true/false arm pair, rather than flattening every condition's
+/// arms into one undifferentiated list. This is the signal generators like
+/// gcovr emit for compound boolean decisions (e.g. `if (a && b)`), and lets
+/// downstream reporters tell "both outcomes of the decision were taken"
+/// apart from "each individual condition was independently exercised both
+/// ways".
+///
+/// Each `<condition>` is itself a binary decision, so its `coverage`
+/// percentage can only be `0%`, `50%`, or `100%`.
+fn emit_grouped_condition_branches(
+    file: &mut FileCoverage,
+    branch_indices: &mut HashMap<u32, u32>,
+    line_number: u32,
+    conditions: Vec<(u32, String)>,
+) {
+    let idx = branch_indices.entry(line_number).or_insert(0);
+    for (condition_number, coverage_pct) in conditions {
+        let pct: u32 = coverage_pct.trim_end_matches('%').parse().unwrap_or(0);
+        let covered_arms = if pct >= 100 {
+            2
+        } else if pct >= 50 {
+            1
+        } else {
+            0
+        };
+        for arm in 0..2u32 {
+            let hit_count: u64 = if arm < covered_arms { 1 } else { 0 };
+            file.branches.push(BranchCoverage {
+                line_number,
+                branch_index: *idx,
+                hit_count,
+                group_id: Some(condition_number),
+                kind: BranchKind::If,
+                arm_line: None,
+            });
+            *idx += 1;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -338,6 +687,54 @@ mod tests {
         assert_eq!(file.branches[1].hit_count, 0); // uncovered arm
     }
 
+    #[test]
+    fn test_parse_cobertura_grouped_conditions() {
+        // A line with nested <conditions> (compound `if (a && b)`) should
+        // produce one decision group per condition, not one flattened list.
+        let input = br#"<?xml version="1.0"?>
+<coverage version="1.0">
+  <packages>
+    <package name="src">
+      <classes>
+        <class name="lib" filename="lib.rs">
+          <lines>
+            <line number="3" hits="5" branch="true" condition-coverage="75% (3/4)">
+              <conditions>
+                <condition number="0" type="jump" coverage="100%"/>
+                <condition number="1" type="jump" coverage="50%"/>
+              </conditions>
+            </line>
+          </lines>
+        </class>
+      </classes>
+    </package>
+  </packages>
+</coverage>
+"#;
+        let data = parse(input).unwrap();
+
+        assert_eq!(data.files.len(), 1);
+        let file = &data.files[0];
+        assert_eq!(file.branches.len(), 4);
+
+        let condition_0: Vec<_> = file
+            .branches
+            .iter()
+            .filter(|b| b.group_id == Some(0))
+            .collect();
+        assert_eq!(condition_0.len(), 2);
+        assert!(condition_0.iter().all(|b| b.hit_count == 1));
+        assert!(condition_0.iter().all(|b| b.kind == BranchKind::If));
+
+        let condition_1: Vec<_> = file
+            .branches
+            .iter()
+            .filter(|b| b.group_id == Some(1))
+            .collect();
+        assert_eq!(condition_1.len(), 2);
+        assert_eq!(condition_1.iter().filter(|b| b.hit_count == 1).count(), 1);
+    }
+
     #[test]
     fn test_parse_cobertura_multiple_sources() {
         // First <source> is empty, second is the real prefix.
@@ -378,4 +775,90 @@ mod tests {
             "Error should contain position info: {err_msg}",
         );
     }
+
+    #[test]
+    fn test_export_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_schema(&conn).unwrap();
+
+        let mut data = CoverageData::new();
+        let mut file = FileCoverage::new("src/lib.rs".to_string());
+        file.lines.push(LineCoverage {
+            line_number: 1,
+            hit_count: 3,
+        });
+        file.lines.push(LineCoverage {
+            line_number: 2,
+            hit_count: 0,
+        });
+        file.branches.push(BranchCoverage {
+            line_number: 1,
+            branch_index: 0,
+            hit_count: 1,
+            group_id: None,
+            kind: BranchKind::Unknown,
+            arm_line: None,
+        });
+        data.files.push(file);
+
+        let mut conn = conn;
+        crate::db::insert_coverage(&mut conn, "r1", "lcov", None, &data, false).unwrap();
+
+        let xml = export(&conn).unwrap();
+        assert!(xml.contains("<coverage"));
+        assert!(xml.contains("filename=\"src/lib.rs\""));
+        assert!(xml.contains("number=\"1\" hits=\"3\""));
+        assert!(xml.contains("number=\"2\" hits=\"0\""));
+    }
+
+    #[test]
+    fn test_export_data_emits_doctype_and_timestamp() {
+        let data = CoverageData::new();
+        let xml = export_data(&data);
+
+        assert!(xml.contains("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.contains("<!DOCTYPE coverage SYSTEM \"http://cobertura.sourceforge.net/xml/coverage-04.dtd\">"));
+        assert!(xml.contains("timestamp=\""));
+    }
+
+    #[test]
+    fn test_export_data_with_sources_emits_sources_block() {
+        let data = CoverageData::new();
+        let sources = vec!["/home/user/project".to_string()];
+        let xml = export_data_with_sources(&data, &sources);
+
+        assert!(xml.contains("<sources>"));
+        assert!(xml.contains("<source>/home/user/project</source>"));
+    }
+
+    #[test]
+    fn test_export_data_reconstructs_condition_coverage() {
+        let mut data = CoverageData::new();
+        let mut file = FileCoverage::new("src/lib.rs".to_string());
+        file.lines.push(LineCoverage {
+            line_number: 8,
+            hit_count: 1,
+        });
+        file.branches.push(BranchCoverage {
+            line_number: 8,
+            branch_index: 0,
+            hit_count: 1,
+            group_id: None,
+            kind: BranchKind::Unknown,
+            arm_line: None,
+        });
+        file.branches.push(BranchCoverage {
+            line_number: 8,
+            branch_index: 1,
+            hit_count: 0,
+            group_id: None,
+            kind: BranchKind::Unknown,
+            arm_line: None,
+        });
+        data.files.push(file);
+
+        let xml = export_data(&data);
+        assert!(xml.contains(r#"branch="true""#));
+        assert!(xml.contains(r#"condition-coverage="50% (1/2)""#));
+    }
 }
@@ -0,0 +1,285 @@
+/// Parser for Codecov's internal "pyreport" report format.
+///
+/// Reference: https://docs.codecov.com/docs/codecov-custom-coverage-format
+///
+/// Unlike every other parser in this module, a pyreport is split across
+/// two files that must be read together, so it doesn't fit the
+/// single-`(path, content)` [`super::CoverageParser`] contract — there's
+/// no `CodecovParser` registered in [`super::all`]/[`super::detect`]; call
+/// [`parse`] directly once both files are in hand.
+///
+///   - `report_json`: `{ "files": { "src/foo.py": [<chunk index>, ...], ... }, "sessions": {...} }`
+///     — maps each file path to the ordinal of its chunk in the chunks file.
+///   - `chunks`: line-oriented. A header JSON line, then a
+///     `<<<<< end_of_header >>>>>` separator, then one chunk per file
+///     (in `report_json`'s index order) separated by
+///     `<<<<< end_of_chunk >>>>>`. Each chunk starts with its own header
+///     JSON line, followed by one record per source line (1-based, in
+///     order): either `null` (not instrumented) or `[coverage, type, sessions, ...]`
+///     where `coverage` is an integer hit count, a `"hits/total"` string
+///     (partial branch coverage), or a bool.
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::model::*;
+
+const END_OF_HEADER: &str = "<<<<< end_of_header >>>>>";
+const END_OF_CHUNK: &str = "<<<<< end_of_chunk >>>>>";
+
+#[derive(serde::Deserialize)]
+struct ReportJson {
+    files: HashMap<String, Value>,
+}
+
+/// Parse a pyreport's `report_json` + `chunks` files into `CoverageData`.
+pub fn parse(report_json: &[u8], chunks: &[u8]) -> Result<CoverageData> {
+    let mut data = CoverageData::new();
+    parse_streaming(report_json, &mut &*chunks, &mut |file| {
+        data.files.push(file);
+        Ok(())
+    })?;
+    Ok(data)
+}
+
+/// Streaming variant: `report_json` is small and fully parsed up front
+/// (it's just the path→chunk-index map), but `chunks` — which holds the
+/// actual per-line records and can be large — is scanned one line at a
+/// time, calling `emit` once per completed chunk.
+pub fn parse_streaming(
+    report_json: &[u8],
+    chunks: &mut dyn BufRead,
+    emit: &mut dyn FnMut(FileCoverage) -> Result<()>,
+) -> Result<()> {
+    let report: ReportJson =
+        serde_json::from_slice(report_json).context("Invalid report_json in Codecov pyreport")?;
+
+    let mut path_by_index: HashMap<u64, String> = HashMap::new();
+    for (path, entry) in report.files {
+        let index = chunk_index(&entry);
+        path_by_index.insert(index, path);
+    }
+
+    let mut raw_line = String::new();
+
+    // Report-level header line, then the end-of-header separator.
+    raw_line.clear();
+    if chunks
+        .read_line(&mut raw_line)
+        .context("Invalid UTF-8 in Codecov chunks data")?
+        == 0
+    {
+        return Ok(()); // empty input
+    }
+    raw_line.clear();
+    match chunks
+        .read_line(&mut raw_line)
+        .context("Invalid UTF-8 in Codecov chunks data")?
+    {
+        0 => return Ok(()),
+        _ if raw_line.trim() == END_OF_HEADER => {}
+        _ => anyhow::bail!("Expected `{END_OF_HEADER}` after chunks header"),
+    }
+
+    let mut chunk_ordinal: u64 = 0;
+    let mut current: Option<FileCoverage> = None;
+    let mut line_number: u32 = 0;
+    let mut first_line_of_chunk = true;
+
+    loop {
+        raw_line.clear();
+        if chunks
+            .read_line(&mut raw_line)
+            .context("Invalid UTF-8 in Codecov chunks data")?
+            == 0
+        {
+            break; // EOF
+        }
+        let line = raw_line.trim();
+
+        if first_line_of_chunk {
+            // The chunk's own header JSON line — no fields we need.
+            first_line_of_chunk = false;
+            let path = path_by_index
+                .get(&chunk_ordinal)
+                .cloned()
+                .unwrap_or_else(|| format!("<unknown chunk {chunk_ordinal}>"));
+            current = Some(FileCoverage::new(path));
+            line_number = 0;
+            continue;
+        }
+
+        if line == END_OF_CHUNK {
+            if let Some(file) = current.take() {
+                emit(file)?;
+            }
+            chunk_ordinal += 1;
+            first_line_of_chunk = true;
+            continue;
+        }
+
+        line_number += 1;
+        if let Some(file) = current.as_mut() {
+            parse_record(line, line_number, file)?;
+        }
+    }
+
+    if let Some(file) = current.take() {
+        emit(file)?;
+    }
+
+    Ok(())
+}
+
+/// A `report_json` file entry is either a bare chunk index or an array
+/// whose first element is the index (the rest is per-file totals we
+/// don't need).
+fn chunk_index(entry: &Value) -> u64 {
+    match entry {
+        Value::Number(n) => n.as_u64().unwrap_or(0),
+        Value::Array(arr) => arr.first().and_then(|v| v.as_u64()).unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Parse one `[coverage, type, sessions, ...]` / `null` record line and
+/// fold it into `file` at `line_number`.
+fn parse_record(line: &str, line_number: u32, file: &mut FileCoverage) -> Result<()> {
+    let line = line.trim();
+    if line.is_empty() || line == "null" {
+        return Ok(());
+    }
+
+    let record: Value =
+        serde_json::from_str(line).with_context(|| format!("Invalid chunk record: {line}"))?;
+    let Value::Array(fields) = record else {
+        return Ok(());
+    };
+    let Some(coverage) = fields.first() else {
+        return Ok(());
+    };
+
+    match coverage {
+        Value::Number(n) => {
+            file.lines.push(LineCoverage {
+                line_number,
+                hit_count: n.as_u64().unwrap_or(0),
+            });
+        }
+        Value::Bool(b) => {
+            file.lines.push(LineCoverage {
+                line_number,
+                hit_count: if *b { 1 } else { 0 },
+            });
+        }
+        Value::String(s) => {
+            let Some((hits, total)) = s.split_once('/') else {
+                return Ok(());
+            };
+            let hits: u32 = hits.parse().unwrap_or(0);
+            let total: u32 = total.parse().unwrap_or(0);
+
+            file.lines.push(LineCoverage {
+                line_number,
+                hit_count: hits as u64,
+            });
+
+            for i in 0..total {
+                file.branches.push(BranchCoverage {
+                    line_number,
+                    branch_index: i,
+                    hit_count: if i < hits { 1 } else { 0 },
+                    group_id: None,
+                    kind: BranchKind::Unknown,
+                    arm_line: None,
+                });
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_pyreport() {
+        let report_json = br#"{"files": {"src/foo.py": [0], "src/bar.py": [1]}}"#;
+        let chunks = "{}\n\
+<<<<< end_of_header >>>>>\n\
+{}\n\
+[1]\n\
+[0]\n\
+null\n\
+<<<<< end_of_chunk >>>>>\n\
+{}\n\
+[1]\n\
+<<<<< end_of_chunk >>>>>\n";
+
+        let data = parse(report_json, chunks.as_bytes()).unwrap();
+
+        assert_eq!(data.files.len(), 2);
+        let foo = data.files.iter().find(|f| f.path == "src/foo.py").unwrap();
+        assert_eq!(foo.lines.len(), 2);
+        assert_eq!(foo.lines[0].line_number, 1);
+        assert_eq!(foo.lines[0].hit_count, 1);
+        assert_eq!(foo.lines[1].line_number, 2);
+        assert_eq!(foo.lines[1].hit_count, 0);
+
+        let bar = data.files.iter().find(|f| f.path == "src/bar.py").unwrap();
+        assert_eq!(bar.lines.len(), 1);
+        assert_eq!(bar.lines[0].hit_count, 1);
+    }
+
+    #[test]
+    fn test_parse_partial_branch_coverage() {
+        let report_json = br#"{"files": {"src/foo.py": [0]}}"#;
+        let chunks = "{}\n\
+<<<<< end_of_header >>>>>\n\
+{}\n\
+[\"1/2\", \"b\"]\n\
+<<<<< end_of_chunk >>>>>\n";
+
+        let data = parse(report_json, chunks.as_bytes()).unwrap();
+
+        assert_eq!(data.files.len(), 1);
+        let foo = &data.files[0];
+        assert_eq!(foo.lines.len(), 1);
+        assert_eq!(foo.lines[0].hit_count, 1);
+
+        assert_eq!(foo.branches.len(), 2);
+        assert_eq!(foo.branches[0].hit_count, 1);
+        assert_eq!(foo.branches[1].hit_count, 0);
+    }
+
+    #[test]
+    fn test_parse_bool_coverage() {
+        let report_json = br#"{"files": {"src/foo.py": [0]}}"#;
+        let chunks = "{}\n\
+<<<<< end_of_header >>>>>\n\
+{}\n\
+[true]\n\
+[false]\n\
+<<<<< end_of_chunk >>>>>\n";
+
+        let data = parse(report_json, chunks.as_bytes()).unwrap();
+
+        let foo = &data.files[0];
+        assert_eq!(foo.lines[0].hit_count, 1);
+        assert_eq!(foo.lines[1].hit_count, 0);
+    }
+
+    #[test]
+    fn test_parse_empty_chunks() {
+        let report_json = br#"{"files": {}}"#;
+        let chunks = "{}\n<<<<< end_of_header >>>>>\n";
+
+        let data = parse(report_json, chunks.as_bytes()).unwrap();
+        assert_eq!(data.files.len(), 0);
+    }
+}
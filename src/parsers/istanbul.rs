@@ -206,6 +206,13 @@ fn parse_statements(entry: &Value, file: &mut FileCoverage) {
 ///
 /// `branchMap` maps string indices to `{ type, locations: [{ start: { line } }, ...] }`.
 /// `b` maps the same indices to arrays of hit counts (one per branch arm).
+///
+/// Each `branchMap` entry is one decision, so its index becomes the arms'
+/// shared `group_id`, `type` is translated into a [`BranchKind`], and each
+/// arm's own `locations[i].start.line` is kept as `arm_line` when it differs
+/// from the decision's `line_number`; an arm on the decision's own line
+/// (e.g. an `if`'s implicit `else` arm, attributed to the `if` line itself)
+/// gets `None`.
 fn parse_branches(entry: &Value, file: &mut FileCoverage) {
     let branch_map = match entry.get("branchMap").and_then(|v| v.as_object()) {
         Some(m) => m,
@@ -247,19 +254,58 @@ fn parse_branches(entry: &Value, file: &mut FileCoverage) {
             None => continue,
         };
 
-        for count_val in counts {
+        let kind = branch_info
+            .get("type")
+            .and_then(|t| t.as_str())
+            .map(branch_kind_from_istanbul_type)
+            .unwrap_or(BranchKind::Unknown);
+
+        let arm_lines: Vec<Option<u32>> = branch_info
+            .get("locations")
+            .and_then(|locs| locs.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .map(|loc| {
+                        loc.get("start")
+                            .and_then(|s| s.get("line"))
+                            .and_then(|l| l.as_u64())
+                            .map(|l| l as u32)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let group_id = idx.parse::<u32>().ok();
+
+        for (arm, count_val) in counts.iter().enumerate() {
             let hit_count = count_val.as_u64().unwrap_or(0);
             let branch_index = line_branch_idx.entry(line).or_insert(0);
+            let arm_line = arm_lines.get(arm).copied().flatten().filter(|l| *l != line);
             file.branches.push(BranchCoverage {
                 line_number: line,
                 branch_index: *branch_index,
                 hit_count,
+                group_id,
+                kind,
+                arm_line,
             });
             *branch_index += 1;
         }
     }
 }
 
+/// Translate an Istanbul `branchMap[n].type` string into a [`BranchKind`].
+fn branch_kind_from_istanbul_type(type_str: &str) -> BranchKind {
+    match type_str {
+        "if" => BranchKind::If,
+        "switch" => BranchKind::Switch,
+        "cond-expr" => BranchKind::Ternary,
+        // "binary-expr" (short-circuit `&&`/`||` operands) has no
+        // corresponding variant yet.
+        _ => BranchKind::Unknown,
+    }
+}
+
 /// Extract function coverage from `fnMap` + `f`.
 ///
 /// `fnMap` maps string indices to `{ name, decl: { start: { line } }, loc: { start: { line }, end: { line } } }`.
@@ -385,6 +431,62 @@ mod tests {
         assert_eq!(data.files[0].lines[0].hit_count, 7); // max(3, 7)
     }
 
+    #[test]
+    fn test_parse_branches_preserves_type_and_arm_locations() {
+        // An `if` on line 2 whose `else` arm is implicit (attributed back
+        // to the `if` line), alongside an n-way `switch` on line 10 whose
+        // arms each start on their own line.
+        let input = r#"{
+            "/src/app.js": {
+                "statementMap": {},
+                "s": {},
+                "fnMap": {},
+                "f": {},
+                "branchMap": {
+                    "0": {
+                        "type": "if",
+                        "loc": { "start": { "line": 2 } },
+                        "locations": [
+                            { "start": { "line": 2 } },
+                            { "start": { "line": 2 } }
+                        ]
+                    },
+                    "1": {
+                        "type": "switch",
+                        "loc": { "start": { "line": 10 } },
+                        "locations": [
+                            { "start": { "line": 11 } },
+                            { "start": { "line": 12 } }
+                        ]
+                    }
+                },
+                "b": { "0": [3, 0], "1": [1, 0] }
+            }
+        }"#;
+        let data = parse(input.as_bytes()).unwrap();
+        let file = &data.files[0];
+
+        let if_arms: Vec<_> = file
+            .branches
+            .iter()
+            .filter(|b| b.group_id == Some(0))
+            .collect();
+        assert_eq!(if_arms.len(), 2);
+        assert_eq!(if_arms[0].kind, BranchKind::If);
+        // Both arms sit on the `if` line itself, so arm_line is None.
+        assert!(if_arms.iter().all(|b| b.arm_line.is_none()));
+
+        let switch_arms: Vec<_> = file
+            .branches
+            .iter()
+            .filter(|b| b.group_id == Some(1))
+            .collect();
+        assert_eq!(switch_arms.len(), 2);
+        assert_eq!(switch_arms[0].kind, BranchKind::Switch);
+        assert_eq!(switch_arms[0].arm_line, Some(11));
+        assert_eq!(switch_arms[1].arm_line, Some(12));
+    }
+
     #[test]
     fn test_looks_like_istanbul() {
         assert!(looks_like_istanbul(
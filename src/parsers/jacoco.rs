@@ -57,7 +57,10 @@ impl CoverageParser for JacocoParser {
         // specific child elements (sessioninfo, package, etc.)
         super::looks_like_xml(&head)
             && head.contains("<report")
-            && (head.contains("jacoco") || head.contains("JACOCO") || head.contains("<package"))
+            && (head.contains("jacoco")
+                || head.contains("JACOCO")
+                || head.contains("<package")
+                || head.contains("<sourcefile"))
     }
 
     fn parse_streaming(
@@ -69,20 +72,51 @@ impl CoverageParser for JacocoParser {
     }
 }
 
-/// Parse JaCoCo XML coverage data from raw bytes.
+/// Parse JaCoCo XML coverage data from raw bytes, including `<sessioninfo>`
+/// and report-level `<counter>` metadata that the generic [`CoverageParser`]
+/// streaming interface has no channel for (see [`parse_streaming_with_metadata`]).
 pub fn parse(input: &[u8]) -> Result<CoverageData> {
     let mut data = CoverageData::new();
-    parse_streaming(&mut &*input, &mut |file| {
-        data.files.push(file);
-        Ok(())
-    })?;
+    let mut sessions = Vec::new();
+    let mut summary = None;
+    parse_streaming_with_metadata(
+        &mut &*input,
+        &mut |file| {
+            data.files.push(file);
+            Ok(())
+        },
+        &mut sessions,
+        &mut summary,
+    )?;
+    data.sessions = sessions;
+    data.summary = summary;
     Ok(data)
 }
 
-/// Streaming JaCoCo parser — calls `emit` once per `</sourcefile>`.
+/// Streaming JaCoCo parser — calls `emit` once per `</sourcefile>`. This is
+/// the entry point the [`CoverageParser`] trait is constrained to (`emit`
+/// only carries per-file coverage), so session/summary metadata collected
+/// by [`parse_streaming_with_metadata`] is discarded here; use [`parse`]
+/// directly to get at it.
 fn parse_streaming(
     reader: &mut dyn BufRead,
     emit: &mut dyn FnMut(FileCoverage) -> Result<()>,
+) -> Result<()> {
+    let mut sessions = Vec::new();
+    let mut summary = None;
+    parse_streaming_with_metadata(reader, emit, &mut sessions, &mut summary)
+}
+
+/// Streaming JaCoCo parser that also collects `<sessioninfo>` entries and
+/// the report-level `<counter>` totals (i.e. counters outside any
+/// `<package>`/`<class>`/`<sourcefile>`/`<method>`), which JaCoCo emits as
+/// its own authoritative summary rather than something derived from
+/// `<line>` rows.
+fn parse_streaming_with_metadata(
+    reader: &mut dyn BufRead,
+    emit: &mut dyn FnMut(FileCoverage) -> Result<()>,
+    sessions: &mut Vec<SessionInfo>,
+    summary: &mut Option<CoverageSummary>,
 ) -> Result<()> {
     let mut xml = super::xml_reader(reader);
     let mut buf = Vec::new();
@@ -110,6 +144,58 @@ fn parse_streaming(
             Ok(Event::Eof) => break,
             Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
                 match e.name().as_ref() {
+                    b"sessioninfo" => {
+                        if let Some(id) = get_attr(e, b"id") {
+                            let start = get_attr(e, b"start")
+                                .and_then(|v| v.parse().ok())
+                                .unwrap_or(0);
+                            let dump = get_attr(e, b"dump")
+                                .and_then(|v| v.parse().ok())
+                                .unwrap_or(0);
+                            sessions.push(SessionInfo { id, start, dump });
+                        }
+                    }
+                    b"counter"
+                        if !in_method
+                            && current_package.is_none()
+                            && current_class_source.is_none()
+                            && current_sourcefile.is_none() =>
+                    {
+                        // A report-level counter — JaCoCo's own declared
+                        // total for this counter type, not derived from
+                        // <line> rows.
+                        if let Some(counter_type) = get_attr(e, b"type") {
+                            let missed: u64 =
+                                get_attr(e, b"missed").and_then(|v| v.parse().ok()).unwrap_or(0);
+                            let covered: u64 = get_attr(e, b"covered")
+                                .and_then(|v| v.parse().ok())
+                                .unwrap_or(0);
+                            let summary = summary.get_or_insert_with(CoverageSummary::default);
+                            match counter_type.as_str() {
+                                "INSTRUCTION" => {
+                                    summary.instructions_missed = missed;
+                                    summary.instructions_covered = covered;
+                                }
+                                "BRANCH" => {
+                                    summary.branches_missed = missed;
+                                    summary.branches_covered = covered;
+                                }
+                                "LINE" => {
+                                    summary.lines_missed = missed;
+                                    summary.lines_covered = covered;
+                                }
+                                "METHOD" => {
+                                    summary.methods_missed = missed;
+                                    summary.methods_covered = covered;
+                                }
+                                "COMPLEXITY" => {
+                                    summary.complexity_missed = missed;
+                                    summary.complexity_covered = covered;
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
                     b"package" => {
                         current_package = get_attr(e, b"name");
                     }
@@ -217,9 +303,19 @@ fn parse_streaming(
                                     });
                                 }
 
-                                // Branch coverage
+                                // Branch coverage. JaCoCo doesn't expose the
+                                // decision's actual shape, so classify it
+                                // heuristically from the arm count: a
+                                // binary decision (2 arms) is almost always
+                                // an `if`, anything wider looks like a
+                                // `switch`.
                                 let total_branches = cb + mb;
                                 if total_branches > 0 {
+                                    let kind = match total_branches {
+                                        2 => BranchKind::If,
+                                        n if n > 2 => BranchKind::Switch,
+                                        _ => BranchKind::Unknown,
+                                    };
                                     let idx = branch_indices.entry(line_number).or_insert(0);
                                     for i in 0..total_branches {
                                         let branch_hit: u64 = if i < cb { 1 } else { 0 };
@@ -227,6 +323,9 @@ fn parse_streaming(
                                             line_number,
                                             branch_index: *idx,
                                             hit_count: branch_hit,
+                                            group_id: None,
+                                            kind,
+                                            arm_line: None,
                                         });
                                         *idx += 1;
                                     }
@@ -336,6 +435,52 @@ mod tests {
         assert_eq!(bar.branches.len(), 0);
     }
 
+    #[test]
+    fn test_parse_jacoco_captures_sessions_and_report_summary() {
+        let input = br#"<?xml version="1.0"?>
+<report name="test">
+  <sessioninfo id="run-1" start="1000" dump="2000"/>
+  <sessioninfo id="run-2" start="3000" dump="4000"/>
+  <package name="com/example">
+    <class name="com/example/Foo" sourcefilename="Foo.java">
+      <counter type="INSTRUCTION" missed="2" covered="10"/>
+    </class>
+    <sourcefile name="Foo.java">
+      <line nr="10" mi="0" ci="3" mb="0" cb="0"/>
+      <counter type="LINE" missed="0" covered="1"/>
+    </sourcefile>
+    <counter type="INSTRUCTION" missed="2" covered="10"/>
+  </package>
+  <counter type="INSTRUCTION" missed="2" covered="10"/>
+  <counter type="BRANCH" missed="1" covered="3"/>
+  <counter type="LINE" missed="1" covered="5"/>
+  <counter type="METHOD" missed="0" covered="2"/>
+  <counter type="COMPLEXITY" missed="1" covered="4"/>
+</report>"#;
+        let data = parse(input).unwrap();
+
+        assert_eq!(data.sessions.len(), 2);
+        assert_eq!(data.sessions[0].id, "run-1");
+        assert_eq!(data.sessions[0].start, 1000);
+        assert_eq!(data.sessions[0].dump, 2000);
+        assert_eq!(data.sessions[1].id, "run-2");
+
+        // Only the report-scoped counters (outside <package>/<class>/
+        // <sourcefile>) populate the summary — the class- and
+        // package-level ones above are ignored.
+        let summary = data.summary.unwrap();
+        assert_eq!(summary.instructions_missed, 2);
+        assert_eq!(summary.instructions_covered, 10);
+        assert_eq!(summary.branches_missed, 1);
+        assert_eq!(summary.branches_covered, 3);
+        assert_eq!(summary.lines_missed, 1);
+        assert_eq!(summary.lines_covered, 5);
+        assert_eq!(summary.methods_missed, 0);
+        assert_eq!(summary.methods_covered, 2);
+        assert_eq!(summary.complexity_missed, 1);
+        assert_eq!(summary.complexity_covered, 4);
+    }
+
     #[test]
     fn test_parse_jacoco_no_package() {
         let input = include_bytes!("../../tests/fixtures/jacoco_no_package.xml");
@@ -366,6 +511,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_jacoco_nested_package_path() {
+        let input = br#"<?xml version="1.0"?>
+<report name="test">
+  <package name="com/example/sub">
+    <sourcefile name="Foo.java">
+      <line nr="1" mi="0" ci="1" mb="0" cb="0"/>
+    </sourcefile>
+  </package>
+</report>"#;
+        let data = parse(input).unwrap();
+
+        assert_eq!(data.files.len(), 1);
+        assert_eq!(data.files[0].path, "com/example/sub/Foo.java");
+    }
+
+    #[test]
+    fn test_can_parse_jacoco_no_package_no_doctype() {
+        let parser = JacocoParser;
+
+        // A <report> with a bare <sourcefile> and no <package>, DOCTYPE, or
+        // "jacoco" marker should still be recognized — this is the shape a
+        // default-package report takes (see test_parse_jacoco_no_package).
+        let content =
+            br#"<?xml version="1.0"?><report name="test"><sourcefile name="App.java">"#;
+        assert!(parser.can_parse(Path::new("report.xml"), content));
+    }
+
     #[test]
     fn test_can_parse_jacoco() {
         let parser = JacocoParser;
@@ -21,6 +21,7 @@ use std::io::BufRead;
 use std::path::Path;
 
 use anyhow::{Context, Result};
+use rusqlite::Connection;
 
 use super::{CoverageParser, Format};
 use crate::model::*;
@@ -175,11 +176,16 @@ fn parse_streaming_reader(
             }
             "BRDA" => {
                 // BRDA:<line>,<block>,<branch>,<taken>
-                // <taken> can be "-" meaning 0.
+                // <taken> can be "-" meaning 0. <block> groups the branch
+                // arms belonging to one decision (e.g. the conditions of a
+                // chained `&&`/`||`, or the two outcomes of a plain `if`) —
+                // we carry it through as `group_id` for MC/DC-style
+                // reporting in `db::diff_branch_coverage`.
                 if let Some(file) = current_file.as_mut() {
                     let parts: Vec<&str> = value.splitn(4, ',').collect();
                     if parts.len() == 4 {
                         if let Ok(line_number) = parts[0].parse::<u32>() {
+                            let group_id = parts[1].parse::<u32>().ok();
                             let hit_count = if parts[3] == "-" {
                                 0
                             } else {
@@ -190,6 +196,9 @@ fn parse_streaming_reader(
                                 line_number,
                                 branch_index: *idx,
                                 hit_count,
+                                group_id,
+                                kind: BranchKind::Unknown,
+                                arm_line: None,
                             });
                             *idx += 1;
                         }
@@ -209,6 +218,70 @@ fn parse_streaming_reader(
     Ok(())
 }
 
+/// Write the full contents of the database out as LCOV `.info` data.
+///
+/// Per file: `SF:<path>`, one `FN:<start_line>,<name>` plus
+/// `FNDA:<hit_count>,<name>` per function followed by `FNF`/`FNH` totals,
+/// one `BRDA:<line>,0,<branch_index>,<hit_count or ->` per branch arm
+/// followed by `BRF`/`BRH`, one `DA:<line>,<hit_count>` per line followed
+/// by `LF`/`LH`, and a terminating `end_of_record`. The `<block>` field of
+/// `BRDA` is always emitted as `0`, so an arm's `group_id` is not carried
+/// through an LCOV export (the LCOV parser above reads `<block>` back as
+/// `group_id`).
+pub fn export(conn: &Connection) -> Result<String> {
+    let data = crate::db::get_full_coverage(conn, crate::db::MergeMode::Union)?;
+    Ok(export_data(&data))
+}
+
+/// Render already-loaded coverage data as LCOV text (see [`export`]). Split
+/// out so callers with a specific [`CoverageData`] in hand — e.g.
+/// [`crate::cli::cmd_export`] exporting a single named report via
+/// [`crate::db::get_report_coverage`] — don't need a full-database union.
+pub fn export_data(data: &CoverageData) -> String {
+    let mut out = String::new();
+    for file in &data.files {
+        out.push_str(&format!("SF:{}\n", file.path));
+
+        for func in &file.functions {
+            if let Some(start_line) = func.start_line {
+                out.push_str(&format!("FN:{start_line},{}\n", func.name));
+            }
+        }
+        for func in &file.functions {
+            out.push_str(&format!("FNDA:{},{}\n", func.hit_count, func.name));
+        }
+        let fnh = file.functions.iter().filter(|f| f.hit_count > 0).count();
+        out.push_str(&format!("FNF:{}\n", file.functions.len()));
+        out.push_str(&format!("FNH:{fnh}\n"));
+
+        for branch in &file.branches {
+            let taken = if branch.hit_count > 0 {
+                branch.hit_count.to_string()
+            } else {
+                "-".to_string()
+            };
+            out.push_str(&format!(
+                "BRDA:{},0,{},{}\n",
+                branch.line_number, branch.branch_index, taken
+            ));
+        }
+        let brh = file.branches.iter().filter(|b| b.hit_count > 0).count();
+        out.push_str(&format!("BRF:{}\n", file.branches.len()));
+        out.push_str(&format!("BRH:{brh}\n"));
+
+        for line in &file.lines {
+            out.push_str(&format!("DA:{},{}\n", line.line_number, line.hit_count));
+        }
+        let lh = file.lines.iter().filter(|l| l.hit_count > 0).count();
+        out.push_str(&format!("LF:{}\n", file.lines.len()));
+        out.push_str(&format!("LH:{lh}\n"));
+
+        out.push_str("end_of_record\n");
+    }
+
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,6 +349,72 @@ mod tests {
         assert_eq!(file.lines[2].hit_count, 3);
     }
 
+    #[test]
+    fn test_parse_lcov_brda_group_id() {
+        // BRDA's <block> field groups the arms of one decision (e.g. the
+        // two outcomes of a chained `&&`). Two BRDA lines sharing a block
+        // should come out with the same `group_id`; a different block is a
+        // different decision.
+        let input = b"SF:/src/lib.rs\n\
+BRDA:10,0,0,5\n\
+BRDA:10,0,1,-\n\
+BRDA:10,1,0,3\n\
+end_of_record\n";
+        let data = parse(input).unwrap();
+        let file = &data.files[0];
+        assert_eq!(file.branches.len(), 3);
+        assert_eq!(file.branches[0].group_id, Some(0));
+        assert_eq!(file.branches[1].group_id, Some(0));
+        assert_eq!(file.branches[1].hit_count, 0);
+        assert_eq!(file.branches[2].group_id, Some(1));
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_schema(&conn).unwrap();
+
+        let mut data = CoverageData::new();
+        let mut file = FileCoverage::new("src/lib.rs".to_string());
+        file.lines.push(LineCoverage {
+            line_number: 1,
+            hit_count: 3,
+        });
+        file.lines.push(LineCoverage {
+            line_number: 2,
+            hit_count: 0,
+        });
+        file.branches.push(BranchCoverage {
+            line_number: 1,
+            branch_index: 0,
+            hit_count: 1,
+            group_id: None,
+            kind: BranchKind::Unknown,
+            arm_line: None,
+        });
+        file.functions.push(FunctionCoverage {
+            name: "main".to_string(),
+            start_line: Some(1),
+            end_line: None,
+            hit_count: 3,
+        });
+        data.files.push(file);
+
+        let mut conn = conn;
+        crate::db::insert_coverage(&mut conn, "r1", "lcov", None, &data, false).unwrap();
+
+        let out = export(&conn).unwrap();
+        assert!(out.contains("SF:src/lib.rs"));
+        assert!(out.contains("FN:1,main"));
+        assert!(out.contains("FNDA:3,main"));
+        assert!(out.contains("BRDA:1,0,0,1"));
+        assert!(out.contains("DA:1,3"));
+        assert!(out.contains("DA:2,0"));
+        assert!(out.contains("LF:2"));
+        assert!(out.contains("LH:1"));
+        assert!(out.contains("end_of_record"));
+    }
+
     #[test]
     fn test_parse_lcov_empty() {
         // An LCOV file with only a test name and no records should produce
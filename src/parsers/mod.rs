@@ -1,17 +1,19 @@
 pub mod cobertura;
+pub mod codecov;
 pub mod gocover;
 pub mod istanbul;
 pub mod jacoco;
 pub mod lcov;
+pub mod v8;
 
 use std::io::BufRead;
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use quick_xml::events::BytesStart;
 use quick_xml::reader::Reader;
 
-use crate::model::FileCoverage;
+use crate::model::{CoverageData, FileCoverage};
 
 /// Parser for a specific coverage format.
 pub trait CoverageParser {
@@ -72,6 +74,7 @@ pub enum Format {
     Istanbul,
     Jacoco,
     Lcov,
+    V8,
 }
 
 impl std::fmt::Display for Format {
@@ -82,6 +85,7 @@ impl std::fmt::Display for Format {
             Format::Istanbul => f.write_str("istanbul"),
             Format::Jacoco => f.write_str("jacoco"),
             Format::Lcov => f.write_str("lcov"),
+            Format::V8 => f.write_str("v8"),
         }
     }
 }
@@ -96,8 +100,9 @@ impl std::str::FromStr for Format {
             "istanbul" | "nyc" => Ok(Format::Istanbul),
             "jacoco" => Ok(Format::Jacoco),
             "lcov" => Ok(Format::Lcov),
+            "v8" | "v8json" | "deno" => Ok(Format::V8),
             _ => Err(anyhow::anyhow!(
-                "Unknown format: '{s}'. Supported: cobertura, gocover, istanbul, jacoco, lcov"
+                "Unknown format: '{s}'. Supported: cobertura, gocover, istanbul, jacoco, lcov, v8"
             )),
         }
     }
@@ -114,11 +119,14 @@ impl std::str::FromStr for Format {
 /// JaCoCo is checked before Cobertura since both are XML but JaCoCo's
 /// `<report` + `jacoco`/`<package` markers are more specific than
 /// Cobertura's `<coverage`.
+/// V8 is checked alongside Istanbul (both JSON) — its `scriptId`/`ranges`/
+/// `startOffset` markers don't overlap with Istanbul's `statementMap`/`fnMap`.
 pub fn all() -> Vec<Box<dyn CoverageParser>> {
     vec![
         Box::new(lcov::LcovParser),
         Box::new(gocover::GocoverParser),
         Box::new(istanbul::IstanbulParser),
+        Box::new(v8::V8Parser::new()),
         Box::new(jacoco::JacocoParser),
         Box::new(cobertura::CoberturaParser),
     ]
@@ -129,6 +137,29 @@ pub fn detect(path: &Path, content: &[u8]) -> Option<Box<dyn CoverageParser>> {
     all().into_iter().find(|p| p.can_parse(path, content))
 }
 
+/// Parse `paths`, auto-detecting each file's format via [`detect`], and
+/// fold the results into one combined [`CoverageData`] via
+/// [`CoverageData::merge`] — lets heterogeneous inputs (e.g. an LCOV file
+/// alongside a Cobertura XML file from a different test run) be combined
+/// in a single pass even though each needs a different parser.
+pub fn parse_and_merge(paths: &[&Path]) -> Result<CoverageData> {
+    let mut data = CoverageData::new();
+    for path in paths {
+        let content =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let parser = detect(path, &content)
+            .with_context(|| format!("Could not detect coverage format for {}", path.display()))?;
+
+        let mut file_data = CoverageData::new();
+        parser.parse_streaming(&mut &content[..], &mut |file| {
+            file_data.files.push(file);
+            Ok(())
+        })?;
+        data.merge(file_data);
+    }
+    Ok(data)
+}
+
 /// Get the appropriate parser for an explicit format name.
 pub fn for_format(format: Format) -> Box<dyn CoverageParser> {
     match format {
@@ -137,6 +168,7 @@ pub fn for_format(format: Format) -> Box<dyn CoverageParser> {
         Format::Istanbul => Box::new(istanbul::IstanbulParser),
         Format::Jacoco => Box::new(jacoco::JacocoParser),
         Format::Lcov => Box::new(lcov::LcovParser),
+        Format::V8 => Box::new(v8::V8Parser::new()),
     }
 }
 
@@ -212,8 +244,60 @@ mod tests {
         assert_eq!(parser.format(), Format::Istanbul);
     }
 
+    #[test]
+    fn test_format_from_str_accepts_v8_aliases() {
+        assert_eq!("v8".parse::<Format>().unwrap(), Format::V8);
+        assert_eq!("v8json".parse::<Format>().unwrap(), Format::V8);
+        assert_eq!("deno".parse::<Format>().unwrap(), Format::V8);
+    }
+
     #[test]
     fn test_detect_unknown() {
         assert!(detect(Path::new("random.dat"), b"hello world").is_none());
     }
+
+    #[test]
+    fn test_parse_and_merge_unions_heterogeneous_formats() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let lcov_path = dir.path().join("a.info");
+        std::fs::write(
+            &lcov_path,
+            "TN:\nSF:/src/lib.rs\nDA:1,2\nDA:2,0\nend_of_record\n",
+        )
+        .unwrap();
+
+        let cobertura_path = dir.path().join("b.xml");
+        std::fs::write(
+            &cobertura_path,
+            r#"<?xml version="1.0"?>
+<coverage version="1.0">
+  <packages>
+    <package name="src">
+      <classes>
+        <class name="lib" filename="/src/lib.rs">
+          <lines>
+            <line number="1" hits="1"/>
+            <line number="2" hits="3"/>
+          </lines>
+        </class>
+      </classes>
+    </package>
+  </packages>
+</coverage>
+"#,
+        )
+        .unwrap();
+
+        let data =
+            parse_and_merge(&[lcov_path.as_path(), cobertura_path.as_path()]).unwrap();
+
+        assert_eq!(data.files.len(), 1);
+        let file = &data.files[0];
+        assert_eq!(file.path, "/src/lib.rs");
+        let line1 = file.lines.iter().find(|l| l.line_number == 1).unwrap();
+        assert_eq!(line1.hit_count, 3);
+        let line2 = file.lines.iter().find(|l| l.line_number == 2).unwrap();
+        assert_eq!(line2.hit_count, 3);
+    }
 }
@@ -0,0 +1,641 @@
+/// Parser for Chrome DevTools Protocol / V8 "precise coverage" JSON, as
+/// produced by `Profiler.takePreciseCoverage()` and written to disk by
+/// Deno's `--coverage` flag and Node's `NODE_V8_COVERAGE`.
+///
+/// Reference: https://chromedevtools.github.io/devtools-protocol/tot/Profiler/#method-takePreciseCoverage
+///
+/// Node's `NODE_V8_COVERAGE` writes the full CDP response shape, a single
+/// object wrapping the script list:
+///   { "result": [{ "scriptId": "123", "url": "file:///src/lib.js",
+///       "functions": [{ "functionName": "foo", "isBlockCoverage": true,
+///                       "ranges": [{ "startOffset": 0, "endOffset": 120, "count": 1 }] }] }] }
+///
+/// A bare JSON array of the same per-script entries (as written by some
+/// Deno coverage layouts) is also accepted.
+///
+/// Offsets are byte indexes into the script's source text, not line
+/// numbers, so we resolve the source text named by each entry's `url` (by
+/// default, reading it from its local path) and build a line-start table to
+/// translate offsets into `line_number`s.
+use std::fs;
+use std::io::{BufRead, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use super::{CoverageParser, Format};
+use crate::model::*;
+
+/// V8 precise-coverage JSON parser.
+///
+/// By default, resolves a script's source by reading its `url`'s local
+/// path; use [`V8Parser::with_source_resolver`] to supply source text from
+/// elsewhere (e.g. an in-memory build output, or a non-`file://` URL).
+pub struct V8Parser {
+    resolve_source: Box<dyn Fn(&str) -> Option<Vec<u8>>>,
+}
+
+impl V8Parser {
+    pub fn new() -> Self {
+        Self {
+            resolve_source: Box::new(default_source_resolver),
+        }
+    }
+
+    /// Resolve each script's source text via `resolver` (keyed by the
+    /// script's `url`) instead of reading it from disk.
+    pub fn with_source_resolver(resolver: impl Fn(&str) -> Option<Vec<u8>> + 'static) -> Self {
+        Self {
+            resolve_source: Box::new(resolver),
+        }
+    }
+
+    /// Resolve each script's source relative to `root` instead of the
+    /// process's current directory — lets a V8 coverage file captured on a
+    /// different machine (or inside a container) be matched up with a local
+    /// checkout of the same sources. The `url`'s path is treated as
+    /// root-relative (its leading `/`, if any, is stripped before joining).
+    pub fn with_source_root(root: &Path) -> Self {
+        let root = root.to_path_buf();
+        Self::with_source_resolver(move |url| {
+            let path = local_path(url)?;
+            let relative = path.strip_prefix("/").unwrap_or(&path);
+            fs::read(root.join(relative)).ok()
+        })
+    }
+}
+
+impl Default for V8Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CoverageParser for V8Parser {
+    fn format(&self) -> Format {
+        Format::V8
+    }
+
+    fn can_parse(&self, path: &Path, content: &[u8]) -> bool {
+        let is_json = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+        if !is_json {
+            return false;
+        }
+        let head = super::sniff_head(content);
+        if !(head.contains("\"ranges\"") && head.contains("\"startOffset\"")) {
+            return false;
+        }
+        let trimmed = head.trim_start();
+        // The CDP response wrapper (`{ "result": [...] }`) or a bare array
+        // of script entries.
+        (trimmed.starts_with('{') && head.contains("\"result\""))
+            || (trimmed.starts_with('[') && head.contains("\"scriptId\""))
+    }
+
+    fn parse_streaming(
+        &self,
+        reader: &mut dyn BufRead,
+        emit: &mut dyn FnMut(FileCoverage) -> Result<()>,
+    ) -> Result<()> {
+        let mut raw = String::new();
+        reader.read_to_string(&mut raw)?;
+        let input: CoverageInput = serde_json::from_str(&raw)?;
+        for script in &input.into_scripts() {
+            if let Some(file) = convert_script(script, self.resolve_source.as_ref())? {
+                emit(file)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Either shape accepted by [`V8Parser`]: the full CDP response object, or a
+/// bare array of script entries.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CoverageInput {
+    Wrapped { result: Vec<ScriptCoverage> },
+    Bare(Vec<ScriptCoverage>),
+}
+
+impl CoverageInput {
+    fn into_scripts(self) -> Vec<ScriptCoverage> {
+        match self {
+            CoverageInput::Wrapped { result } => result,
+            CoverageInput::Bare(scripts) => scripts,
+        }
+    }
+}
+
+/// Parse V8 precise-coverage JSON from raw bytes, reading referenced
+/// sources from their local paths.
+pub fn parse(input: &[u8]) -> Result<CoverageData> {
+    let mut data = CoverageData::new();
+    V8Parser::new().parse_streaming(&mut &*input, &mut |file| {
+        data.files.push(file);
+        Ok(())
+    })?;
+    Ok(data)
+}
+
+/// Default [`V8Parser`] source resolver: strip the `url`'s `file://` scheme
+/// (if any) and read it from disk.
+fn default_source_resolver(url: &str) -> Option<Vec<u8>> {
+    fs::read(local_path(url)?).ok()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScriptCoverage {
+    #[allow(dead_code)]
+    script_id: String,
+    url: String,
+    functions: Vec<FunctionEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FunctionEntry {
+    function_name: String,
+    #[serde(default)]
+    is_block_coverage: bool,
+    ranges: Vec<RangeEntry>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct RangeEntry {
+    start_offset: usize,
+    end_offset: usize,
+    count: u64,
+}
+
+/// Strip a `file://` URL scheme. Returns `None` for non-local URLs
+/// (`http(s)://`, `node:`, etc.) which have no source we can read.
+fn local_path(url: &str) -> Option<PathBuf> {
+    if let Some(rest) = url.strip_prefix("file://") {
+        Some(PathBuf::from(rest))
+    } else if url.contains("://") || url.starts_with("node:") {
+        None
+    } else {
+        Some(PathBuf::from(url))
+    }
+}
+
+/// Byte offset of the start of each line (0-indexed), ascending.
+fn line_starts(source: &[u8]) -> Vec<usize> {
+    let mut starts = vec![0usize];
+    for (i, &b) in source.iter().enumerate() {
+        if b == b'\n' {
+            starts.push(i + 1);
+        }
+    }
+    starts
+}
+
+/// Translate a byte offset into a 1-indexed line number.
+fn offset_to_line(starts: &[usize], offset: usize) -> u32 {
+    let idx = match starts.binary_search(&offset) {
+        Ok(i) => i,
+        Err(i) => i.saturating_sub(1),
+    };
+    (idx + 1) as u32
+}
+
+/// Resolve a function's (possibly nested) ranges into non-overlapping
+/// `(start, end, count)` byte spans, where an inner range's `count`
+/// overrides its enclosing range for the bytes it covers — e.g. an outer
+/// function body counted 5 containing an `if` branch counted 0 yields the
+/// `if`'s span as uncovered rather than being swallowed by the outer
+/// range's count. Ranges are expected to either nest or be disjoint, as
+/// V8 block coverage produces; the input need not already be sorted.
+fn resolve_ranges(ranges: &[RangeEntry]) -> Vec<(usize, usize, u64)> {
+    if ranges.is_empty() {
+        return Vec::new();
+    }
+    // Sort by startOffset asc, endOffset desc so a parent always precedes
+    // its children, and each range's children form a contiguous run right
+    // after it in the sorted order.
+    let mut sorted: Vec<&RangeEntry> = ranges.iter().collect();
+    sorted.sort_by(|a, b| {
+        a.start_offset
+            .cmp(&b.start_offset)
+            .then(b.end_offset.cmp(&a.end_offset))
+    });
+    resolve_siblings(&sorted)
+}
+
+/// Partition a list of ranges at the same tree level — already sorted
+/// start-asc/end-desc — into non-overlapping spans, recursing into each
+/// range's nested children first so the deepest range always wins.
+fn resolve_siblings(ranges: &[&RangeEntry]) -> Vec<(usize, usize, u64)> {
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < ranges.len() {
+        let parent = ranges[i];
+        let mut j = i + 1;
+        while j < ranges.len() && ranges[j].start_offset < parent.end_offset {
+            j += 1;
+        }
+        let child_spans = resolve_siblings(&ranges[i + 1..j]);
+
+        // Fill the gaps between (and around) the children with the
+        // parent's own count.
+        let mut cursor = parent.start_offset;
+        for (start, end, count) in child_spans {
+            if cursor < start {
+                spans.push((cursor, start, parent.count));
+            }
+            spans.push((start, end, count));
+            cursor = end;
+        }
+        if cursor < parent.end_offset {
+            spans.push((cursor, parent.end_offset, parent.count));
+        }
+
+        i = j;
+    }
+    spans
+}
+
+fn convert_script(
+    script: &ScriptCoverage,
+    resolve_source: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> Result<Option<FileCoverage>> {
+    let source = match resolve_source(&script.url) {
+        Some(s) => s,
+        // `local_path` returning `None` means this URL's scheme (e.g.
+        // `node:`, `http(s)://`) has no local source to begin with — skip
+        // it rather than treating it as an error. Anything else (a `file://`
+        // or bare path the resolver couldn't read) was expected to resolve,
+        // so missing source there is a clear misconfiguration, not something
+        // to paper over.
+        None if local_path(&script.url).is_none() => return Ok(None),
+        None => anyhow::bail!(
+            "Source for '{}' is unavailable — supply a source resolver \
+             (or source root) that can read it",
+            script.url
+        ),
+    };
+    let starts = line_starts(&source);
+
+    let display_path = local_path(&script.url)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|| script.url.clone());
+    let mut file = FileCoverage::new(display_path);
+    let mut line_hits: std::collections::BTreeMap<u32, u64> = std::collections::BTreeMap::new();
+    let mut branch_indices: std::collections::HashMap<u32, u32> = std::collections::HashMap::new();
+
+    for func in &script.functions {
+        for (start, end, count) in resolve_ranges(&func.ranges) {
+            if end <= start {
+                continue;
+            }
+            let start_line = offset_to_line(&starts, start);
+            let end_line = offset_to_line(&starts, end.saturating_sub(1));
+            for line in start_line..=end_line {
+                line_hits
+                    .entry(line)
+                    .and_modify(|e| *e = (*e).max(count))
+                    .or_insert(count);
+            }
+        }
+
+        if let Some(outer) = func.ranges.first() {
+            let end_line = if outer.end_offset > outer.start_offset {
+                offset_to_line(&starts, outer.end_offset.saturating_sub(1))
+            } else {
+                offset_to_line(&starts, outer.start_offset)
+            };
+            file.functions.push(FunctionCoverage {
+                name: func.function_name.clone(),
+                start_line: Some(offset_to_line(&starts, outer.start_offset)),
+                end_line: Some(end_line),
+                hit_count: outer.count,
+            });
+        }
+
+        // Under block-level coverage, every range after the outer
+        // function-body range carves out a sub-block (e.g. an `if`/`else`
+        // arm) — report each as a branch arm on the line it starts on.
+        if func.is_block_coverage {
+            for r in func.ranges.iter().skip(1) {
+                let line = offset_to_line(&starts, r.start_offset);
+                let idx = branch_indices.entry(line).or_insert(0);
+                file.branches.push(BranchCoverage {
+                    line_number: line,
+                    branch_index: *idx,
+                    hit_count: r.count,
+                    group_id: None,
+                    kind: BranchKind::Unknown,
+                    arm_line: None,
+                });
+                *idx += 1;
+            }
+        }
+    }
+
+    for (line_number, hit_count) in line_hits {
+        file.lines.push(LineCoverage {
+            line_number,
+            hit_count,
+        });
+    }
+
+    Ok(Some(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_to_line() {
+        // "ab\ncd\nef" -> line starts at 0, 3, 6
+        let starts = line_starts(b"ab\ncd\nef");
+        assert_eq!(starts, vec![0, 3, 6]);
+        assert_eq!(offset_to_line(&starts, 0), 1);
+        assert_eq!(offset_to_line(&starts, 3), 2);
+        assert_eq!(offset_to_line(&starts, 7), 3);
+    }
+
+    #[test]
+    fn test_offset_to_line_counts_bytes_not_chars() {
+        // "café" is 5 bytes (the "é" is a 2-byte UTF-8 sequence) but 4
+        // chars — line 2 must start at byte offset 6, not char offset 5,
+        // or a char-counting implementation would resolve it a line early.
+        let starts = line_starts("café\nline2\n".as_bytes());
+        assert_eq!(starts, vec![0, 6, 12]);
+        assert_eq!(offset_to_line(&starts, 6), 2);
+    }
+
+    fn range(start_offset: usize, end_offset: usize, count: u64) -> RangeEntry {
+        RangeEntry {
+            start_offset,
+            end_offset,
+            count,
+        }
+    }
+
+    #[test]
+    fn test_resolve_ranges_inner_range_overrides_outer() {
+        // Outer function body counted 5, containing a zero-count `if`
+        // branch from 10 to 20 — the inner range must win for [10, 20).
+        let ranges = vec![range(0, 30, 5), range(10, 20, 0)];
+        let spans = resolve_ranges(&ranges);
+
+        assert_eq!(spans, vec![(0, 10, 5), (10, 20, 0), (20, 30, 5)]);
+    }
+
+    #[test]
+    fn test_resolve_ranges_handles_sibling_and_nested_children() {
+        // Two sibling `if`/`else` arms (5..10 and 10..15) inside an outer
+        // range, with a further-nested range inside the first arm.
+        let ranges = vec![
+            range(0, 15, 1),
+            range(5, 10, 2),
+            range(6, 8, 0),
+            range(10, 15, 3),
+        ];
+        let spans = resolve_ranges(&ranges);
+
+        assert_eq!(
+            spans,
+            vec![
+                (0, 5, 1),
+                (5, 6, 2),
+                (6, 8, 0),
+                (8, 10, 2),
+                (10, 15, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_ranges_accepts_unsorted_input() {
+        let ranges = vec![range(10, 20, 0), range(0, 30, 5)];
+        let spans = resolve_ranges(&ranges);
+
+        assert_eq!(spans, vec![(0, 10, 5), (10, 20, 0), (20, 30, 5)]);
+    }
+
+    #[test]
+    fn test_resolve_ranges_empty_input() {
+        assert_eq!(resolve_ranges(&[]), Vec::new());
+    }
+
+    #[test]
+    fn test_local_path() {
+        assert_eq!(
+            local_path("file:///src/lib.js"),
+            Some(PathBuf::from("/src/lib.js"))
+        );
+        assert_eq!(local_path("https://example.com/a.js"), None);
+        assert_eq!(local_path("node:internal/a"), None);
+    }
+
+    #[test]
+    fn test_can_parse() {
+        let parser = V8Parser::new();
+        let content = br#"[{"scriptId":"1","url":"file:///a.js","functions":[{"functionName":"f","isBlockCoverage":true,"ranges":[{"startOffset":0,"endOffset":10,"count":1}]}]}]"#;
+        assert!(parser.can_parse(Path::new("coverage.json"), content));
+        assert!(!parser.can_parse(Path::new("coverage.lcov"), content));
+        assert!(!parser.can_parse(Path::new("coverage.json"), b"{}"));
+    }
+
+    #[test]
+    fn test_with_source_resolver_reads_from_in_memory_source() {
+        let source = b"function f() {\n  if (cond) {\n    used();\n  } else {\n    unused();\n  }\n}\n";
+        let input = br#"[{"scriptId":"1","url":"app:///f.js","functions":[{"functionName":"f","isBlockCoverage":true,"ranges":[{"startOffset":0,"endOffset":72,"count":1},{"startOffset":50,"endOffset":69,"count":0}]}]}]"#;
+
+        let parser = V8Parser::with_source_resolver(|url| {
+            if url == "app:///f.js" {
+                Some(source.to_vec())
+            } else {
+                None
+            }
+        });
+
+        let mut data = CoverageData::new();
+        parser
+            .parse_streaming(&mut &input[..], &mut |file| {
+                data.files.push(file);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(data.files.len(), 1);
+        let file = &data.files[0];
+        assert_eq!(file.path, "app:///f.js");
+        // Line 5 ("unused();") falls under the zero-count inner range.
+        let line5 = file.lines.iter().find(|l| l.line_number == 5).unwrap();
+        assert_eq!(line5.hit_count, 0);
+        // Line 3 ("used();") falls under only the outer, covered range.
+        let line3 = file.lines.iter().find(|l| l.line_number == 3).unwrap();
+        assert_eq!(line3.hit_count, 1);
+
+        // The zero-count inner range (the `else` arm) is reported as an
+        // uncovered branch arm on its start line.
+        let branch = file
+            .branches
+            .iter()
+            .find(|b| b.line_number == 4)
+            .expect("branch arm for the else block");
+        assert_eq!(branch.hit_count, 0);
+    }
+
+    #[test]
+    fn test_convert_script_merges_same_line_ranges_by_max_not_last_write() {
+        // `if (a) { used() } else { unused() }` fits on one line, so the
+        // if-arm (count 5) and else-arm (count 0) spans both land on line 1.
+        // The else-arm span is resolved last (it starts at a higher byte
+        // offset), so naively overwriting line_hits on every span would
+        // report line 1 as uncovered even though the if-arm executed.
+        let source = b"if (a) { used() } else { unused() }";
+        let input = br#"[{"scriptId":"1","url":"app:///f.js","functions":[{"functionName":"","isBlockCoverage":true,"ranges":[{"startOffset":0,"endOffset":35,"count":1},{"startOffset":7,"endOffset":17,"count":5},{"startOffset":23,"endOffset":35,"count":0}]}]}]"#;
+
+        let parser = V8Parser::with_source_resolver(|url| {
+            if url == "app:///f.js" {
+                Some(source.to_vec())
+            } else {
+                None
+            }
+        });
+
+        let mut data = CoverageData::new();
+        parser
+            .parse_streaming(&mut &input[..], &mut |file| {
+                data.files.push(file);
+                Ok(())
+            })
+            .unwrap();
+
+        let line1 = data.files[0]
+            .lines
+            .iter()
+            .find(|l| l.line_number == 1)
+            .unwrap();
+        assert_eq!(line1.hit_count, 5);
+    }
+
+    #[test]
+    fn test_parses_wrapped_cdp_result_shape() {
+        let source = b"function f() {\n  used();\n}\n";
+        let input = br#"{"result":[{"scriptId":"1","url":"app:///f.js","functions":[{"functionName":"f","isBlockCoverage":true,"ranges":[{"startOffset":0,"endOffset":27,"count":1}]}]}]}"#;
+
+        let parser = V8Parser::with_source_resolver(|url| {
+            if url == "app:///f.js" {
+                Some(source.to_vec())
+            } else {
+                None
+            }
+        });
+
+        let mut data = CoverageData::new();
+        parser
+            .parse_streaming(&mut &input[..], &mut |file| {
+                data.files.push(file);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(data.files.len(), 1);
+        assert_eq!(data.files[0].path, "app:///f.js");
+
+        let function = &data.files[0].functions[0];
+        assert_eq!(function.start_line, Some(1));
+        assert_eq!(function.end_line, Some(3));
+    }
+
+    #[test]
+    fn test_line_resolution_unaffected_by_multibyte_source() {
+        // A multi-byte character on line 2 must not throw off the line
+        // resolution of the range's end offset on line 3 — offsets are
+        // byte indexes, and mixing up bytes and chars is the classic bug
+        // here.
+        let source = "function f() {\n  caf\u{e9}();\n}\n".as_bytes().to_vec();
+        let end = source.len();
+        let input = format!(
+            r#"[{{"scriptId":"1","url":"app:///f.js","functions":[{{"functionName":"f","isBlockCoverage":false,"ranges":[{{"startOffset":0,"endOffset":{end},"count":2}}]}}]}}]"#,
+        );
+
+        let parser = V8Parser::with_source_resolver(move |url| {
+            if url == "app:///f.js" {
+                Some(source.clone())
+            } else {
+                None
+            }
+        });
+
+        let mut data = CoverageData::new();
+        parser
+            .parse_streaming(&mut input.as_bytes(), &mut |file| {
+                data.files.push(file);
+                Ok(())
+            })
+            .unwrap();
+
+        let file = &data.files[0];
+        let line3 = file.lines.iter().find(|l| l.line_number == 3).unwrap();
+        assert_eq!(line3.hit_count, 2);
+    }
+
+    #[test]
+    fn test_unavailable_source_is_a_clear_error_not_a_silent_skip() {
+        let input = br#"[{"scriptId":"1","url":"file:///missing.js","functions":[{"functionName":"f","isBlockCoverage":false,"ranges":[{"startOffset":0,"endOffset":10,"count":1}]}]}]"#;
+        let parser = V8Parser::with_source_resolver(|_| None);
+
+        let err = parser
+            .parse_streaming(&mut &input[..], &mut |_| Ok(()))
+            .unwrap_err();
+        assert!(err.to_string().contains("file:///missing.js"));
+    }
+
+    #[test]
+    fn test_non_local_script_url_is_silently_skipped_not_an_error() {
+        // Node's NODE_V8_COVERAGE dumps mix in built-in `node:` modules
+        // alongside user scripts — those never have a readable local
+        // source, so they're skipped rather than failing the whole ingest.
+        let input = br#"[{"scriptId":"1","url":"node:internal/bootstrap","functions":[{"functionName":"f","isBlockCoverage":false,"ranges":[{"startOffset":0,"endOffset":10,"count":1}]}]}]"#;
+        let parser = V8Parser::new();
+
+        let mut data = CoverageData::new();
+        parser
+            .parse_streaming(&mut &input[..], &mut |file| {
+                data.files.push(file);
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(data.files.is_empty());
+    }
+
+    #[test]
+    fn test_with_source_root_reads_script_relative_to_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("f.js"), b"function f() {\n  used();\n}\n").unwrap();
+
+        let input = br#"[{"scriptId":"1","url":"file:///f.js","functions":[{"functionName":"f","isBlockCoverage":false,"ranges":[{"startOffset":0,"endOffset":29,"count":1}]}]}]"#;
+        let parser = V8Parser::with_source_root(dir.path());
+
+        let mut data = CoverageData::new();
+        parser
+            .parse_streaming(&mut &input[..], &mut |file| {
+                data.files.push(file);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(data.files.len(), 1);
+        let line2 = data.files[0]
+            .lines
+            .iter()
+            .find(|l| l.line_number == 2)
+            .unwrap();
+        assert_eq!(line2.hit_count, 1);
+    }
+}
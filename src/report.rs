@@ -1,9 +1,14 @@
 //! Output formatting for diff coverage results.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 
-use crate::model::{rate, FileDiffCoverage};
+use anyhow::Context;
+
+use crate::model::{
+    rate, FileBranchDiffCoverage, FileDiffCoverage, FileFunctionDiffCoverage, FileSummary,
+    LineDetail, ReportSummary,
+};
 
 /// Aggregated diff coverage data, ready to be formatted.
 pub struct DiffCoverageReport {
@@ -17,12 +22,28 @@ pub struct DiffCoverageReport {
     pub total_covered: usize,
     /// Total instrumentable diff lines.
     pub total_instrumentable: usize,
+    /// Per-file branch/decision diff coverage (only files with at least one branch).
+    pub branch_files: Vec<FileBranchDiffCoverage>,
+    /// Total diff decisions that are covered.
+    pub total_branches_covered: usize,
+    /// Total diff decisions.
+    pub total_branches: usize,
+    /// Per-file function diff coverage (only files defining a function on a diff line).
+    pub function_files: Vec<FileFunctionDiffCoverage>,
+    /// Total diff functions that are covered.
+    pub total_functions_covered: usize,
+    /// Total diff functions.
+    pub total_functions: usize,
     /// Overall project line coverage rate (if available).
     pub total_rate: Option<f64>,
     /// Per-file total line coverage rates (path → rate as 0.0–1.0).
     pub file_rates: HashMap<String, f64>,
     /// Commit SHA to display.
     pub sha: Option<String>,
+    /// Threshold violations found by [`Thresholds::evaluate`], if the caller
+    /// ran one. Empty when no thresholds were configured or all passed.
+    /// `TextFormatter`/`MarkdownFormatter` render these inline.
+    pub threshold_failures: Vec<ThresholdFailure>,
 }
 
 impl DiffCoverageReport {
@@ -31,6 +52,318 @@ impl DiffCoverageReport {
     pub fn format(&self, formatter: &dyn ReportFormatter) -> String {
         formatter.format(self)
     }
+
+    /// Whether every configured threshold passed (or none were configured).
+    /// Callers map this to a process exit code.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.threshold_failures.is_empty()
+    }
+
+    /// Paths of files with a per-file threshold violation, for formatters
+    /// that mark failing rows (e.g. a ❌ in the markdown table).
+    fn failing_paths(&self) -> HashSet<&str> {
+        self.threshold_failures
+            .iter()
+            .filter_map(|f| match f {
+                ThresholdFailure::File { path, .. } => Some(path.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Per-file `(covered, total)` decision counts, for formatters that
+    /// render a "branch X/Y" column alongside line coverage.
+    fn branch_totals_by_path(&self) -> HashMap<&str, (usize, usize)> {
+        self.branch_files
+            .iter()
+            .map(|f| (f.path.as_str(), (f.covered_lines.len(), f.total())))
+            .collect()
+    }
+
+    /// Per-file `(covered, total)` function counts, for formatters that
+    /// render a "fn X/Y" column alongside line coverage.
+    fn function_totals_by_path(&self) -> HashMap<&str, (usize, usize)> {
+        self.function_files
+            .iter()
+            .map(|f| (f.path.as_str(), (f.covered_lines.len(), f.total())))
+            .collect()
+    }
+}
+
+/// Coverage bars a diff (or the whole project) must clear. Each field is a
+/// percentage in 0–100; `None` skips that dimension. Evaluate against a
+/// built [`DiffCoverageReport`] with [`Thresholds::evaluate`], then store the
+/// result in the report's `threshold_failures` field before formatting so
+/// `TextFormatter`/`MarkdownFormatter` can annotate failing rows.
+#[derive(Debug, Clone, Default)]
+pub struct Thresholds {
+    /// Minimum diff line coverage percentage.
+    pub diff_min: Option<f64>,
+    /// Minimum overall project line coverage percentage.
+    pub project_min: Option<f64>,
+    /// Minimum per-file diff line coverage percentage, checked against
+    /// every file touched by the diff that has no matching entry in
+    /// `path_overrides`.
+    pub per_file_min: Option<f64>,
+    /// Per-glob minimum line coverage percentages (e.g. from a `[thresholds]`
+    /// config loaded with [`Thresholds::load`]), checked against every
+    /// file's path with the longest-matching glob winning when more than
+    /// one pattern matches. Falls back to `per_file_min` when no glob
+    /// matches a given file.
+    pub path_overrides: Vec<(String, f64)>,
+}
+
+impl Thresholds {
+    /// Check `report` against these thresholds, returning every violation
+    /// found (empty when everything passes).
+    #[must_use]
+    pub fn evaluate(&self, report: &DiffCoverageReport) -> Vec<ThresholdFailure> {
+        let mut failures = Vec::new();
+
+        if let Some(min) = self.diff_min {
+            if report.total_instrumentable > 0 {
+                let actual =
+                    rate(report.total_covered as u64, report.total_instrumentable as u64) * 100.0;
+                if actual < min {
+                    failures.push(ThresholdFailure::Diff {
+                        actual,
+                        required: min,
+                    });
+                }
+            }
+        }
+
+        if let Some(min) = self.project_min {
+            if let Some(project_rate) = report.total_rate {
+                let actual = project_rate * 100.0;
+                if actual < min {
+                    failures.push(ThresholdFailure::Project {
+                        actual,
+                        required: min,
+                    });
+                }
+            }
+        }
+
+        for f in &report.files {
+            if let Some(min) = self.file_min(&f.path) {
+                let actual = f.rate() * 100.0;
+                if actual < min {
+                    failures.push(ThresholdFailure::File {
+                        path: f.path.clone(),
+                        actual,
+                        required: min,
+                    });
+                }
+            }
+        }
+
+        failures
+    }
+
+    /// Resolve the minimum line coverage percentage that applies to `path`:
+    /// the longest-matching glob in `path_overrides`, or `per_file_min` when
+    /// nothing matches.
+    fn file_min(&self, path: &str) -> Option<f64> {
+        self.path_overrides
+            .iter()
+            .filter(|(glob, _)| glob_match(glob, path))
+            .max_by_key(|(glob, _)| glob.len())
+            .map(|&(_, min)| min)
+            .or(self.per_file_min)
+    }
+
+    /// Parse a small config file mapping path globs to minimum line
+    /// coverage percentages, one `glob = percentage` assignment per line
+    /// (blank lines, `#` comments, and `[section]` headers are ignored):
+    ///
+    /// ```text
+    /// [thresholds]
+    /// src/generated/** = 0
+    /// src/core/* = 90
+    /// ```
+    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse_config(&text)
+    }
+
+    /// Parse config text in the format described by [`Thresholds::load`].
+    pub fn parse_config(text: &str) -> anyhow::Result<Self> {
+        let mut path_overrides = Vec::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+                continue;
+            }
+            let (glob, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid threshold line: {line}"))?;
+            let glob = glob.trim().to_string();
+            let min: f64 = value
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid threshold value in line: {line}"))?;
+            path_overrides.push((glob, min));
+        }
+
+        Ok(Thresholds {
+            path_overrides,
+            ..Default::default()
+        })
+    }
+}
+
+/// Match `path` against a glob `pattern` where `*` matches any run of
+/// non-`/` characters and `**` matches across directory separators too.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    fn matches<'a>(pat: &[&'a str], segs: &[&'a str]) -> bool {
+        match pat.split_first() {
+            None => segs.is_empty(),
+            Some((&"**", rest)) => {
+                (0..=segs.len()).any(|i| matches(rest, &segs[i..]))
+            }
+            Some((&head, rest)) => {
+                !segs.is_empty() && segment_match(head, segs[0]) && matches(rest, &segs[1..])
+            }
+        }
+    }
+
+    fn segment_match(pattern: &str, segment: &str) -> bool {
+        let parts: Vec<&str> = pattern.split('*').collect();
+        if parts.len() == 1 {
+            return pattern == segment;
+        }
+        let mut rest = segment;
+        for (i, part) in parts.iter().enumerate() {
+            if part.is_empty() {
+                continue;
+            }
+            if i == 0 {
+                match rest.strip_prefix(part) {
+                    Some(r) => rest = r,
+                    None => return false,
+                }
+            } else if i == parts.len() - 1 {
+                return rest.ends_with(part);
+            } else {
+                match rest.find(part) {
+                    Some(idx) => rest = &rest[idx + part.len()..],
+                    None => return false,
+                }
+            }
+        }
+        true
+    }
+
+    let pat_segs: Vec<&str> = pattern.split('/').collect();
+    let path_segs: Vec<&str> = path.split('/').collect();
+    matches(&pat_segs, &path_segs)
+}
+
+/// A single threshold violation, ready to be rendered by a formatter or
+/// mapped to a process exit code by the caller.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThresholdFailure {
+    /// Diff coverage fell below `diff_min`.
+    Diff { actual: f64, required: f64 },
+    /// Overall project coverage fell below `project_min`.
+    Project { actual: f64, required: f64 },
+    /// A single file's coverage fell below its per-file minimum
+    /// (`per_file_min`, or the longest matching `path_overrides` glob).
+    File {
+        path: String,
+        actual: f64,
+        required: f64,
+    },
+}
+
+impl std::fmt::Display for ThresholdFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThresholdFailure::Diff { actual, required } => {
+                write!(f, "FAILED: diff coverage {actual:.1}% < {required:.1}%")
+            }
+            ThresholdFailure::Project { actual, required } => {
+                write!(f, "FAILED: project coverage {actual:.1}% < {required:.1}%")
+            }
+            ThresholdFailure::File {
+                path,
+                actual,
+                required,
+            } => write!(f, "FAILED: {path} coverage {actual:.1}% < {required:.1}%"),
+        }
+    }
+}
+
+/// Check whole-project coverage against `thresholds` (not a diff). Reuses
+/// `thresholds.project_min` against the overall summary and `file_min`
+/// (including any glob `path_overrides`) against every file, for callers
+/// like [`crate::cli::cmd_check`] that gate on the state of the database
+/// rather than on a diff.
+pub fn check_project_thresholds(
+    thresholds: &Thresholds,
+    summary: &crate::model::ReportSummary,
+    files: &[crate::model::FileSummary],
+) -> Vec<ThresholdFailure> {
+    let mut failures = Vec::new();
+
+    if let Some(min) = thresholds.project_min {
+        let actual = summary.line_rate() * 100.0;
+        if actual < min {
+            failures.push(ThresholdFailure::Project {
+                actual,
+                required: min,
+            });
+        }
+    }
+
+    for f in files {
+        if let Some(min) = thresholds.file_min(&f.path) {
+            let actual = f.line_rate() * 100.0;
+            if actual < min {
+                failures.push(ThresholdFailure::File {
+                    path: f.path.clone(),
+                    actual,
+                    required: min,
+                });
+            }
+        }
+    }
+
+    failures
+}
+
+/// Whether `path` should be included given an allow/deny glob list: `path`
+/// must match at least one pattern in `allow` (or `allow` is empty, meaning
+/// "no restriction") and must match none of the patterns in `deny`. Lets
+/// callers keep vendored or generated files out of a printed summary's
+/// totals.
+#[must_use]
+pub fn path_allowed(allow: &[String], deny: &[String], path: &str) -> bool {
+    (allow.is_empty() || allow.iter().any(|g| glob_match(g, path)))
+        && !deny.iter().any(|g| glob_match(g, path))
+}
+
+/// Recompute project-wide line/branch totals from `files` — pair with
+/// [`path_allowed`] so a glob-filtered file list's totals reflect only the
+/// included files rather than the whole database. Function and condition
+/// counts aren't available at file-summary granularity, so they're left at 0.
+#[must_use]
+pub fn summarize(files: &[FileSummary]) -> ReportSummary {
+    ReportSummary {
+        total_files: files.len() as u64,
+        total_lines: files.iter().map(|f| f.total_lines).sum(),
+        covered_lines: files.iter().map(|f| f.covered_lines).sum(),
+        total_branches: files.iter().map(|f| f.total_branches).sum(),
+        covered_branches: files.iter().map(|f| f.covered_branches).sum(),
+        total_functions: 0,
+        covered_functions: 0,
+        total_conditions: 0,
+        independently_covered_conditions: 0,
+    }
 }
 
 /// Trait for formatting diff coverage reports.
@@ -74,6 +407,21 @@ impl ReportFormatter for TextFormatter {
         )
         .unwrap();
 
+        if report.total_branches > 0 {
+            let branch_pct =
+                rate(report.total_branches_covered as u64, report.total_branches as u64) * 100.0;
+            let branch_covered = report.total_branches_covered;
+            let branch_total = report.total_branches;
+            writeln!(
+                out,
+                "Branch coverage: {branch_pct:.1}% ({branch_covered}/{branch_total} decisions covered)"
+            )
+            .unwrap();
+        }
+
+        let branch_totals = report.branch_totals_by_path();
+        let function_totals = report.function_totals_by_path();
+
         let mut files_with_misses: Vec<_> = report
             .files
             .iter()
@@ -89,11 +437,18 @@ impl ReportFormatter for TextFormatter {
                 let path = &f.path;
                 let all_instrumentable = f.all_instrumentable();
                 let missed = format_line_ranges(&f.missed_lines, &all_instrumentable);
-                writeln!(
+                write!(
                     out,
                     "  {path}  {file_covered}/{file_total} ({file_rate:.1}%)  missed: {missed}",
                 )
                 .unwrap();
+                if let Some(&(bc, bt)) = branch_totals.get(path.as_str()) {
+                    write!(out, "  branch {bc}/{bt}").unwrap();
+                }
+                if let Some(&(fc, ft)) = function_totals.get(path.as_str()) {
+                    write!(out, "  fn {fc}/{ft}").unwrap();
+                }
+                out.push('\n');
             }
         }
 
@@ -103,6 +458,13 @@ impl ReportFormatter for TextFormatter {
             writeln!(out, "Full project coverage: {pct:.1}%").unwrap();
         }
 
+        if !report.threshold_failures.is_empty() {
+            out.push('\n');
+            for failure in &report.threshold_failures {
+                writeln!(out, "{failure}").unwrap();
+            }
+        }
+
         out
     }
 }
@@ -130,6 +492,22 @@ impl ReportFormatter for MarkdownFormatter {
         }
         md.push('\n');
 
+        if report.total_branches > 0 {
+            let branch_pct =
+                rate(report.total_branches_covered as u64, report.total_branches as u64) * 100.0;
+            let branch_covered = report.total_branches_covered;
+            let branch_total = report.total_branches;
+            writeln!(
+                md,
+                "**Branch coverage:** {branch_pct:.1}% ({branch_covered}/{branch_total} decisions covered)"
+            )
+            .unwrap();
+        }
+
+        let branch_totals = report.branch_totals_by_path();
+        let function_totals = report.function_totals_by_path();
+        let failing_paths = report.failing_paths();
+
         let mut files_with_misses: Vec<&FileDiffCoverage> = report
             .files
             .iter()
@@ -140,17 +518,30 @@ impl ReportFormatter for MarkdownFormatter {
         if files_with_misses.is_empty() {
             md.push_str("\nAll diff lines are covered! 🎉\n");
         } else {
-            md.push_str("\n| File | Missed | Diff | Total | \n");
-            md.push_str("|:-----|-------:|-----:|------:|\n");
+            md.push_str("\n| File | Missed | Diff | Total | Branch | Fn | \n");
+            md.push_str("|:-----|-------:|-----:|------:|-------:|---:|\n");
 
             for f in &files_with_misses {
                 let file_rate = f.rate() * 100.0;
                 let path = &f.path;
                 let missed_count = f.missed_lines.len();
                 let total_rate = report.file_rates.get(path).copied().unwrap_or(0.0) * 100.0;
+                let branch_col = match branch_totals.get(path.as_str()) {
+                    Some(&(bc, bt)) => format!("{bc}/{bt}"),
+                    None => "-".to_string(),
+                };
+                let fn_col = match function_totals.get(path.as_str()) {
+                    Some(&(fc, ft)) => format!("{fc}/{ft}"),
+                    None => "-".to_string(),
+                };
+                let path_col = if failing_paths.contains(path.as_str()) {
+                    format!("`{path}` ❌")
+                } else {
+                    format!("`{path}`")
+                };
                 writeln!(
                     md,
-                    "| `{path}` | {missed_count} | {file_rate:.0}% | {total_rate:.0}% |"
+                    "| {path_col} | {missed_count} | {file_rate:.0}% | {total_rate:.0}% | {branch_col} | {fn_col} |"
                 )
                 .unwrap();
             }
@@ -176,12 +567,373 @@ impl ReportFormatter for MarkdownFormatter {
             let pct = rate * 100.0;
             writeln!(md, "<sub>Full project coverage: **{pct:.1}%**</sub>").unwrap();
         }
+
+        if !report.threshold_failures.is_empty() {
+            md.push('\n');
+            for failure in &report.threshold_failures {
+                writeln!(md, "> {failure}").unwrap();
+            }
+        }
+
         md.push_str("<sub>[covrs](https://github.com/scttnlsn/covrs)</sub>\n");
 
         md
     }
 }
 
+/// Machine-readable formatter for CI pipelines: one JSON object with the
+/// overall `covered`/`total`/`rate` plus a `per_file` breakdown, so a
+/// pipeline can gate on diff coverage without parsing human-oriented text.
+pub struct JsonFormatter;
+
+impl ReportFormatter for JsonFormatter {
+    fn format(&self, report: &DiffCoverageReport) -> String {
+        let per_file: Vec<serde_json::Value> = report
+            .files
+            .iter()
+            .map(|f| {
+                serde_json::json!({
+                    "path": f.path,
+                    "covered": f.covered_lines.len(),
+                    "total": f.total(),
+                    "rate": f.rate(),
+                })
+            })
+            .collect();
+
+        let threshold_failures: Vec<String> = report
+            .threshold_failures
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        let body = serde_json::json!({
+            "covered": report.total_covered,
+            "total": report.total_instrumentable,
+            "rate": rate(report.total_covered as u64, report.total_instrumentable as u64),
+            "per_file": per_file,
+            "threshold_failures": threshold_failures,
+        });
+
+        serde_json::to_string_pretty(&body).expect("diff coverage JSON serialization is infallible")
+    }
+}
+
+/// Trait for formatting project-wide summary reports (see
+/// [`crate::db::get_summary`] and [`crate::db::get_file_summaries`]), mirroring
+/// [`ReportFormatter`] but over the whole project rather than a diff.
+pub trait SummaryFormatter {
+    /// Format the summary plus per-file table to a string.
+    fn format(&self, summary: &ReportSummary, files: &[FileSummary]) -> String;
+}
+
+/// Sort files worst-covered-first, matching the diff formatters' convention
+/// of surfacing the files that need the most attention up top.
+fn sorted_by_line_rate(files: &[FileSummary]) -> Vec<&FileSummary> {
+    let mut files: Vec<&FileSummary> = files.iter().collect();
+    files.sort_by(|a, b| a.line_rate().total_cmp(&b.line_rate()));
+    files
+}
+
+/// Plain text project summary formatter: an aligned per-file table (path,
+/// line %, branch %) with a totals footer.
+pub struct TextSummaryFormatter;
+
+impl SummaryFormatter for TextSummaryFormatter {
+    fn format(&self, summary: &ReportSummary, files: &[FileSummary]) -> String {
+        let mut out = String::new();
+        let files = sorted_by_line_rate(files);
+
+        writeln!(out, "{:<60} {:>8} {:>10}", "FILE", "LINE %", "BRANCH %").unwrap();
+        writeln!(out, "{}", "-".repeat(80)).unwrap();
+        for f in &files {
+            let branch_col = if f.total_branches > 0 {
+                format!("{:.1}%", rate(f.covered_branches, f.total_branches) * 100.0)
+            } else {
+                "-".to_string()
+            };
+            writeln!(
+                out,
+                "{:<60} {:>7.1}% {:>10}",
+                f.path,
+                f.line_rate() * 100.0,
+                branch_col
+            )
+            .unwrap();
+        }
+
+        out.push('\n');
+        writeln!(
+            out,
+            "TOTAL  lines {}/{} ({:.1}%)",
+            summary.covered_lines,
+            summary.total_lines,
+            summary.line_rate() * 100.0
+        )
+        .unwrap();
+        if summary.total_branches > 0 {
+            writeln!(
+                out,
+                "       branches {}/{} ({:.1}%)",
+                summary.covered_branches,
+                summary.total_branches,
+                summary.branch_rate() * 100.0
+            )
+            .unwrap();
+        }
+        if summary.total_functions > 0 {
+            writeln!(
+                out,
+                "       functions {}/{} ({:.1}%)",
+                summary.covered_functions,
+                summary.total_functions,
+                summary.function_rate() * 100.0
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+/// Markdown project summary formatter: a per-file table (path, line %,
+/// branch %) with a totals line.
+pub struct MarkdownSummaryFormatter;
+
+impl SummaryFormatter for MarkdownSummaryFormatter {
+    fn format(&self, summary: &ReportSummary, files: &[FileSummary]) -> String {
+        let mut md = String::new();
+        let files = sorted_by_line_rate(files);
+
+        writeln!(md, "### Coverage Summary\n").unwrap();
+        md.push_str("| File | Line % | Branch % |\n");
+        md.push_str("|:-----|-------:|---------:|\n");
+        for f in &files {
+            let branch_col = if f.total_branches > 0 {
+                format!("{:.1}%", rate(f.covered_branches, f.total_branches) * 100.0)
+            } else {
+                "-".to_string()
+            };
+            writeln!(
+                md,
+                "| `{}` | {:.1}% | {} |",
+                f.path,
+                f.line_rate() * 100.0,
+                branch_col
+            )
+            .unwrap();
+        }
+
+        md.push('\n');
+        write!(md, "**Total:** {:.1}% lines", summary.line_rate() * 100.0).unwrap();
+        if summary.total_branches > 0 {
+            write!(md, ", {:.1}% branches", summary.branch_rate() * 100.0).unwrap();
+        }
+        if summary.total_functions > 0 {
+            write!(md, ", {:.1}% functions", summary.function_rate() * 100.0).unwrap();
+        }
+        md.push('\n');
+
+        md
+    }
+}
+
+/// ANSI color codes for terminal output, thresholded the same way most
+/// coverage tools band their summary tables: green at/above 80%, yellow
+/// at/above 50%, red below.
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Wrap `text` in the ANSI color matching `pct`'s coverage band.
+fn colorize(pct: f64, text: &str) -> String {
+    let color = if pct >= 80.0 {
+        ANSI_GREEN
+    } else if pct >= 50.0 {
+        ANSI_YELLOW
+    } else {
+        ANSI_RED
+    };
+    format!("{color}{text}{ANSI_RESET}")
+}
+
+/// Terminal project summary formatter: like [`TextSummaryFormatter`], but
+/// each percentage is ANSI-colored by coverage band (green/yellow/red) so a
+/// run of `covrs summary` stands out at a glance the way `jest --coverage`
+/// or `go tool cover`'s colored terminal output does.
+pub struct ColorSummaryFormatter;
+
+impl SummaryFormatter for ColorSummaryFormatter {
+    fn format(&self, summary: &ReportSummary, files: &[FileSummary]) -> String {
+        let mut out = String::new();
+        let files = sorted_by_line_rate(files);
+
+        writeln!(out, "{:<60} {:>8} {:>10}", "FILE", "LINE %", "BRANCH %").unwrap();
+        writeln!(out, "{}", "-".repeat(80)).unwrap();
+        for f in &files {
+            let line_pct = f.line_rate() * 100.0;
+            let line_col = colorize(line_pct, &format!("{line_pct:.1}%"));
+            let branch_col = if f.total_branches > 0 {
+                let branch_pct = rate(f.covered_branches, f.total_branches) * 100.0;
+                colorize(branch_pct, &format!("{branch_pct:.1}%"))
+            } else {
+                "-".to_string()
+            };
+            writeln!(out, "{:<60} {:>7} {:>10}", f.path, line_col, branch_col).unwrap();
+        }
+
+        out.push('\n');
+        let total_pct = summary.line_rate() * 100.0;
+        writeln!(
+            out,
+            "TOTAL  lines {}/{} ({})",
+            summary.covered_lines,
+            summary.total_lines,
+            colorize(total_pct, &format!("{total_pct:.1}%"))
+        )
+        .unwrap();
+        if summary.total_branches > 0 {
+            let branch_pct = summary.branch_rate() * 100.0;
+            writeln!(
+                out,
+                "       branches {}/{} ({})",
+                summary.covered_branches,
+                summary.total_branches,
+                colorize(branch_pct, &format!("{branch_pct:.1}%"))
+            )
+            .unwrap();
+        }
+        if summary.total_functions > 0 {
+            let function_pct = summary.function_rate() * 100.0;
+            writeln!(
+                out,
+                "       functions {}/{} ({})",
+                summary.covered_functions,
+                summary.total_functions,
+                colorize(function_pct, &format!("{function_pct:.1}%"))
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+/// Renders a single source file annotated with each line's hit count —
+/// analogous to Deno's `pretty` coverage reporter. Each line is prefixed
+/// with its hit count and a ✓/✗ marker highlighting whether it was
+/// exercised; lines with no matching [`LineDetail`] (blank lines, comments,
+/// braces) still print, but with a blank count column, so the output reads
+/// as the whole file rather than just the instrumented subset.
+pub struct PrettyFormatter;
+
+impl PrettyFormatter {
+    /// Format `source_text` using per-line hit counts from `lines` (e.g.
+    /// from [`crate::db::get_lines`]).
+    #[must_use]
+    pub fn format(&self, source_text: &str, lines: &[LineDetail]) -> String {
+        let hits: HashMap<u32, u64> = lines.iter().map(|l| (l.line_number, l.hit_count)).collect();
+
+        let mut out = String::new();
+        writeln!(out, "{:>6}  {:>6}", "LINE", "HITS").unwrap();
+        writeln!(out, "{}", "-".repeat(18)).unwrap();
+        for (i, text) in source_text.lines().enumerate() {
+            let line_number = (i + 1) as u32;
+            match hits.get(&line_number) {
+                Some(&hit_count) => {
+                    let marker = if hit_count > 0 { "✓" } else { "✗" };
+                    writeln!(out, "{line_number:>6}  {hit_count:>6}  {marker}  {text}").unwrap();
+                }
+                None => {
+                    writeln!(out, "{line_number:>6}  {:>6}     {text}", "").unwrap();
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Default number of source lines of context kept around each run of missed
+/// lines by [`HunkFormatter`], mirroring a typical diff hunk.
+const DEFAULT_HUNK_CONTEXT: u32 = 2;
+
+/// Renders only the missed lines of a source file, each with a few lines of
+/// surrounding context — like a diff hunk view, but for coverage misses
+/// rather than changes. Hunks whose context windows touch or overlap are
+/// merged into one; hunks are separated by an ellipsis marker.
+pub struct HunkFormatter {
+    /// How many source lines of context to keep before/after each missed
+    /// line.
+    pub context: u32,
+}
+
+impl Default for HunkFormatter {
+    fn default() -> Self {
+        Self {
+            context: DEFAULT_HUNK_CONTEXT,
+        }
+    }
+}
+
+impl HunkFormatter {
+    /// Format `source_text` to show only the hunks surrounding lines with a
+    /// zero hit count in `lines` (e.g. from [`crate::db::get_lines`]).
+    /// Returns an empty string when nothing is missed.
+    #[must_use]
+    pub fn format(&self, source_text: &str, lines: &[LineDetail]) -> String {
+        let hits: HashMap<u32, u64> = lines.iter().map(|l| (l.line_number, l.hit_count)).collect();
+        let source_lines: Vec<&str> = source_text.lines().collect();
+        let total_lines = source_lines.len() as u32;
+
+        // Lines beyond the current file's length (stale coverage data
+        // relative to the source on disk) can't be rendered — drop them
+        // rather than letting a dangling, empty hunk slip through.
+        let missed: Vec<u32> = lines
+            .iter()
+            .filter(|l| l.hit_count == 0 && l.line_number <= total_lines)
+            .map(|l| l.line_number)
+            .collect();
+        if missed.is_empty() {
+            return String::new();
+        }
+
+        // Expand each missed line by `context` lines on either side, then
+        // merge ranges that touch or overlap so adjacent misses share one
+        // hunk instead of printing the same context lines twice.
+        let mut hunks: Vec<(u32, u32)> = Vec::new();
+        for &line_number in &missed {
+            let start = line_number.saturating_sub(self.context).max(1);
+            let end = (line_number + self.context).min(total_lines.max(1));
+            match hunks.last_mut() {
+                Some((_, last_end)) if start <= *last_end + 1 => {
+                    *last_end = end.max(*last_end);
+                }
+                _ => hunks.push((start, end)),
+            }
+        }
+
+        let mut out = String::new();
+        for (i, (start, end)) in hunks.iter().enumerate() {
+            if i > 0 {
+                writeln!(out, "  ...").unwrap();
+            }
+            for line_number in *start..=*end {
+                let Some(text) = source_lines.get((line_number - 1) as usize) else {
+                    continue;
+                };
+                let (hit_str, marker) = match hits.get(&line_number) {
+                    Some(&count) if count > 0 => (count.to_string(), " "),
+                    Some(_) => ("0".to_string(), "✗"),
+                    None => (String::new(), " "),
+                };
+                writeln!(out, "{line_number:>6}  {hit_str:>6}  {marker}  {text}").unwrap();
+            }
+        }
+        out
+    }
+}
+
 /// Build a [`DiffCoverageReport`] from parsed diff lines and a database connection.
 pub fn build_report(
     conn: &rusqlite::Connection,
@@ -194,10 +946,22 @@ pub fn build_report(
     let (files, total_covered, total_instrumentable) = if diff_lines.is_empty() {
         (vec![], 0, 0)
     } else {
-        crate::db::diff_coverage(conn, diff_lines)?
+        crate::db::diff_coverage(conn, diff_lines, crate::db::MergeMode::Union)?
+    };
+
+    let (branch_files, total_branches_covered, total_branches) = if diff_lines.is_empty() {
+        (vec![], 0, 0)
+    } else {
+        crate::db::diff_branch_coverage(conn, diff_lines, crate::db::MergeMode::Union)?
+    };
+
+    let (function_files, total_functions_covered, total_functions) = if diff_lines.is_empty() {
+        (vec![], 0, 0)
+    } else {
+        crate::db::diff_function_coverage(conn, diff_lines, crate::db::MergeMode::Union)?
     };
 
-    let total_rate = match crate::db::get_summary(conn) {
+    let total_rate = match crate::db::get_summary(conn, crate::db::MergeMode::Union) {
         Ok(s) if s.total_lines > 0 => Some(s.line_rate()),
         Ok(_) => None,
         Err(e) => {
@@ -208,7 +972,7 @@ pub fn build_report(
 
     let mut file_rates = HashMap::new();
     for f in &files {
-        match crate::db::get_file_line_rate(conn, &f.path) {
+        match crate::db::get_file_line_rate(conn, &f.path, crate::db::MergeMode::Union) {
             Ok(Some(r)) => {
                 file_rates.insert(f.path.clone(), r);
             }
@@ -219,16 +983,238 @@ pub fn build_report(
         }
     }
 
-    Ok(DiffCoverageReport {
-        diff_files,
-        diff_lines: diff_line_count,
-        files,
-        total_covered,
-        total_instrumentable,
-        total_rate,
-        file_rates,
-        sha: sha.map(|s| s.to_owned()),
-    })
+    Ok(DiffCoverageReport {
+        diff_files,
+        diff_lines: diff_line_count,
+        files,
+        total_covered,
+        total_instrumentable,
+        branch_files,
+        total_branches_covered,
+        total_branches,
+        function_files,
+        total_functions_covered,
+        total_functions,
+        total_rate,
+        file_rates,
+        sha: sha.map(|s| s.to_owned()),
+        threshold_failures: Vec::new(),
+    })
+}
+
+/// Write a browsable static HTML coverage report to `out_dir`: an
+/// `index.html` page listing files grouped by directory with aggregated
+/// line rates, plus one annotated page per source file rendering the real
+/// source with each line colored hit/miss and a per-line hit count in the
+/// gutter. Mirrors Deno's `HtmlCoverageReporter`.
+///
+/// When `report_name` is `Some`, the report is scoped to that single stored
+/// report (via [`crate::db::get_report_coverage`]); when `None`, it covers
+/// every report in the database, unioned (see [`crate::db::MergeMode`]).
+///
+/// Source text for each file page is read from disk at `path` (joined
+/// under `root` if given, same convention as [`crate::exclude::ExclusionRules::apply`]);
+/// a file whose source can't be read still gets a page (and stays in the
+/// index, since its coverage data is known) showing a "source unavailable"
+/// placeholder instead of failing the whole report.
+pub fn write_html_report(
+    conn: &rusqlite::Connection,
+    out_dir: &std::path::Path,
+    root: Option<&std::path::Path>,
+    report_name: Option<&str>,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+
+    let (files, hits_by_path) = match report_name {
+        Some(name) => {
+            let data = crate::db::get_report_coverage(conn, name)?;
+            let mut files = Vec::new();
+            let mut hits_by_path = HashMap::new();
+            for file in &data.files {
+                files.push(crate::model::FileSummary {
+                    path: file.path.clone(),
+                    total_lines: file.lines.len() as u64,
+                    covered_lines: file.lines.iter().filter(|l| l.hit_count > 0).count() as u64,
+                    total_branches: file.branches.len() as u64,
+                    covered_branches: file
+                        .branches
+                        .iter()
+                        .filter(|b| b.hit_count > 0)
+                        .count() as u64,
+                });
+                hits_by_path.insert(
+                    file.path.clone(),
+                    file.lines
+                        .iter()
+                        .map(|l| (l.line_number, l.hit_count))
+                        .collect::<HashMap<u32, u64>>(),
+                );
+            }
+            (files, hits_by_path)
+        }
+        None => {
+            let files = crate::db::get_file_summaries(conn, crate::db::MergeMode::Union)?;
+            let mut hits_by_path = HashMap::new();
+            for f in &files {
+                let hits = crate::db::get_lines(conn, &f.path, crate::db::MergeMode::Union)?
+                    .into_iter()
+                    .map(|l| (l.line_number, l.hit_count))
+                    .collect();
+                hits_by_path.insert(f.path.clone(), hits);
+            }
+            (files, hits_by_path)
+        }
+    };
+
+    let mut written: Vec<&crate::model::FileSummary> = Vec::new();
+    for f in &files {
+        let hits = hits_by_path.get(&f.path).cloned().unwrap_or_default();
+        write_html_file_page(out_dir, &f.path, &hits, root)?;
+        written.push(f);
+    }
+
+    write_html_index_page(out_dir, &written)?;
+
+    Ok(())
+}
+
+/// Name of the HTML page for a given source path, flattened so every file
+/// gets a unique sibling page in `out_dir` regardless of directory depth.
+fn html_page_name(source_path: &str) -> String {
+    format!("{}.html", source_path.replace(['/', '\\'], "_"))
+}
+
+fn write_html_file_page(
+    out_dir: &std::path::Path,
+    source_path: &str,
+    hits: &HashMap<u32, u64>,
+    root: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    let path = match root {
+        Some(root) => root.join(source_path),
+        None => std::path::Path::new(source_path).to_path_buf(),
+    };
+    let source = std::fs::read_to_string(&path).ok();
+
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>",
+        html_escape(source_path)
+    )
+    .unwrap();
+    html.push_str(
+        "<style>\n\
+         body { font-family: monospace; }\n\
+         .line { display: flex; }\n\
+         .gutter { width: 4em; text-align: right; padding-right: 1em; color: #888; }\n\
+         .hit { background: #e6ffed; }\n\
+         .miss { background: #ffeef0; }\n\
+         .src { white-space: pre; }\n\
+         .unavailable { color: #888; font-style: italic; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    writeln!(html, "<h1>{}</h1>", html_escape(source_path)).unwrap();
+
+    match source {
+        Some(source) => {
+            html.push_str("<div class=\"code\">\n");
+            for (idx, text) in source.lines().enumerate() {
+                let line_number = (idx + 1) as u32;
+                let (class, gutter) = match hits.get(&line_number) {
+                    Some(&count) if count > 0 => ("hit", count.to_string()),
+                    Some(_) => ("miss", "0".to_string()),
+                    None => ("", String::new()),
+                };
+                writeln!(
+                    html,
+                    "<div class=\"line {class}\"><span class=\"gutter\">{gutter}</span><span class=\"src\">{}</span></div>",
+                    html_escape(text)
+                )
+                .unwrap();
+            }
+            html.push_str("</div>\n");
+        }
+        None => {
+            html.push_str("<p class=\"unavailable\">Source unavailable.</p>\n");
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+
+    let page_path = out_dir.join(html_page_name(source_path));
+    std::fs::write(&page_path, html)
+        .with_context(|| format!("Failed to write {}", page_path.display()))?;
+
+    Ok(())
+}
+
+fn write_html_index_page(
+    out_dir: &std::path::Path,
+    files: &[&crate::model::FileSummary],
+) -> anyhow::Result<()> {
+    let mut by_dir: std::collections::BTreeMap<String, Vec<&crate::model::FileSummary>> =
+        std::collections::BTreeMap::new();
+    for f in files {
+        let dir = std::path::Path::new(&f.path)
+            .parent()
+            .map(|p| p.to_string_lossy().into_owned())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+        by_dir.entry(dir).or_default().push(f);
+    }
+
+    let mut html = String::new();
+    html.push_str(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Coverage Report</title>\n",
+    );
+    html.push_str(
+        "<style>\n\
+         body { font-family: monospace; }\n\
+         table { border-collapse: collapse; margin-bottom: 1em; }\n\
+         td, th { padding: 2px 8px; text-align: left; }\n\
+         .dir { font-weight: bold; margin-top: 1em; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    html.push_str("<h1>Coverage Report</h1>\n");
+
+    for (dir, files) in &by_dir {
+        writeln!(html, "<div class=\"dir\">{}</div>", html_escape(dir)).unwrap();
+        html.push_str("<table>\n<tr><th>File</th><th>Lines</th><th>Rate</th></tr>\n");
+
+        let mut files = files.clone();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        for f in &files {
+            let name = std::path::Path::new(&f.path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| f.path.clone());
+            writeln!(
+                html,
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}/{}</td><td>{:.1}%</td></tr>",
+                html_page_name(&f.path),
+                html_escape(&name),
+                f.covered_lines,
+                f.total_lines,
+                f.line_rate() * 100.0,
+            )
+            .unwrap();
+        }
+
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+
+    std::fs::write(out_dir.join("index.html"), html)?;
+
+    Ok(())
+}
+
+/// Escape the characters that are meaningful in HTML text content.
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
 }
 
 /// Maximum number of consecutive non-instrumentable lines that can be bridged
@@ -326,10 +1312,312 @@ pub fn format_line_ranges(lines: &[u32], all_instrumentable: &[u32]) -> String {
         .join(", ")
 }
 
+/// Render a [`crate::compare::CoverageDelta`] (`covrs compare --base/--head`):
+/// the aggregate line/branch/function rate change, then a per-file
+/// breakdown of files whose coverage moved, with newly covered/missed line
+/// ranges so a reviewer can see exactly what flipped between the two
+/// reports.
+#[must_use]
+pub fn format_compare(delta: &crate::compare::CoverageDelta) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "Line coverage:     {:.1}% -> {:.1}% ({})",
+        delta.before_rate * 100.0,
+        delta.after_rate * 100.0,
+        signed_pct(delta.change())
+    )
+    .unwrap();
+
+    if let Some(change) = delta.branch_change() {
+        writeln!(
+            out,
+            "Branch coverage:   {:.1}% -> {:.1}% ({})",
+            delta.before_branch_rate.unwrap() * 100.0,
+            delta.after_branch_rate.unwrap() * 100.0,
+            signed_pct(change)
+        )
+        .unwrap();
+    }
+
+    if let Some(change) = delta.function_change() {
+        writeln!(
+            out,
+            "Function coverage: {:.1}% -> {:.1}% ({})",
+            delta.before_function_rate.unwrap() * 100.0,
+            delta.after_function_rate.unwrap() * 100.0,
+            signed_pct(change)
+        )
+        .unwrap();
+    }
+
+    let mut changed: Vec<&crate::compare::FileCoverageDelta> = delta
+        .files
+        .iter()
+        .filter(|f| {
+            f.before != f.after
+                || f.branch_before != f.branch_after
+                || f.function_before != f.function_after
+                || !f.newly_covered.is_empty()
+                || !f.newly_missed.is_empty()
+        })
+        .collect();
+    changed.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if !changed.is_empty() {
+        out.push('\n');
+        for f in &changed {
+            let path = &f.path;
+            let rate_col = match (f.before, f.after) {
+                (Some(before), Some(after)) => {
+                    format!("{:.1}% -> {:.1}%", before * 100.0, after * 100.0)
+                }
+                (None, Some(after)) => format!("new file, {:.1}%", after * 100.0),
+                (Some(before), None) => format!("removed (was {:.1}%)", before * 100.0),
+                (None, None) => "-".to_string(),
+            };
+            writeln!(out, "  {path}  {rate_col}").unwrap();
+            if let Some(line) = rate_change_line("branch", f.branch_before, f.branch_after) {
+                writeln!(out, "    {line}").unwrap();
+            }
+            if let Some(line) = rate_change_line("function", f.function_before, f.function_after) {
+                writeln!(out, "    {line}").unwrap();
+            }
+            if !f.newly_covered.is_empty() {
+                let ranges = format_consecutive_line_ranges(&f.newly_covered);
+                writeln!(out, "    newly covered: {ranges}").unwrap();
+            }
+            if !f.newly_missed.is_empty() {
+                let ranges = format_consecutive_line_ranges(&f.newly_missed);
+                writeln!(out, "    newly missed:  {ranges}").unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+/// Render a per-file branch/function rate change line for [`format_compare`],
+/// or `None` when `before`/`after` are equal (including both absent). Covers
+/// the file-had-no-data-on-one-side case (e.g. a file's first branch, or its
+/// last) as `n/a -> X%`/`X% -> n/a`, not just the Some/Some case, so a file
+/// that's only listed as "changed" because of a branch/function
+/// availability flip still gets an explanation rather than a bare path.
+fn rate_change_line(label: &str, before: Option<f64>, after: Option<f64>) -> Option<String> {
+    if before == after {
+        return None;
+    }
+    let value = match (before, after) {
+        (Some(b), Some(a)) => format!("{:.1}% -> {:.1}%", b * 100.0, a * 100.0),
+        (None, Some(a)) => format!("n/a -> {:.1}%", a * 100.0),
+        (Some(b), None) => format!("{:.1}% -> n/a", b * 100.0),
+        (None, None) => unreachable!("before == after already handled"),
+    };
+    Some(format!("{:<10}{value}", format!("{label}:")))
+}
+
+/// Format sorted line numbers into compact range notation, bridging only
+/// strictly consecutive numbers (no gap-bridging against a known
+/// instrumentable set — unlike [`format_line_ranges`]) — used for
+/// [`format_compare`]'s newly-covered/newly-missed sets, where there's no
+/// "all instrumentable lines" list to bridge gaps against honestly.
+fn format_consecutive_line_ranges(lines: &[u32]) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    let mut start = lines[0];
+    let mut end = lines[0];
+    for &line in &lines[1..] {
+        if line == end + 1 {
+            end = line;
+        } else {
+            ranges.push((start, end));
+            start = line;
+            end = line;
+        }
+    }
+    ranges.push((start, end));
+
+    ranges
+        .iter()
+        .map(|&(s, e)| if s == e { s.to_string() } else { format!("{s}-{e}") })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Render a percentage-point change with an explicit `+` sign on
+/// improvements, matching the convention used throughout this module for
+/// diff/delta numbers.
+fn signed_pct(change: f64) -> String {
+    if change >= 0.0 {
+        format!("+{change:.1}")
+    } else {
+        format!("{change:.1}")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // -- path_allowed / summarize tests ---------------------------------------
+
+    #[test]
+    fn test_path_allowed_with_no_filters() {
+        assert!(path_allowed(&[], &[], "src/main.rs"));
+    }
+
+    #[test]
+    fn test_path_allowed_deny_wins() {
+        let deny = vec!["vendor/**".to_string()];
+        assert!(!path_allowed(&[], &deny, "vendor/lib.rs"));
+        assert!(path_allowed(&[], &deny, "src/main.rs"));
+    }
+
+    #[test]
+    fn test_path_allowed_allow_list_is_a_whitelist() {
+        let allow = vec!["src/**".to_string()];
+        assert!(path_allowed(&allow, &[], "src/main.rs"));
+        assert!(!path_allowed(&allow, &[], "vendor/lib.rs"));
+    }
+
+    #[test]
+    fn test_summarize_sums_filtered_files() {
+        let files = vec![
+            FileSummary {
+                path: "src/good.rs".to_string(),
+                total_lines: 5,
+                covered_lines: 5,
+                total_branches: 0,
+                covered_branches: 0,
+            },
+            FileSummary {
+                path: "src/bad.rs".to_string(),
+                total_lines: 5,
+                covered_lines: 2,
+                total_branches: 4,
+                covered_branches: 2,
+            },
+        ];
+
+        let summary = summarize(&files);
+        assert_eq!(summary.total_files, 2);
+        assert_eq!(summary.total_lines, 10);
+        assert_eq!(summary.covered_lines, 7);
+        assert_eq!(summary.total_branches, 4);
+        assert_eq!(summary.covered_branches, 2);
+        assert_eq!(summary.total_functions, 0);
+    }
+
+    // -- PrettyFormatter tests -------------------------------------------------
+
+    #[test]
+    fn test_pretty_formatter_marks_covered_and_uncovered_lines() {
+        let source = "fn main() {\n    println!(\"hi\");\n}\n";
+        let lines = vec![
+            LineDetail {
+                line_number: 1,
+                hit_count: 1,
+            },
+            LineDetail {
+                line_number: 2,
+                hit_count: 0,
+            },
+        ];
+
+        let out = PrettyFormatter.format(source, &lines);
+        let line1 = out.lines().find(|l| l.contains("fn main")).unwrap();
+        let line2 = out.lines().find(|l| l.contains("println!")).unwrap();
+        let line3 = out.lines().find(|l| l.trim_end().ends_with('}')).unwrap();
+
+        assert!(line1.contains('✓'));
+        assert!(line2.contains('✗'));
+        assert!(!line3.contains('✓') && !line3.contains('✗'));
+    }
+
+    // -- SummaryFormatter tests -----------------------------------------------
+
+    fn sample_summary() -> (ReportSummary, Vec<FileSummary>) {
+        let summary = ReportSummary {
+            total_files: 2,
+            total_lines: 10,
+            covered_lines: 7,
+            total_branches: 4,
+            covered_branches: 2,
+            total_functions: 2,
+            covered_functions: 1,
+            total_conditions: 0,
+            independently_covered_conditions: 0,
+        };
+        let files = vec![
+            FileSummary {
+                path: "src/good.rs".to_string(),
+                total_lines: 5,
+                covered_lines: 5,
+                total_branches: 0,
+                covered_branches: 0,
+            },
+            FileSummary {
+                path: "src/bad.rs".to_string(),
+                total_lines: 5,
+                covered_lines: 2,
+                total_branches: 4,
+                covered_branches: 2,
+            },
+        ];
+        (summary, files)
+    }
+
+    #[test]
+    fn test_text_summary_formatter_sorts_worst_first() {
+        let (summary, files) = sample_summary();
+        let out = TextSummaryFormatter.format(&summary, &files);
+
+        let bad_pos = out.find("src/bad.rs").unwrap();
+        let good_pos = out.find("src/good.rs").unwrap();
+        assert!(bad_pos < good_pos);
+        assert!(out.contains("50.0%"));
+        assert!(out.contains("TOTAL  lines 7/10 (70.0%)"));
+        assert!(out.contains("branches 2/4 (50.0%)"));
+        assert!(out.contains("functions 1/2 (50.0%)"));
+    }
+
+    #[test]
+    fn test_text_summary_formatter_dash_when_no_branches() {
+        let (summary, files) = sample_summary();
+        let out = TextSummaryFormatter.format(&summary, &files);
+
+        let good_line = out.lines().find(|l| l.contains("src/good.rs")).unwrap();
+        assert!(good_line.trim_end().ends_with('-'));
+    }
+
+    #[test]
+    fn test_markdown_summary_formatter() {
+        let (summary, files) = sample_summary();
+        let md = MarkdownSummaryFormatter.format(&summary, &files);
+
+        assert!(md.contains("| File | Line % | Branch % |"));
+        assert!(md.contains("| `src/bad.rs` | 40.0% | 50.0% |"));
+        assert!(md.contains("| `src/good.rs` | 100.0% | - |"));
+        assert!(md.contains("**Total:** 70.0% lines, 50.0% branches, 50.0% functions"));
+    }
+
+    #[test]
+    fn test_color_summary_formatter_bands_percentages() {
+        let (summary, files) = sample_summary();
+        let out = ColorSummaryFormatter.format(&summary, &files);
+
+        // src/good.rs is 100% -> green, src/bad.rs is 40% -> red.
+        assert!(out.contains(&format!("{ANSI_GREEN}100.0%{ANSI_RESET}")));
+        assert!(out.contains(&format!("{ANSI_RED}40.0%{ANSI_RESET}")));
+        // bad.rs's branch rate (50%) and the overall totals (70%/50%/50%) land in yellow.
+        assert!(out.contains(&format!("{ANSI_YELLOW}50.0%{ANSI_RESET}")));
+        assert!(out.contains(&format!("TOTAL  lines 7/10 ({ANSI_YELLOW}70.0%{ANSI_RESET})")));
+    }
+
     // -- coalesce_ranges tests -----------------------------------------------
 
     #[test]
@@ -473,9 +1761,16 @@ mod tests {
             files: vec![],
             total_covered: 10,
             total_instrumentable: 10,
+            branch_files: vec![],
+            total_branches_covered: 0,
+            total_branches: 0,
+            function_files: vec![],
+            total_functions_covered: 0,
+            total_functions: 0,
             total_rate: Some(0.85),
             file_rates: HashMap::new(),
             sha: Some("abc1234def".to_string()),
+            threshold_failures: Vec::new(),
         };
         let body = report.format(&MarkdownFormatter);
         assert!(body.contains("Diff Coverage: 100.0%"));
@@ -497,9 +1792,16 @@ mod tests {
             }],
             total_covered: 3,
             total_instrumentable: 5,
+            branch_files: vec![],
+            total_branches_covered: 0,
+            total_branches: 0,
+            function_files: vec![],
+            total_functions_covered: 0,
+            total_functions: 0,
             total_rate: None,
             file_rates: HashMap::from([("src/foo.rs".to_string(), 0.75)]),
             sha: None,
+            threshold_failures: Vec::new(),
         };
         let body = report.format(&MarkdownFormatter);
         assert!(body.contains("60.0%"));
@@ -522,14 +1824,106 @@ mod tests {
             }],
             total_covered: 3,
             total_instrumentable: 5,
+            branch_files: vec![],
+            total_branches_covered: 0,
+            total_branches: 0,
+            function_files: vec![],
+            total_functions_covered: 0,
+            total_functions: 0,
             total_rate: None,
             file_rates: HashMap::new(),
             sha: Some("abc1234def".to_string()),
+            threshold_failures: Vec::new(),
         };
         let body = report.format(&MarkdownFormatter);
         assert!(body.contains("[5-6](../blob/abc1234def/src/foo.rs#L5-L6)"));
     }
 
+    #[test]
+    fn test_format_json_shape() {
+        let report = DiffCoverageReport {
+            diff_files: 1,
+            diff_lines: 5,
+            files: vec![FileDiffCoverage {
+                path: "src/foo.rs".to_string(),
+                covered_lines: vec![1, 2, 3],
+                missed_lines: vec![5, 6],
+            }],
+            total_covered: 3,
+            total_instrumentable: 5,
+            branch_files: vec![],
+            total_branches_covered: 0,
+            total_branches: 0,
+            function_files: vec![],
+            total_functions_covered: 0,
+            total_functions: 0,
+            total_rate: None,
+            file_rates: HashMap::new(),
+            sha: None,
+            threshold_failures: vec![ThresholdFailure::Diff {
+                actual: 60.0,
+                required: 90.0,
+            }],
+        };
+        let body = report.format(&JsonFormatter);
+        let parsed: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(parsed["covered"], 3);
+        assert_eq!(parsed["total"], 5);
+        assert_eq!(parsed["rate"], 0.6);
+        let per_file = parsed["per_file"].as_array().unwrap();
+        assert_eq!(per_file.len(), 1);
+        assert_eq!(per_file[0]["path"], "src/foo.rs");
+        assert_eq!(per_file[0]["covered"], 3);
+        assert_eq!(per_file[0]["total"], 5);
+        let failures = parsed["threshold_failures"].as_array().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].as_str().unwrap().contains("90"));
+    }
+
+    #[test]
+    fn test_format_with_branch_and_function_coverage() {
+        let report = DiffCoverageReport {
+            diff_files: 1,
+            diff_lines: 5,
+            files: vec![FileDiffCoverage {
+                path: "src/foo.rs".to_string(),
+                covered_lines: vec![1, 2, 3],
+                missed_lines: vec![5, 6],
+            }],
+            total_covered: 3,
+            total_instrumentable: 5,
+            branch_files: vec![FileBranchDiffCoverage {
+                path: "src/foo.rs".to_string(),
+                covered_lines: vec![1],
+                missed_lines: vec![2],
+                partial: vec![],
+            }],
+            total_branches_covered: 1,
+            total_branches: 2,
+            function_files: vec![FileFunctionDiffCoverage {
+                path: "src/foo.rs".to_string(),
+                covered_lines: vec![1],
+                missed_lines: vec![],
+            }],
+            total_functions_covered: 1,
+            total_functions: 1,
+            total_rate: None,
+            file_rates: HashMap::new(),
+            sha: None,
+            threshold_failures: Vec::new(),
+        };
+
+        let text = report.format(&TextFormatter);
+        assert!(text.contains("Branch coverage: 50.0% (1/2 decisions covered)"));
+        assert!(text.contains("branch 1/2"));
+        assert!(text.contains("fn 1/1"));
+
+        let md = report.format(&MarkdownFormatter);
+        assert!(md.contains("**Branch coverage:** 50.0% (1/2 decisions covered)"));
+        assert!(md.contains("| `src/foo.rs` | 2 | 60% | 0% | 1/2 | 1/1 |"));
+    }
+
     #[test]
     fn test_format_with_trait() {
         let report = DiffCoverageReport {
@@ -538,9 +1932,16 @@ mod tests {
             files: vec![],
             total_covered: 5,
             total_instrumentable: 5,
+            branch_files: vec![],
+            total_branches_covered: 0,
+            total_branches: 0,
+            function_files: vec![],
+            total_functions_covered: 0,
+            total_functions: 0,
             total_rate: None,
             file_rates: HashMap::new(),
             sha: None,
+            threshold_failures: Vec::new(),
         };
 
         // Test using the trait directly
@@ -550,4 +1951,314 @@ mod tests {
         let md = report.format(&MarkdownFormatter);
         assert!(md.contains("Diff Coverage: 100.0%"));
     }
+
+    // -- Thresholds tests -----------------------------------------------------
+
+    fn report_for_thresholds() -> DiffCoverageReport {
+        DiffCoverageReport {
+            diff_files: 1,
+            diff_lines: 5,
+            files: vec![FileDiffCoverage {
+                path: "src/foo.rs".to_string(),
+                covered_lines: vec![1, 2, 3],
+                missed_lines: vec![5, 6],
+            }],
+            total_covered: 3,
+            total_instrumentable: 5,
+            branch_files: vec![],
+            total_branches_covered: 0,
+            total_branches: 0,
+            function_files: vec![],
+            total_functions_covered: 0,
+            total_functions: 0,
+            total_rate: Some(0.5),
+            file_rates: HashMap::new(),
+            sha: None,
+            threshold_failures: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_thresholds_evaluate_passes_when_no_minimums_set() {
+        let report = report_for_thresholds();
+        let failures = Thresholds::default().evaluate(&report);
+        assert!(failures.is_empty());
+    }
+
+    #[test]
+    fn test_thresholds_evaluate_catches_diff_project_and_file_failures() {
+        let report = report_for_thresholds();
+        let thresholds = Thresholds {
+            diff_min: Some(80.0),
+            project_min: Some(80.0),
+            per_file_min: Some(80.0),
+            path_overrides: vec![],
+        };
+
+        let failures = thresholds.evaluate(&report);
+
+        assert_eq!(failures.len(), 3);
+        assert!(failures
+            .iter()
+            .any(|f| matches!(f, ThresholdFailure::Diff { .. })));
+        assert!(failures
+            .iter()
+            .any(|f| matches!(f, ThresholdFailure::Project { .. })));
+        assert!(failures
+            .iter()
+            .any(|f| matches!(f, ThresholdFailure::File { path, .. } if path == "src/foo.rs")));
+    }
+
+    #[test]
+    fn test_thresholds_evaluate_passes_above_minimums() {
+        let report = report_for_thresholds();
+        let thresholds = Thresholds {
+            diff_min: Some(50.0),
+            project_min: Some(50.0),
+            per_file_min: Some(50.0),
+            path_overrides: vec![],
+        };
+
+        assert!(thresholds.evaluate(&report).is_empty());
+    }
+
+    #[test]
+    fn test_check_project_thresholds_global_failure() {
+        let (summary, files) = sample_summary();
+        let thresholds = Thresholds {
+            project_min: Some(90.0),
+            ..Default::default()
+        };
+
+        let failures = check_project_thresholds(&thresholds, &summary, &files);
+
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(failures[0], ThresholdFailure::Project { .. }));
+    }
+
+    #[test]
+    fn test_check_project_thresholds_per_glob_override_passes_where_global_would_fail() {
+        let (summary, files) = sample_summary();
+        let thresholds = Thresholds {
+            per_file_min: Some(90.0),
+            path_overrides: vec![("src/bad.*".to_string(), 30.0)],
+            ..Default::default()
+        };
+
+        let failures = check_project_thresholds(&thresholds, &summary, &files);
+
+        // src/good.rs (100%) clears the blanket 90% minimum, and src/bad.rs
+        // (40%) clears its 30% override even though it would fail the 90%
+        // per_file_min without it.
+        assert!(failures.is_empty());
+    }
+
+    #[test]
+    fn test_check_project_thresholds_all_pass() {
+        let (summary, files) = sample_summary();
+        let thresholds = Thresholds {
+            project_min: Some(50.0),
+            per_file_min: Some(30.0),
+            ..Default::default()
+        };
+
+        assert!(check_project_thresholds(&thresholds, &summary, &files).is_empty());
+    }
+
+    #[test]
+    fn test_file_min_longest_matching_glob_wins() {
+        let thresholds = Thresholds {
+            per_file_min: Some(50.0),
+            path_overrides: vec![
+                ("src/**".to_string(), 60.0),
+                ("src/bad.rs".to_string(), 10.0),
+            ],
+            ..Default::default()
+        };
+
+        // The exact-path pattern is longer (and more specific) than the
+        // directory wildcard, so it should win over both the shorter glob
+        // and the blanket per_file_min.
+        assert_eq!(thresholds.file_min("src/bad.rs"), Some(10.0));
+        assert_eq!(thresholds.file_min("src/other.rs"), Some(60.0));
+        assert_eq!(thresholds.file_min("tests/foo.rs"), Some(50.0));
+    }
+
+    #[test]
+    fn test_thresholds_parse_config() {
+        let text = "\
+[thresholds]
+# overrides below
+src/**       = 60
+src/hot/**   = 90.5
+";
+        let thresholds = Thresholds::parse_config(text).unwrap();
+
+        assert_eq!(
+            thresholds.path_overrides,
+            vec![
+                ("src/**".to_string(), 60.0),
+                ("src/hot/**".to_string(), 90.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_threshold_failure_display() {
+        let failure = ThresholdFailure::Diff {
+            actual: 60.0,
+            required: 80.0,
+        };
+        assert_eq!(
+            failure.to_string(),
+            "FAILED: diff coverage 60.0% < 80.0%"
+        );
+    }
+
+    #[test]
+    fn test_format_renders_threshold_failures() {
+        let mut report = report_for_thresholds();
+        report.threshold_failures = vec![
+            ThresholdFailure::Diff {
+                actual: 60.0,
+                required: 80.0,
+            },
+            ThresholdFailure::File {
+                path: "src/foo.rs".to_string(),
+                actual: 60.0,
+                required: 80.0,
+            },
+        ];
+
+        let text = report.format(&TextFormatter);
+        assert!(text.contains("FAILED: diff coverage 60.0% < 80.0%"));
+        assert!(text.contains("FAILED: src/foo.rs coverage 60.0% < 80.0%"));
+        assert!(!report.passed());
+
+        let md = report.format(&MarkdownFormatter);
+        assert!(md.contains("> FAILED: diff coverage 60.0% < 80.0%"));
+        assert!(md.contains("`src/foo.rs` ❌"));
+    }
+
+    // -- format_compare tests -------------------------------------------------
+
+    #[test]
+    fn test_format_compare_shows_aggregate_and_per_file_change() {
+        let delta = crate::compare::CoverageDelta {
+            before_rate: 0.5,
+            after_rate: 0.75,
+            before_branch_rate: None,
+            after_branch_rate: None,
+            before_function_rate: None,
+            after_function_rate: None,
+            files: vec![crate::compare::FileCoverageDelta {
+                path: "src/foo.rs".to_string(),
+                before: Some(0.5),
+                after: Some(0.75),
+                branch_before: None,
+                branch_after: None,
+                function_before: None,
+                function_after: None,
+                newly_covered: vec![2, 3],
+                newly_missed: vec![],
+            }],
+        };
+
+        let out = format_compare(&delta);
+
+        assert!(out.contains("Line coverage:     50.0% -> 75.0% (+25.0)"));
+        assert!(out.contains("src/foo.rs  50.0% -> 75.0%"));
+        assert!(out.contains("newly covered: 2-3"));
+        assert!(!out.contains("Branch coverage"));
+    }
+
+    #[test]
+    fn test_format_compare_unchanged_file_is_omitted() {
+        let delta = crate::compare::CoverageDelta {
+            before_rate: 1.0,
+            after_rate: 1.0,
+            before_branch_rate: None,
+            after_branch_rate: None,
+            before_function_rate: None,
+            after_function_rate: None,
+            files: vec![crate::compare::FileCoverageDelta {
+                path: "src/foo.rs".to_string(),
+                before: Some(1.0),
+                after: Some(1.0),
+                branch_before: None,
+                branch_after: None,
+                function_before: None,
+                function_after: None,
+                newly_covered: vec![],
+                newly_missed: vec![],
+            }],
+        };
+
+        let out = format_compare(&delta);
+
+        assert!(!out.contains("src/foo.rs"));
+    }
+
+    #[test]
+    fn test_format_compare_surfaces_per_file_branch_only_change() {
+        // Line coverage is unchanged, but branch coverage improved — the
+        // file must still be listed so a reviewer can localize the
+        // aggregate branch-coverage change to this file.
+        let delta = crate::compare::CoverageDelta {
+            before_rate: 1.0,
+            after_rate: 1.0,
+            before_branch_rate: Some(0.0),
+            after_branch_rate: Some(1.0),
+            before_function_rate: None,
+            after_function_rate: None,
+            files: vec![crate::compare::FileCoverageDelta {
+                path: "src/foo.rs".to_string(),
+                before: Some(1.0),
+                after: Some(1.0),
+                branch_before: Some(0.0),
+                branch_after: Some(1.0),
+                function_before: None,
+                function_after: None,
+                newly_covered: vec![],
+                newly_missed: vec![],
+            }],
+        };
+
+        let out = format_compare(&delta);
+
+        assert!(out.contains("src/foo.rs"));
+        assert!(out.contains("branch:   0.0% -> 100.0%"));
+    }
+
+    #[test]
+    fn test_format_compare_surfaces_file_gaining_branch_data() {
+        // The file had no branches at all in the base report (branch_before
+        // is None, not Some(0.0)) and gained some in head. It's still
+        // listed as changed, and the detail line explains why instead of
+        // showing a bare path with an unchanged line rate.
+        let delta = crate::compare::CoverageDelta {
+            before_rate: 1.0,
+            after_rate: 1.0,
+            before_branch_rate: None,
+            after_branch_rate: Some(1.0),
+            before_function_rate: None,
+            after_function_rate: None,
+            files: vec![crate::compare::FileCoverageDelta {
+                path: "src/foo.rs".to_string(),
+                before: Some(1.0),
+                after: Some(1.0),
+                branch_before: None,
+                branch_after: Some(1.0),
+                function_before: None,
+                function_after: None,
+                newly_covered: vec![],
+                newly_missed: vec![],
+            }],
+        };
+
+        let out = format_compare(&delta);
+
+        assert!(out.contains("src/foo.rs"));
+        assert!(out.contains("branch:   n/a -> 100.0%"));
+    }
 }
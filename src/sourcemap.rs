@@ -0,0 +1,412 @@
+//! Source Map (v3) resolution for coverage collected on generated/transpiled
+//! output (e.g. TypeScript compiled to JS, or bundled/minified JS).
+//!
+//! When an instrumenter only sees the generated file, `FileCoverage.path`
+//! and its line numbers refer to that generated file, not the sources a
+//! diff is computed against. This module loads the source map referenced
+//! by a generated file (a `//# sourceMappingURL=` comment or a sibling
+//! `<file>.map`) and rewrites coverage to point at the original sources.
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::model::{BranchCoverage, CoverageData, FileCoverage, FunctionCoverage, LineCoverage};
+
+#[derive(Deserialize)]
+struct RawSourceMap {
+    #[serde(default)]
+    sources: Vec<String>,
+    mappings: String,
+}
+
+/// A parsed source map, indexed for line-level lookups.
+pub struct SourceMap {
+    sources: Vec<String>,
+    /// For each generated line (0-indexed), the segments on that line in
+    /// the order they appear, each naming a source index and original
+    /// (0-indexed) line.
+    lines: Vec<Vec<(u32, u32)>>,
+}
+
+impl SourceMap {
+    /// Parse a source map from its JSON text.
+    pub fn parse(json: &str) -> Result<Self> {
+        let raw: RawSourceMap = serde_json::from_str(json)?;
+        let lines = decode_mappings(&raw.mappings);
+        Ok(Self {
+            sources: raw.sources,
+            lines,
+        })
+    }
+
+    /// Load the source map referenced by `generated_path`: a trailing
+    /// `//# sourceMappingURL=<url>` comment in its content, falling back to
+    /// a sibling `<file>.map`. Returns `None` if neither is found/readable.
+    pub fn for_generated_file(generated_path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(generated_path).ok()?;
+        let url = content
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix("//# sourceMappingURL="))
+            .map(|s| s.to_string());
+
+        let map_path = match url {
+            Some(url) if !url.starts_with("data:") => {
+                generated_path.parent().unwrap_or(Path::new("")).join(url)
+            }
+            _ => {
+                let mut p = generated_path.as_os_str().to_owned();
+                p.push(".map");
+                PathBuf::from(p)
+            }
+        };
+
+        let json = fs::read_to_string(map_path).ok()?;
+        Self::parse(&json).ok()
+    }
+
+    /// Resolve a generated 1-indexed line number to an (original source
+    /// path, original 1-indexed line) pair, using the first segment on
+    /// that generated line (the nearest preceding segment at column 0).
+    fn resolve(&self, generated_line_1: u32) -> Option<(&str, u32)> {
+        let idx = (generated_line_1 as usize).checked_sub(1)?;
+        let segment = self.lines.get(idx)?.first()?;
+        let (source_index, original_line) = *segment;
+        let source = self.sources.get(source_index as usize)?;
+        Some((source, original_line + 1))
+    }
+}
+
+/// Remap a generated file's coverage to its original sources via its
+/// source map, splitting into one `FileCoverage` per original source. If no
+/// source map can be found, returns the file unchanged.
+///
+/// Locates the map on disk (one generated file at a time, for the ingest
+/// pipeline) then delegates the actual remapping — lines, branches, and
+/// functions alike — to [`apply_map`], so branch coverage on a
+/// source-mapped file isn't silently dropped.
+pub fn remap_file(file: FileCoverage, generated_root: Option<&Path>) -> Vec<FileCoverage> {
+    let generated_path = match generated_root {
+        Some(root) => root.join(&file.path),
+        None => PathBuf::from(&file.path),
+    };
+
+    let map = match SourceMap::for_generated_file(&generated_path) {
+        Some(m) => m,
+        None => return vec![file],
+    };
+
+    apply_map(file, &map)
+}
+
+impl CoverageData {
+    /// Rewrite every file's coverage from generated-file line numbers to
+    /// original-source line numbers, via `resolve` — a lookup from a
+    /// generated file's path to its already-parsed [`SourceMap`]. Files
+    /// `resolve` has no map for pass through unchanged.
+    ///
+    /// Unlike [`remap_file`] (which locates a map on disk one generated
+    /// file at a time, for the ingest pipeline), this is the bulk,
+    /// in-memory entry point: callers that already have parsed maps (e.g.
+    /// fetched alongside a build artifact) can normalize a whole report in
+    /// one pass, turning coverage collected on bundled/transpiled output
+    /// into coverage against the original `.ts`/`.vue`/etc. sources.
+    #[must_use]
+    pub fn apply_source_maps(self, resolve: impl Fn(&str) -> Option<&SourceMap>) -> Self {
+        let mut files = Vec::new();
+        for file in self.files {
+            match resolve(&file.path) {
+                Some(map) => files.extend(apply_map(file, map)),
+                None => files.push(file),
+            }
+        }
+        Self { files, ..self }
+    }
+}
+
+/// Remap every file in `coverage` through the single already-parsed `map`
+/// — the convenience entry point for the common case of one generated
+/// bundle measured against one source map, vs. [`CoverageData::apply_source_maps`]'s
+/// per-file resolver for reports spanning several generated files.
+#[must_use]
+pub fn remap(coverage: CoverageData, map: &SourceMap) -> CoverageData {
+    coverage.apply_source_maps(|_| Some(map))
+}
+
+/// Remap a single file's coverage into per-original-source entries via an
+/// already-parsed `map`, carrying lines, branches, and functions alike.
+/// When multiple generated lines map to the same original line (or
+/// branch), hit counts are merged by taking the max — the original
+/// line/branch counts as covered if any of its generated counterparts
+/// were. Lines/branches/functions with no mapping are dropped.
+fn apply_map(file: FileCoverage, map: &SourceMap) -> Vec<FileCoverage> {
+    let mut by_source: HashMap<String, FileCoverage> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+
+    for line in file.lines {
+        if let Some((source, original_line)) = map.resolve(line.line_number) {
+            let target = by_source.entry(source.to_string()).or_insert_with(|| {
+                order.push(source.to_string());
+                FileCoverage::new(source.to_string())
+            });
+            match target
+                .lines
+                .iter_mut()
+                .find(|l| l.line_number == original_line)
+            {
+                Some(existing) => existing.hit_count = existing.hit_count.max(line.hit_count),
+                None => target.lines.push(LineCoverage {
+                    line_number: original_line,
+                    hit_count: line.hit_count,
+                }),
+            }
+        }
+    }
+
+    for branch in file.branches {
+        if let Some((source, original_line)) = map.resolve(branch.line_number) {
+            let target = by_source.entry(source.to_string()).or_insert_with(|| {
+                order.push(source.to_string());
+                FileCoverage::new(source.to_string())
+            });
+            match target.branches.iter_mut().find(|b| {
+                b.line_number == original_line && b.branch_index == branch.branch_index
+            }) {
+                Some(existing) => existing.hit_count = existing.hit_count.max(branch.hit_count),
+                None => target.branches.push(BranchCoverage {
+                    line_number: original_line,
+                    ..branch
+                }),
+            }
+        }
+    }
+
+    for func in file.functions {
+        let Some(start_line) = func.start_line else {
+            continue;
+        };
+        if let Some((source, original_line)) = map.resolve(start_line) {
+            let target = by_source.entry(source.to_string()).or_insert_with(|| {
+                order.push(source.to_string());
+                FileCoverage::new(source.to_string())
+            });
+            target.functions.push(FunctionCoverage {
+                start_line: Some(original_line),
+                ..func
+            });
+        }
+    }
+
+    order
+        .into_iter()
+        .map(|path| by_source.remove(&path).unwrap())
+        .collect()
+}
+
+/// Decode a source map's `mappings` string into per-generated-line segment
+/// lists. Each segment is `(source_index, original_line)`, both 0-indexed
+/// as stored in the map. Original column and name indices are decoded (to
+/// keep the VLQ stream position correct) but not retained — we only need
+/// line-level granularity.
+fn decode_mappings(mappings: &str) -> Vec<Vec<(u32, u32)>> {
+    let mut lines: Vec<Vec<(u32, u32)>> = Vec::new();
+    let mut current_line: Vec<(u32, u32)> = Vec::new();
+
+    // Running (delta-decoded) state, per the source map v3 spec: fields are
+    // relative to the previous segment, except generated column which
+    // resets to 0 at the start of every line.
+    let mut source_index: i64 = 0;
+    let mut original_line: i64 = 0;
+    let mut original_column: i64 = 0;
+
+    for line_text in mappings.split(';') {
+        current_line.clear();
+        let mut generated_column: i64 = 0;
+
+        for segment_text in line_text.split(',') {
+            if segment_text.is_empty() {
+                continue;
+            }
+            let values = decode_vlq(segment_text);
+            if values.is_empty() {
+                continue;
+            }
+            generated_column += values[0];
+            if values.len() >= 4 {
+                source_index += values[1];
+                original_line += values[2];
+                original_column += values[3];
+                if source_index >= 0 && original_line >= 0 {
+                    current_line.push((source_index as u32, original_line as u32));
+                }
+            }
+        }
+
+        lines.push(current_line.clone());
+    }
+
+    lines
+}
+
+/// Decode a single VLQ (variable-length quantity) segment, base64-encoded
+/// per the source map spec, into its signed field values.
+fn decode_vlq(s: &str) -> Vec<i64> {
+    let mut values = Vec::new();
+    let mut shift = 0u32;
+    let mut result: i64 = 0;
+
+    for c in s.chars() {
+        let digit = match base64_digit(c) {
+            Some(d) => d as i64,
+            None => return values,
+        };
+        let continuation = digit & 0x20;
+        let chunk = digit & 0x1f;
+        result += chunk << shift;
+        shift += 5;
+
+        if continuation == 0 {
+            let negate = result & 1 == 1;
+            let value = result >> 1;
+            values.push(if negate { -value } else { value });
+            result = 0;
+            shift = 0;
+        }
+    }
+
+    values
+}
+
+fn base64_digit(c: char) -> Option<u32> {
+    match c {
+        'A'..='Z' => Some(c as u32 - 'A' as u32),
+        'a'..='z' => Some(c as u32 - 'a' as u32 + 26),
+        '0'..='9' => Some(c as u32 - '0' as u32 + 52),
+        '+' => Some(62),
+        '/' => Some(63),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_vlq_single_values() {
+        // "A" = 0, "C" = 1, "D" = -1 (per the spec's sign-in-low-bit scheme)
+        assert_eq!(decode_vlq("A"), vec![0]);
+        assert_eq!(decode_vlq("C"), vec![1]);
+        assert_eq!(decode_vlq("D"), vec![-1]);
+    }
+
+    #[test]
+    fn test_decode_mappings_single_line() {
+        // "AAAA" decodes to one segment [0,0,0,0]: generated col 0 maps to
+        // source 0, original line 0, original column 0.
+        let lines = decode_mappings("AAAA");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0], vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_resolve_second_line() {
+        // Two generated lines, second line's segment points one source
+        // line further down (relative encoding: delta original_line = +1
+        // encoded as "C" after the initial zero state carries over).
+        let map = SourceMap {
+            sources: vec!["orig.ts".to_string()],
+            lines: vec![vec![(0, 0)], vec![(0, 1)]],
+        };
+        assert_eq!(map.resolve(1), Some(("orig.ts", 1)));
+        assert_eq!(map.resolve(2), Some(("orig.ts", 2)));
+        assert_eq!(map.resolve(3), None);
+    }
+
+    #[test]
+    fn test_apply_source_maps_merges_by_max_and_drops_unmapped() {
+        // Generated lines 1 and 2 both map to original line 1 (e.g. a
+        // statement split across two output lines); generated line 3 has
+        // no mapping at all.
+        let map = SourceMap {
+            sources: vec!["orig.ts".to_string()],
+            lines: vec![vec![(0, 0)], vec![(0, 0)]],
+        };
+
+        let mut file = FileCoverage::new("bundle.js".to_string());
+        file.lines.push(LineCoverage {
+            line_number: 1,
+            hit_count: 0,
+        });
+        file.lines.push(LineCoverage {
+            line_number: 2,
+            hit_count: 5,
+        });
+        file.lines.push(LineCoverage {
+            line_number: 3,
+            hit_count: 9,
+        });
+        file.branches.push(BranchCoverage {
+            line_number: 1,
+            branch_index: 0,
+            hit_count: 0,
+            group_id: None,
+            kind: crate::model::BranchKind::Unknown,
+            arm_line: None,
+        });
+        file.branches.push(BranchCoverage {
+            line_number: 2,
+            branch_index: 0,
+            hit_count: 1,
+            group_id: None,
+            kind: crate::model::BranchKind::Unknown,
+            arm_line: None,
+        });
+
+        let data = CoverageData {
+            files: vec![file],
+            ..Default::default()
+        };
+        let remapped = data.apply_source_maps(|path| if path == "bundle.js" { Some(&map) } else { None });
+
+        assert_eq!(remapped.files.len(), 1);
+        let orig = &remapped.files[0];
+        assert_eq!(orig.path, "orig.ts");
+        // Generated lines 1 & 2 both map to original line 1; the
+        // unmapped generated line 3 is dropped entirely.
+        assert_eq!(orig.lines.len(), 1);
+        assert_eq!(orig.lines[0].line_number, 1);
+        assert_eq!(orig.lines[0].hit_count, 5); // max(0, 5)
+
+        assert_eq!(orig.branches.len(), 1);
+        assert_eq!(orig.branches[0].line_number, 1);
+        assert_eq!(orig.branches[0].hit_count, 1); // max(0, 1)
+    }
+
+    #[test]
+    fn test_remap_applies_one_map_to_every_file() {
+        let map = SourceMap {
+            sources: vec!["orig.ts".to_string()],
+            lines: vec![vec![(0, 0)]],
+        };
+
+        let mut file = FileCoverage::new("bundle.js".to_string());
+        file.lines.push(LineCoverage {
+            line_number: 1,
+            hit_count: 3,
+        });
+
+        let data = CoverageData {
+            files: vec![file],
+            ..Default::default()
+        };
+        let remapped = remap(data, &map);
+
+        assert_eq!(remapped.files.len(), 1);
+        assert_eq!(remapped.files[0].path, "orig.ts");
+        assert_eq!(remapped.files[0].lines[0].hit_count, 3);
+    }
+}
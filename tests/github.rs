@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use covrs::github::{Context, ReplayTransport, ReviewPlatform};
+use covrs::model::Annotation;
+
+fn fixture(name: &str) -> ReplayTransport {
+    ReplayTransport::load(&Path::new("tests/recordings").join(name)).unwrap()
+}
+
+/// `from_env` resolves the PR head SHA by querying the Pulls API (see
+/// `fetch_pr_head_sha`), not from `GITHUB_SHA`.
+#[test]
+fn from_env_resolves_head_sha_via_api() {
+    std::env::set_var("GITHUB_TOKEN", "test-token");
+    std::env::set_var("GITHUB_REPOSITORY", "acme/widgets");
+    std::env::set_var("GITHUB_REF", "refs/pull/42/merge");
+    std::env::remove_var("GITHUB_SHA");
+
+    let ctx = Context::from_env_with_transport(fixture("sha_resolution.json")).unwrap();
+    assert_eq!(ctx.sha(), Some("abcdef0123456789"));
+
+    std::env::remove_var("GITHUB_TOKEN");
+    std::env::remove_var("GITHUB_REPOSITORY");
+    std::env::remove_var("GITHUB_REF");
+}
+
+/// When no existing covrs comment is found, `post_comment` creates a new one.
+#[test]
+fn post_comment_creates_when_no_existing_comment() {
+    let ctx = Context::for_testing("acme/widgets", 7, None, fixture("comment_create.json"));
+    ctx.post_comment("fresh coverage report").unwrap();
+}
+
+/// When a covrs comment already exists (found by its hidden marker),
+/// `post_comment` updates it in place instead of creating a duplicate.
+#[test]
+fn post_comment_updates_existing_comment() {
+    let ctx = Context::for_testing("acme/widgets", 7, None, fixture("comment_update.json"));
+    ctx.post_comment("updated coverage report").unwrap();
+}
+
+/// More than 50 annotations must be submitted as a create (first 50) followed
+/// by a PATCH for the remainder, with the PATCH marking the check run
+/// completed.
+#[test]
+fn post_annotations_batches_over_fifty() {
+    let annotations: Vec<Annotation> = (1..=60)
+        .map(|line| Annotation {
+            path: "src/lib.rs".to_string(),
+            start_line: line,
+            end_line: line,
+            message: "uncovered".to_string(),
+        })
+        .collect();
+
+    let ctx = Context::for_testing(
+        "acme/widgets",
+        7,
+        Some("deadbeef".to_string()),
+        fixture("check_run_multi_batch.json"),
+    );
+    ctx.post_annotations(&annotations).unwrap();
+}
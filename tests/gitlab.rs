@@ -0,0 +1,58 @@
+use std::path::Path;
+
+use covrs::github::{ReplayTransport, ReviewPlatform};
+use covrs::gitlab::Context;
+use covrs::model::Annotation;
+
+const API_URL: &str = "https://gitlab.example.com/api/v4";
+
+fn fixture(name: &str) -> ReplayTransport {
+    ReplayTransport::load(&Path::new("tests/recordings").join(name)).unwrap()
+}
+
+/// `fetch_diff` reconstructs a unified diff from the MR changes API, which
+/// returns hunks without `--- a/...`/`+++ b/...` headers.
+#[test]
+fn fetch_diff_reconstructs_unified_diff() {
+    let ctx = Context::for_testing(API_URL, "42", 7, None, fixture("gitlab_changes.json"));
+    let diff = ctx.fetch_diff().unwrap();
+    assert!(diff.contains("+++ b/src/foo.rs"));
+}
+
+/// When no existing covrs discussion is found, `post_comment` creates one.
+#[test]
+fn post_comment_creates_when_no_existing_comment() {
+    let ctx = Context::for_testing(API_URL, "42", 7, None, fixture("gitlab_comment_create.json"));
+    ctx.post_comment("fresh coverage report").unwrap();
+}
+
+/// When a covrs discussion already exists (found by its hidden marker),
+/// `post_comment` updates the note in place instead of creating a duplicate.
+#[test]
+fn post_comment_updates_existing_comment() {
+    let ctx = Context::for_testing(API_URL, "42", 7, None, fixture("gitlab_comment_update.json"));
+    ctx.post_comment("updated coverage report").unwrap();
+}
+
+/// GitLab has no Check Runs equivalent, so each annotation is posted as its
+/// own diff discussion.
+#[test]
+fn post_annotations_posts_one_discussion_per_annotation() {
+    let annotations: Vec<Annotation> = (1..=3)
+        .map(|line| Annotation {
+            path: "src/lib.rs".to_string(),
+            start_line: line,
+            end_line: line,
+            message: "uncovered".to_string(),
+        })
+        .collect();
+
+    let ctx = Context::for_testing(
+        API_URL,
+        "42",
+        7,
+        Some("deadbeef".to_string()),
+        fixture("gitlab_annotations.json"),
+    );
+    ctx.post_annotations(&annotations).unwrap();
+}
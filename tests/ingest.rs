@@ -18,13 +18,13 @@ fn ingest_lcov_file_auto_detect() {
     .unwrap();
 
     let (report_id, format, name) =
-        covrs::ingest::ingest(&mut conn, &lcov_path, None, None, false, None).unwrap();
+        covrs::ingest::ingest(&mut conn, &lcov_path, None, None, false, None, None, false, false, None).unwrap();
 
     assert!(report_id > 0);
     assert_eq!(format, Format::Lcov);
     assert_eq!(name, "coverage.lcov");
 
-    let summary = covrs::db::get_summary(&conn).unwrap();
+    let summary = covrs::db::get_summary(&conn, covrs::db::MergeMode::Union).unwrap();
     assert_eq!(summary.total_lines, 2);
     assert_eq!(summary.covered_lines, 1);
 }
@@ -40,12 +40,12 @@ fn ingest_cobertura_file_auto_detect() {
     f.write_all(fixture).unwrap();
 
     let (report_id, format, _name) =
-        covrs::ingest::ingest(&mut conn, &xml_path, None, None, false, None).unwrap();
+        covrs::ingest::ingest(&mut conn, &xml_path, None, None, false, None, None, false, false, None).unwrap();
 
     assert!(report_id > 0);
     assert_eq!(format, Format::Cobertura);
 
-    let summary = covrs::db::get_summary(&conn).unwrap();
+    let summary = covrs::db::get_summary(&conn, covrs::db::MergeMode::Union).unwrap();
     assert!(summary.total_lines > 0);
     assert!(summary.total_files > 0);
 }
@@ -59,7 +59,7 @@ fn ingest_with_format_override() {
     std::fs::write(&lcov_path, b"SF:/src/lib.rs\nDA:1,1\nend_of_record\n").unwrap();
 
     let (_id, format, _name) =
-        covrs::ingest::ingest(&mut conn, &lcov_path, Some("lcov"), None, false, None).unwrap();
+        covrs::ingest::ingest(&mut conn, &lcov_path, Some("lcov"), None, false, None, None, false, false, None).unwrap();
 
     assert_eq!(format, Format::Lcov);
 }
@@ -72,7 +72,7 @@ fn ingest_with_custom_report_name() {
     std::fs::write(&lcov_path, b"SF:/src/lib.rs\nDA:1,1\nend_of_record\n").unwrap();
 
     let (_id, _format, name) =
-        covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("my-report"), false, None).unwrap();
+        covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("my-report"), false, None, None, false, false, None).unwrap();
 
     assert_eq!(name, "my-report");
 
@@ -87,7 +87,7 @@ fn ingest_unknown_format_fails() {
     let path = dir.path().join("random.dat");
     std::fs::write(&path, b"hello world this is not coverage data").unwrap();
 
-    let result = covrs::ingest::ingest(&mut conn, &path, None, None, false, None);
+    let result = covrs::ingest::ingest(&mut conn, &path, None, None, false, None, None, false, false, None);
     assert!(result.is_err());
 }
 
@@ -99,10 +99,10 @@ fn ingest_duplicate_name_fails() {
     std::fs::write(&lcov_path, b"SF:/src/lib.rs\nDA:1,1\nend_of_record\n").unwrap();
 
     // First ingest succeeds
-    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("dup"), false, None).unwrap();
+    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("dup"), false, None, None, false, false, None).unwrap();
 
     // Second ingest with same name should fail without --overwrite
-    let result = covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("dup"), false, None);
+    let result = covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("dup"), false, None, None, false, false, None);
     assert!(result.is_err());
     let err_msg = format!("{}", result.unwrap_err());
     assert!(err_msg.contains("already exists"), "Error: {}", err_msg);
@@ -119,9 +119,9 @@ fn ingest_overwrite_replaces_report() {
     )
     .unwrap();
 
-    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("report"), false, None).unwrap();
+    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("report"), false, None, None, false, false, None).unwrap();
 
-    let summary = covrs::db::get_summary(&conn).unwrap();
+    let summary = covrs::db::get_summary(&conn, covrs::db::MergeMode::Union).unwrap();
     assert_eq!(summary.total_lines, 2);
     assert_eq!(summary.covered_lines, 1);
 
@@ -133,9 +133,9 @@ fn ingest_overwrite_replaces_report() {
     )
     .unwrap();
 
-    covrs::ingest::ingest(&mut conn, &lcov_path2, None, Some("report"), true, None).unwrap();
+    covrs::ingest::ingest(&mut conn, &lcov_path2, None, Some("report"), true, None, None, false, false, None).unwrap();
 
-    let summary = covrs::db::get_summary(&conn).unwrap();
+    let summary = covrs::db::get_summary(&conn, covrs::db::MergeMode::Union).unwrap();
     assert_eq!(summary.total_lines, 3);
     assert_eq!(summary.covered_lines, 3);
 }
@@ -149,7 +149,7 @@ fn ingest_empty_coverage_file() {
 
     // Should succeed (with a warning to stderr) but produce a report with 0 files
     let (report_id, _format, _name) =
-        covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("empty"), false, None).unwrap();
+        covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("empty"), false, None, None, false, false, None).unwrap();
     assert!(report_id > 0);
 
     // Verify the report was created even though it has no coverage data
@@ -173,9 +173,9 @@ fn ingest_strips_absolute_paths_with_root() {
     .unwrap();
 
     let root = Path::new("/home/user/project");
-    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("test"), false, Some(root)).unwrap();
+    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("test"), false, Some(root), None, false, false, None).unwrap();
 
-    let files = covrs::db::get_file_summaries(&conn).unwrap();
+    let files = covrs::db::get_file_summaries(&conn, covrs::db::MergeMode::Union).unwrap();
     let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
     assert!(paths.contains(&"src/main.rs"), "paths: {paths:?}");
     assert!(paths.contains(&"src/lib.rs"), "paths: {paths:?}");
@@ -189,9 +189,9 @@ fn ingest_leaves_relative_paths_unchanged() {
     std::fs::write(&lcov_path, b"SF:src/main.rs\nDA:1,5\nend_of_record\n").unwrap();
 
     let root = Path::new("/home/user/project");
-    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("test"), false, Some(root)).unwrap();
+    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("test"), false, Some(root), None, false, false, None).unwrap();
 
-    let files = covrs::db::get_file_summaries(&conn).unwrap();
+    let files = covrs::db::get_file_summaries(&conn, covrs::db::MergeMode::Union).unwrap();
     assert_eq!(files[0].path, "src/main.rs");
 }
 
@@ -207,9 +207,9 @@ fn ingest_leaves_absolute_paths_outside_root_unchanged() {
     .unwrap();
 
     let root = Path::new("/home/user/project");
-    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("test"), false, Some(root)).unwrap();
+    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("test"), false, Some(root), None, false, false, None).unwrap();
 
-    let files = covrs::db::get_file_summaries(&conn).unwrap();
+    let files = covrs::db::get_file_summaries(&conn, covrs::db::MergeMode::Union).unwrap();
     assert_eq!(files[0].path, "/other/place/lib.rs");
 }
 
@@ -224,9 +224,9 @@ fn ingest_no_root_skips_normalization() {
     )
     .unwrap();
 
-    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("test"), false, None).unwrap();
+    covrs::ingest::ingest(&mut conn, &lcov_path, None, Some("test"), false, None, None, false, false, None).unwrap();
 
-    let files = covrs::db::get_file_summaries(&conn).unwrap();
+    let files = covrs::db::get_file_summaries(&conn, covrs::db::MergeMode::Union).unwrap();
     assert_eq!(files[0].path, "/absolute/path/main.rs");
 }
 
@@ -241,9 +241,9 @@ fn ingest_root_strips_cobertura_absolute_paths() {
     f.write_all(fixture).unwrap();
 
     let root = Path::new("/home/user/project");
-    covrs::ingest::ingest(&mut conn, &xml_path, None, Some("test"), false, Some(root)).unwrap();
+    covrs::ingest::ingest(&mut conn, &xml_path, None, Some("test"), false, Some(root), None, false, false, None).unwrap();
 
-    let files = covrs::db::get_file_summaries(&conn).unwrap();
+    let files = covrs::db::get_file_summaries(&conn, covrs::db::MergeMode::Union).unwrap();
     let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
     // /home/user/project/src/main.py → src/main.py
     assert!(paths.contains(&"src/main.py"), "paths: {paths:?}");